@@ -0,0 +1,1850 @@
+// a doubly linked list without any unsafe
+// the nodes live in a Vec (the slab) and link to each other by their u32 position in it, so the
+// borrow checker can see everything we do, and a bad link is at worst a panic on an index, never
+// a dangling pointer. freed slots go on a free list and get reused.
+//
+// every slot has a generation that goes up each time it's freed. an Index is (slab, slot,
+// generation), so holding on to an Index is like holding a NodeHandle in sixth: it keeps working
+// while its element is in the list, and once the element is gone (even if something else moved
+// into the same slot) it just doesn't resolve any more. every slab gets a unique id when it's
+// made, clones included, and an Index only resolves in the slab that gave it out. the id goes
+// wherever the slab goes, so when a list hands its whole slab over the Indices follow, and the
+// empty slab left behind is a new one none of them match.
+//
+// an ArenaList owns its slab, so it can't relink nodes into another ArenaList (sharing a slab
+// between owners would take a RefCell, or unsafe, and cost us handing out plain &T). its cursor's
+// take_* and insert_list_* move the elements that change lists into the other slab one by one,
+// O(k) like Vec::append, and their Indices stop working. the exception is when one side is the
+// whole list: then the whole slab changes hands in O(1) and every Index goes along with it.
+// ArenaList's append and split_off are built on those, so they copy too, like Vec's.
+//
+// lists that trade nodes should live in one Arena instead (further down). it keeps any number of
+// lists in a single slab, and its append and split_off relink like sixth's, with every Index
+// following its element into the other list.
+#![forbid(unsafe_code)]
+
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    mem,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+// the null link
+const NONE: u32 = u32::MAX;
+
+static NEXT_SLAB_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct ArenaList<T> {
+    // which slab this is, for telling Indices from other lists apart
+    id: u64,
+    slots: Vec<Slot<T>>,
+    front: u32,
+    back: u32,
+    // head of the free list
+    free: u32,
+    // what generation a brand new slot starts at. shrink_to_fit throws away slots, and a slot
+    // that gets made again in the same place must not bring their old Indices back to life
+    fresh_generation: u32,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+#[derive(Clone)]
+enum Entry<T> {
+    Occupied(Node<T>),
+    Free { next_free: u32 },
+}
+
+#[derive(Clone)]
+struct Node<T> {
+    prev: u32,
+    next: u32,
+    elem: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Index {
+    slab: u64,
+    slot: u32,
+    generation: u32,
+}
+
+impl<T> ArenaList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaList {
+            id: NEXT_SLAB_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            slots: Vec::with_capacity(capacity),
+            front: NONE,
+            back: NONE,
+            free: NONE,
+            fresh_generation: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // how many elements fit in all before the slab has to grow, the ones already in it included
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let free_slots = self.slots.len() - self.len;
+        self.slots.reserve(additional.saturating_sub(free_slots));
+    }
+
+    // free slots at the end of the slab can go, but the ones in the middle have to stay where
+    // they are (moving a slot would break its Index), so this gets rid of as much as it can
+    pub fn shrink_to_fit(&mut self) {
+        while let Some(last) = self.slots.last()
+            && let Entry::Free { .. } = last.entry
+        {
+            self.fresh_generation = self.fresh_generation.max(last.generation);
+            self.slots.pop();
+        }
+        // the free list could run through any of the slots we just dropped, so it's made again
+        self.free = NONE;
+        for (i, slot) in self.slots.iter_mut().enumerate().rev() {
+            if let Entry::Free { next_free } = &mut slot.entry {
+                *next_free = self.free;
+                self.free = i as u32;
+            }
+        }
+        self.slots.shrink_to_fit();
+    }
+
+    pub fn clear(&mut self) {
+        // nothing can resolve afterwards, so old generations only matter for what comes after
+        // them
+        let newest = self.slots.iter().map(|slot| slot.generation).max();
+        self.fresh_generation = self
+            .fresh_generation
+            .max(newest.map_or(0, |g| g.wrapping_add(1)));
+        self.slots.clear();
+        self.front = NONE;
+        self.back = NONE;
+        self.free = NONE;
+        self.len = 0;
+    }
+
+    // slots
+    // links always point at occupied slots, so finding a free one is a bug in here
+    fn node(&self, i: u32) -> &Node<T> {
+        match &self.slots[i as usize].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("link to a free slot"),
+        }
+    }
+
+    fn node_mut(&mut self, i: u32) -> &mut Node<T> {
+        match &mut self.slots[i as usize].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free { .. } => unreachable!("link to a free slot"),
+        }
+    }
+
+    // a slot for elem, not linked in yet
+    fn alloc(&mut self, elem: T) -> u32 {
+        let node = Entry::Occupied(Node {
+            prev: NONE,
+            next: NONE,
+            elem,
+        });
+        if self.free != NONE {
+            let i = self.free;
+            let slot = &mut self.slots[i as usize];
+            match mem::replace(&mut slot.entry, node) {
+                Entry::Free { next_free } => self.free = next_free,
+                Entry::Occupied(_) => unreachable!("occupied slot on the free list"),
+            }
+            i
+        } else {
+            let i = self.slots.len();
+            assert!(i < NONE as usize, "ArenaList is out of u32 indices");
+            self.slots.push(Slot {
+                generation: self.fresh_generation,
+                entry: node,
+            });
+            i as u32
+        }
+    }
+
+    // the slot has to be unlinked already. a new generation means every Index to it is stale
+    fn dealloc(&mut self, i: u32) -> T {
+        let slot = &mut self.slots[i as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        let entry = mem::replace(
+            &mut slot.entry,
+            Entry::Free {
+                next_free: self.free,
+            },
+        );
+        self.free = i;
+        match entry {
+            Entry::Occupied(node) => node.elem,
+            Entry::Free { .. } => unreachable!("freeing a free slot"),
+        }
+    }
+
+    // links slot i in between prev and next, which are neighbours. NONE for prev or next means
+    // that end of the list
+    fn link_between(&mut self, prev: u32, next: u32, i: u32) {
+        let node = self.node_mut(i);
+        node.prev = prev;
+        node.next = next;
+        if prev == NONE {
+            self.front = i;
+        } else {
+            self.node_mut(prev).next = i;
+        }
+        if next == NONE {
+            self.back = i;
+        } else {
+            self.node_mut(next).prev = i;
+        }
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, i: u32) -> T {
+        self.unlink_keep(i);
+        self.dealloc(i)
+    }
+
+    // takes a node out of the chain but leaves it in its slot, so its Index stays good
+    fn unlink_keep(&mut self, i: u32) {
+        let Node { prev, next, .. } = *self.node(i);
+        if prev == NONE {
+            self.front = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+        if next == NONE {
+            self.back = prev;
+        } else {
+            self.node_mut(next).prev = prev;
+        }
+        self.len -= 1;
+    }
+
+    fn index_of_slot(&self, i: u32) -> Index {
+        Index {
+            slab: self.id,
+            slot: i,
+            generation: self.slots[i as usize].generation,
+        }
+    }
+
+    // the only way from an Index to a slot
+    fn resolve(&self, index: Index) -> Option<u32> {
+        if index.slab != self.id {
+            return None;
+        }
+        let slot = self.slots.get(index.slot as usize)?;
+        match slot.entry {
+            Entry::Occupied(_) if slot.generation == index.generation => Some(index.slot),
+            _ => None,
+        }
+    }
+
+    // the ends
+    pub fn push_front(&mut self, elem: T) -> Index {
+        let i = self.alloc(elem);
+        self.link_between(NONE, self.front, i);
+        self.index_of_slot(i)
+    }
+
+    pub fn push_back(&mut self, elem: T) -> Index {
+        let i = self.alloc(elem);
+        self.link_between(self.back, NONE, i);
+        self.index_of_slot(i)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        (self.front != NONE).then(|| self.unlink(self.front))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        (self.back != NONE).then(|| self.unlink(self.back))
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        (self.front != NONE).then(|| &self.node(self.front).elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        (self.front != NONE).then(|| &mut self.node_mut(self.front).elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        (self.back != NONE).then(|| &self.node(self.back).elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        (self.back != NONE).then(|| &mut self.node_mut(self.back).elem)
+    }
+
+    pub fn front_index(&self) -> Option<Index> {
+        (self.front != NONE).then(|| self.index_of_slot(self.front))
+    }
+
+    pub fn back_index(&self) -> Option<Index> {
+        (self.back != NONE).then(|| self.index_of_slot(self.back))
+    }
+
+    // by Index
+    pub fn contains_index(&self, index: Index) -> bool {
+        self.resolve(index).is_some()
+    }
+
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.resolve(index).map(|i| &self.node(i).elem)
+    }
+
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        self.resolve(index).map(|i| &mut self.node_mut(i).elem)
+    }
+
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        self.resolve(index).map(|i| self.unlink(i))
+    }
+
+    // a stale index hands the element back
+    pub fn insert_before(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        let Some(next) = self.resolve(index) else {
+            return Err(elem);
+        };
+        let i = self.alloc(elem);
+        self.link_between(self.node(next).prev, next, i);
+        Ok(self.index_of_slot(i))
+    }
+
+    pub fn insert_after(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        let Some(prev) = self.resolve(index) else {
+            return Err(elem);
+        };
+        let i = self.alloc(elem);
+        self.link_between(prev, self.node(prev).next, i);
+        Ok(self.index_of_slot(i))
+    }
+
+    // where a slot sits, walking both ways at once until one of them hits an end
+    fn position(&self, i: u32) -> usize {
+        let (mut towards_front, mut towards_back) = (i, i);
+        let mut steps = 0;
+        loop {
+            towards_front = self.node(towards_front).prev;
+            if towards_front == NONE {
+                return steps;
+            }
+            towards_back = self.node(towards_back).next;
+            if towards_back == NONE {
+                return self.len - 1 - steps;
+            }
+            steps += 1;
+        }
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.cursor_mut().insert_list_before(mem::take(other));
+    }
+
+    // everything from index at onwards is returned and we keep [0, at)
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len;
+        assert!(
+            at <= len,
+            "split index (is {at}) should be <= len (is {len})"
+        );
+        // take_after from the one before at, so what stays keeps the slab and its Indices
+        let mut cursor = self.cursor_at_mut(at);
+        cursor.move_prev();
+        cursor.take_after()
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the slab is copied as is but gets its own id. once the two go their own ways the same slot and
+// generation can hold different elements in each, so no Index works on both
+impl<T: Clone> Clone for ArenaList<T> {
+    fn clone(&self) -> Self {
+        ArenaList {
+            id: NEXT_SLAB_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            slots: self.slots.clone(),
+            front: self.front,
+            back: self.back,
+            free: self.free,
+            fresh_generation: self.fresh_generation,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArenaList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for ArenaList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for ArenaList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a ArenaList<T>,
+    front: u32,
+    back: u32,
+    len: usize,
+}
+
+impl<T> ArenaList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.list.node(self.front);
+            self.len -= 1;
+            self.front = node.next;
+            Some(&node.elem)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.list.node(self.back);
+            self.len -= 1;
+            self.back = node.prev;
+            Some(&node.elem)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// safe code can't hand out &mut to elements of the slab in list order straight from the Vec, so
+// iter_mut splits the slab up front into one (links, &mut elem) per slot and walks the links
+// through that. it costs an allocation the size of the slab
+pub struct IterMut<'a, T> {
+    slots: Vec<Option<(u32, u32, &'a mut T)>>,
+    front: u32,
+    back: u32,
+    len: usize,
+}
+
+impl<T> ArenaList<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            slots: self
+                .slots
+                .iter_mut()
+                .map(|slot| match &mut slot.entry {
+                    Entry::Occupied(node) => Some((node.prev, node.next, &mut node.elem)),
+                    Entry::Free { .. } => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let (_, next, elem) = self.slots[self.front as usize].take().unwrap();
+            self.len -= 1;
+            self.front = next;
+            Some(elem)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let (prev, _, elem) = self.slots[self.back as usize].take().unwrap();
+            self.len -= 1;
+            self.back = prev;
+            Some(elem)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: ArenaList<T>,
+}
+
+impl<T> IntoIterator for ArenaList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// cursors
+// the same ghost as sixth: walking off either end puts us on the ghost (cur is NONE), and from
+// the ghost we come back in at the other end
+pub struct Cursor<'a, T> {
+    list: &'a ArenaList<T>,
+    cur: u32,
+    index: Option<usize>,
+}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            list: self.list,
+            cur: self.cur,
+            index: self.index,
+        }
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    list: &'a mut ArenaList<T>,
+    cur: u32,
+    index: Option<usize>,
+}
+
+impl<T> ArenaList<T> {
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            cur: NONE,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.move_prev();
+        cursor
+    }
+
+    pub fn cursor_at(&self, index: usize) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.seek(index);
+        cursor
+    }
+
+    // on the element index points at, None if it's stale
+    pub fn cursor_from(&self, index: Index) -> Option<Cursor<'_, T>> {
+        let cur = self.resolve(index)?;
+        Some(Cursor {
+            list: self,
+            cur,
+            index: Some(self.position(cur)),
+        })
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            list: self,
+            cur: NONE,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.seek(index);
+        cursor
+    }
+
+    pub fn cursor_from_mut(&mut self, index: Index) -> Option<CursorMut<'_, T>> {
+        let cur = self.resolve(index)?;
+        let index = Some(self.position(cur));
+        Some(CursorMut {
+            list: self,
+            cur,
+            index,
+        })
+    }
+}
+
+// moving is the same for both cursors, so it's written once over the list and the position
+fn step_next<T>(list: &ArenaList<T>, cur: &mut u32, index: &mut Option<usize>) {
+    if *cur != NONE {
+        *cur = list.node(*cur).next;
+        *index = if *cur == NONE {
+            None
+        } else {
+            index.map(|index| index + 1)
+        };
+    } else if !list.is_empty() {
+        *cur = list.front;
+        *index = Some(0);
+    }
+}
+
+fn step_prev<T>(list: &ArenaList<T>, cur: &mut u32, index: &mut Option<usize>) {
+    if *cur != NONE {
+        *cur = list.node(*cur).prev;
+        *index = index.and_then(|index| index.checked_sub(1));
+    } else if !list.is_empty() {
+        *cur = list.back;
+        *index = Some(list.len - 1);
+    }
+}
+
+// index == len is the ghost. walks from whichever is closest: the front, the back or where we
+// are now
+fn seek_to<T>(list: &ArenaList<T>, cur: &mut u32, index: &mut Option<usize>, to: usize) {
+    let len = list.len;
+    assert!(
+        to <= len,
+        "cursor index (is {to}) should be <= len (is {len})"
+    );
+    let from = match *index {
+        Some(from) if from.abs_diff(to) < to.min(len - to) => from,
+        _ => {
+            *cur = NONE;
+            *index = None;
+            // the ghost sits at both -1 and len
+            if to < len - to {
+                step_next(list, cur, index);
+                0
+            } else {
+                len
+            }
+        }
+    };
+    for _ in to..from {
+        step_prev(list, cur, index);
+    }
+    for _ in from..to {
+        step_next(list, cur, index);
+    }
+}
+
+fn peek_slot<T>(list: &ArenaList<T>, cur: u32, forward: bool) -> u32 {
+    match (cur == NONE, forward) {
+        (true, true) => list.front,
+        (true, false) => list.back,
+        (false, true) => list.node(cur).next,
+        (false, false) => list.node(cur).prev,
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        step_next(self.list, &mut self.cur, &mut self.index);
+    }
+
+    pub fn move_prev(&mut self) {
+        step_prev(self.list, &mut self.cur, &mut self.index);
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        seek_to(self.list, &mut self.cur, &mut self.index, index);
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        (self.cur != NONE).then(|| &self.list.node(self.cur).elem)
+    }
+
+    pub fn current_index(&self) -> Option<Index> {
+        (self.cur != NONE).then(|| self.list.index_of_slot(self.cur))
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = peek_slot(self.list, self.cur, true);
+        (next != NONE).then(|| &self.list.node(next).elem)
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = peek_slot(self.list, self.cur, false);
+        (prev != NONE).then(|| &self.list.node(prev).elem)
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self.list,
+            cur: self.cur,
+            index: self.index,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        step_next(self.list, &mut self.cur, &mut self.index);
+    }
+
+    pub fn move_prev(&mut self) {
+        step_prev(self.list, &mut self.cur, &mut self.index);
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        seek_to(self.list, &mut self.cur, &mut self.index, index);
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        (self.cur != NONE).then(|| &mut self.list.node_mut(self.cur).elem)
+    }
+
+    pub fn current_index(&self) -> Option<Index> {
+        (self.cur != NONE).then(|| self.list.index_of_slot(self.cur))
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = peek_slot(self.list, self.cur, true);
+        (next != NONE).then(|| &mut self.list.node_mut(next).elem)
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = peek_slot(self.list, self.cur, false);
+        (prev != NONE).then(|| &mut self.list.node_mut(prev).elem)
+    }
+
+    // on the ghost insert_before puts the element at the back and insert_after at the front
+    pub fn insert_before(&mut self, elem: T) -> Index {
+        let i = self.list.alloc(elem);
+        let prev = peek_slot(self.list, self.cur, false);
+        self.list.link_between(prev, self.cur, i);
+        if let Some(index) = self.index.as_mut() {
+            *index += 1;
+        }
+        self.list.index_of_slot(i)
+    }
+
+    pub fn insert_after(&mut self, elem: T) -> Index {
+        let i = self.list.alloc(elem);
+        let next = peek_slot(self.list, self.cur, true);
+        self.list.link_between(self.cur, next, i);
+        self.list.index_of_slot(i)
+    }
+
+    // takes out the current element and moves on to the next one, which takes over its index
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.cur == NONE {
+            return None;
+        }
+        let next = self.list.node(self.cur).next;
+        let elem = self.list.unlink(self.cur);
+        self.cur = next;
+        if next == NONE {
+            self.index = None;
+        }
+        Some(elem)
+    }
+
+    // the current node moves to an end and we go with it
+    pub fn move_current_to_front(&mut self) {
+        if self.cur != NONE {
+            self.list.unlink_keep(self.cur);
+            self.list.link_between(NONE, self.list.front, self.cur);
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_current_to_back(&mut self) {
+        if self.cur != NONE {
+            self.list.unlink_keep(self.cur);
+            self.list.link_between(self.list.back, NONE, self.cur);
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    // take_before moves everything before us out into a new list and we end up at index 0,
+    // take_after everything after us. that's a pop and a push per element, not sixth's
+    // relinking split. on the ghost either one takes the whole list, and that's the one case
+    // where the slab itself changes hands
+    pub fn take_before(&mut self) -> ArenaList<T> {
+        let Some(count) = self.index else {
+            return mem::take(self.list);
+        };
+        let mut output = ArenaList::with_capacity(count);
+        for _ in 0..count {
+            output.push_back(self.list.pop_front().unwrap());
+        }
+        self.index = Some(0);
+        output
+    }
+
+    pub fn take_after(&mut self) -> ArenaList<T> {
+        let Some(index) = self.index else {
+            return mem::take(self.list);
+        };
+        let count = self.list.len - index - 1;
+        let mut output = ArenaList::with_capacity(count);
+        for _ in 0..count {
+            output.push_front(self.list.pop_back().unwrap());
+        }
+        output
+    }
+
+    // the input moves into our slab element by element, unless we are empty and can just take its
+    // slab over
+    pub fn insert_list_before(&mut self, input: ArenaList<T>) {
+        if self.list.is_empty() {
+            *self.list = input;
+            return;
+        }
+        self.list.reserve(input.len);
+        for elem in input {
+            self.insert_before(elem);
+        }
+    }
+
+    pub fn insert_list_after(&mut self, input: ArenaList<T>) {
+        if self.list.is_empty() {
+            *self.list = input;
+            return;
+        }
+        self.list.reserve(input.len);
+        for elem in input.into_iter().rev() {
+            self.insert_after(elem);
+        }
+    }
+}
+
+// many lists in one slab
+// an ArenaList can't relink its nodes into another list because each one owns its slab. an Arena
+// owns one slab and any number of lists in it, each known by a ListId, so moving nodes between
+// them is only relinking, like sixth: nothing is copied or allocated, and an Index belongs to the
+// arena rather than to a list, so it keeps working wherever its element goes.
+//
+// all a list needs is its front, back and len (a chain), and removing a node by its Index has to
+// fix those up for whichever list it is in. so every slot notes which chain it's on, and moving
+// nodes means retagging them. append and split_off retag whichever side is shorter, and if that's
+// the side that stays put the two chains trade lists instead, so they cost O(min) like std's
+// split_off. the linking itself is ArenaList's own code, run on the slab with the chain lent to
+// it (see on_chain)
+pub struct Arena<T> {
+    // only here for its slab, its own front, back and len are empty outside of on_chain
+    slab: ArenaList<T>,
+    // which chain each slot is on, only means something for occupied ones
+    chain_of: Vec<u32>,
+    chains: Vec<Chain>,
+    free_chains: Vec<u32>,
+    lists: Vec<ListSlot>,
+    free_lists: Vec<u32>,
+}
+
+#[derive(Clone, Copy)]
+struct Chain {
+    front: u32,
+    back: u32,
+    len: usize,
+    // the list slot it belongs to
+    list: u32,
+}
+
+struct ListSlot {
+    generation: u32,
+    // NONE once the list is gone
+    chain: u32,
+}
+
+// same idea as Index: it stops working once its list is removed, even if a new list gets the slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListId {
+    arena: u64,
+    slot: u32,
+    generation: u32,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    // capacity is in elements, over all the lists
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            slab: ArenaList::with_capacity(capacity),
+            chain_of: Vec::with_capacity(capacity),
+            chains: Vec::new(),
+            free_chains: Vec::new(),
+            lists: Vec::new(),
+            free_lists: Vec::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slab.capacity()
+    }
+
+    // the lists
+    pub fn new_list(&mut self) -> ListId {
+        let chain = self.new_chain();
+        let slot = match self.free_lists.pop() {
+            Some(slot) => {
+                self.lists[slot as usize].chain = chain;
+                slot
+            }
+            None => {
+                assert!(self.lists.len() < NONE as usize, "Arena is out of lists");
+                self.lists.push(ListSlot {
+                    generation: 0,
+                    chain,
+                });
+                self.lists.len() as u32 - 1
+            }
+        };
+        self.chains[chain as usize].list = slot;
+        self.list_id(slot)
+    }
+
+    // drops the list and everything in it. its ListId and the Indices of its elements stop
+    // working
+    pub fn remove_list(&mut self, list: ListId) {
+        let chain = self.chain(list);
+        // one by one, anything that takes the slab's whole list would take the whole slab
+        self.on_chain(chain, |slab| while slab.pop_front().is_some() {});
+        let slot = &mut self.lists[list.slot as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.chain = NONE;
+        self.free_lists.push(list.slot);
+        self.free_chains.push(chain);
+    }
+
+    pub fn contains_list(&self, list: ListId) -> bool {
+        self.try_chain(list).is_some()
+    }
+
+    pub fn len(&self, list: ListId) -> usize {
+        self.chains[self.chain(list) as usize].len
+    }
+
+    pub fn is_empty(&self, list: ListId) -> bool {
+        self.len(list) == 0
+    }
+
+    // the list an element is in right now
+    pub fn list_of(&self, index: Index) -> Option<ListId> {
+        let i = self.slab.resolve(index)?;
+        let chain = self.chain_of[i as usize];
+        Some(self.list_id(self.chains[chain as usize].list))
+    }
+
+    fn list_id(&self, slot: u32) -> ListId {
+        ListId {
+            arena: self.slab.id,
+            slot,
+            generation: self.lists[slot as usize].generation,
+        }
+    }
+
+    fn try_chain(&self, list: ListId) -> Option<u32> {
+        if list.arena != self.slab.id {
+            return None;
+        }
+        let slot = self.lists.get(list.slot as usize)?;
+        (slot.generation == list.generation && slot.chain != NONE).then_some(slot.chain)
+    }
+
+    // a ListId that isn't a list here (any more) is a bug on the caller's side, like indexing a
+    // Vec out of bounds, so it panics
+    fn chain(&self, list: ListId) -> u32 {
+        match self.try_chain(list) {
+            Some(chain) => chain,
+            None => panic!("{list:?} is not a list in this arena"),
+        }
+    }
+
+    fn new_chain(&mut self) -> u32 {
+        let chain = Chain {
+            front: NONE,
+            back: NONE,
+            len: 0,
+            list: NONE,
+        };
+        match self.free_chains.pop() {
+            Some(i) => {
+                self.chains[i as usize] = chain;
+                i
+            }
+            None => {
+                self.chains.push(chain);
+                self.chains.len() as u32 - 1
+            }
+        }
+    }
+
+    // runs ArenaList code on one of our chains: the slab gets the chain's ends and len for the
+    // duration and hands them back afterwards, whatever f did to them
+    fn on_chain<R>(&mut self, chain: u32, f: impl FnOnce(&mut ArenaList<T>) -> R) -> R {
+        let c = self.chains[chain as usize];
+        (self.slab.front, self.slab.back, self.slab.len) = (c.front, c.back, c.len);
+        let result = f(&mut self.slab);
+        let c = &mut self.chains[chain as usize];
+        c.front = mem::replace(&mut self.slab.front, NONE);
+        c.back = mem::replace(&mut self.slab.back, NONE);
+        c.len = mem::take(&mut self.slab.len);
+        result
+    }
+
+    // puts count nodes from front on chain
+    fn retag(&mut self, mut i: u32, count: usize, chain: u32) {
+        for _ in 0..count {
+            self.chain_of[i as usize] = chain;
+            i = self.slab.node(i).next;
+        }
+    }
+
+    // a new node is on chain
+    fn tag(&mut self, index: Index, chain: u32) -> Index {
+        let i = index.slot as usize;
+        if i >= self.chain_of.len() {
+            self.chain_of.resize(i + 1, NONE);
+        }
+        self.chain_of[i] = chain;
+        index
+    }
+
+    // the ends
+    pub fn push_front(&mut self, list: ListId, elem: T) -> Index {
+        let chain = self.chain(list);
+        let index = self.on_chain(chain, |slab| slab.push_front(elem));
+        self.tag(index, chain)
+    }
+
+    pub fn push_back(&mut self, list: ListId, elem: T) -> Index {
+        let chain = self.chain(list);
+        let index = self.on_chain(chain, |slab| slab.push_back(elem));
+        self.tag(index, chain)
+    }
+
+    pub fn pop_front(&mut self, list: ListId) -> Option<T> {
+        let chain = self.chain(list);
+        self.on_chain(chain, |slab| slab.pop_front())
+    }
+
+    pub fn pop_back(&mut self, list: ListId) -> Option<T> {
+        let chain = self.chain(list);
+        self.on_chain(chain, |slab| slab.pop_back())
+    }
+
+    pub fn front(&self, list: ListId) -> Option<&T> {
+        let front = self.chains[self.chain(list) as usize].front;
+        (front != NONE).then(|| &self.slab.node(front).elem)
+    }
+
+    pub fn back(&self, list: ListId) -> Option<&T> {
+        let back = self.chains[self.chain(list) as usize].back;
+        (back != NONE).then(|| &self.slab.node(back).elem)
+    }
+
+    // by Index, whatever list the element is in
+    pub fn contains_index(&self, index: Index) -> bool {
+        self.slab.contains_index(index)
+    }
+
+    pub fn get(&self, index: Index) -> Option<&T> {
+        self.slab.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        self.slab.get_mut(index)
+    }
+
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        let i = self.slab.resolve(index)?;
+        let chain = self.chain_of[i as usize];
+        self.on_chain(chain, |slab| slab.remove(index))
+    }
+
+    // the new element goes in the same list as the one at index
+    pub fn insert_before(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        let Some(i) = self.slab.resolve(index) else {
+            return Err(elem);
+        };
+        let chain = self.chain_of[i as usize];
+        let new = self.on_chain(chain, |slab| slab.insert_before(index, elem))?;
+        Ok(self.tag(new, chain))
+    }
+
+    pub fn insert_after(&mut self, index: Index, elem: T) -> Result<Index, T> {
+        let Some(i) = self.slab.resolve(index) else {
+            return Err(elem);
+        };
+        let chain = self.chain_of[i as usize];
+        let new = self.on_chain(chain, |slab| slab.insert_after(index, elem))?;
+        Ok(self.tag(new, chain))
+    }
+
+    // relinking
+    // moves everything in src onto the back of dst, src is left empty
+    pub fn append(&mut self, dst: ListId, src: ListId) {
+        assert_ne!(dst, src, "can't append a list to itself");
+        let (a, b) = (self.chain(dst), self.chain(src));
+        let (ca, cb) = (self.chains[a as usize], self.chains[b as usize]);
+        if cb.len == 0 {
+            return;
+        }
+        if ca.len != 0 {
+            self.slab.node_mut(ca.back).next = cb.front;
+            self.slab.node_mut(cb.front).prev = ca.back;
+        }
+        let merged = Chain {
+            front: if ca.len == 0 { cb.front } else { ca.front },
+            back: cb.back,
+            len: ca.len + cb.len,
+            list: dst.slot,
+        };
+        let empty = Chain {
+            front: NONE,
+            back: NONE,
+            len: 0,
+            list: src.slot,
+        };
+        // the shorter one gets retagged, if that's dst the chains trade lists
+        let (keep, other) = if ca.len >= cb.len {
+            self.retag(cb.front, cb.len, a);
+            (a, b)
+        } else {
+            self.retag(ca.front, ca.len, b);
+            self.lists[dst.slot as usize].chain = b;
+            self.lists[src.slot as usize].chain = a;
+            (b, a)
+        };
+        self.chains[keep as usize] = merged;
+        self.chains[other as usize] = empty;
+    }
+
+    // everything from index at onwards goes into a new list and we keep [0, at)
+    pub fn split_off(&mut self, list: ListId, at: usize) -> ListId {
+        let chain = self.chain(list);
+        let c = self.chains[chain as usize];
+        let len = c.len;
+        assert!(
+            at <= len,
+            "split index (is {at}) should be <= len (is {len})"
+        );
+        let new = self.new_list();
+        if at == len {
+            return new;
+        }
+        let first = self.on_chain(chain, |slab| slab.cursor_at(at).cur);
+        let last_kept = self.slab.node(first).prev;
+        self.slab.node_mut(first).prev = NONE;
+        if last_kept != NONE {
+            self.slab.node_mut(last_kept).next = NONE;
+        }
+        let head = Chain {
+            front: if at == 0 { NONE } else { c.front },
+            back: last_kept,
+            len: at,
+            list: list.slot,
+        };
+        let tail = Chain {
+            front: first,
+            back: c.back,
+            len: len - at,
+            list: new.slot,
+        };
+        let n = self.chain(new);
+        if tail.len <= head.len {
+            self.retag(tail.front, tail.len, n);
+            self.chains[chain as usize] = head;
+            self.chains[n as usize] = tail;
+        } else {
+            self.retag(head.front, head.len, n);
+            self.chains[n as usize] = head;
+            self.chains[chain as usize] = tail;
+            self.lists[list.slot as usize].chain = n;
+            self.lists[new.slot as usize].chain = chain;
+        }
+        new
+    }
+
+    // iterating one list, the same iterators as ArenaList's. iter_mut splits up the whole slab
+    // like ArenaList's does, so it costs an allocation the size of the arena
+    pub fn iter(&self, list: ListId) -> Iter<'_, T> {
+        let c = self.chains[self.chain(list) as usize];
+        Iter {
+            list: &self.slab,
+            front: c.front,
+            back: c.back,
+            len: c.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self, list: ListId) -> IterMut<'_, T> {
+        let c = self.chains[self.chain(list) as usize];
+        let mut iter = self.slab.iter_mut();
+        (iter.front, iter.back, iter.len) = (c.front, c.back, c.len);
+        iter
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<ArenaList<i32>>();
+    is_sync::<ArenaList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    is_send::<Cursor<i32>>();
+    is_sync::<Cursor<i32>>();
+
+    is_send::<CursorMut<i32>>();
+    is_sync::<CursorMut<i32>>();
+
+    is_send::<Arena<i32>>();
+    is_sync::<Arena<i32>>();
+
+    fn arena_list_covariant<'a, T>(x: ArenaList<&'static T>) -> ArenaList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+    fn cursor_covariant<'i, 'a, T>(x: Cursor<'i, &'static T>) -> Cursor<'i, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Arena, ArenaList, ListId, NONE};
+
+    fn list_from<T: Clone>(v: &[T]) -> ArenaList<T> {
+        v.iter().cloned().collect()
+    }
+
+    fn contents<T: Clone>(list: &ArenaList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    // the links agree both ways, len is right, and every slot is either linked or on the free
+    // list exactly once
+    fn check_links<T>(list: &ArenaList<T>) {
+        let mut prev = NONE;
+        let mut cur = list.front;
+        let mut seen = vec![false; list.slots.len()];
+        while cur != NONE {
+            assert!(!seen[cur as usize]);
+            seen[cur as usize] = true;
+            assert_eq!(list.node(cur).prev, prev);
+            prev = cur;
+            cur = list.node(cur).next;
+        }
+        assert_eq!(list.back, prev);
+        assert_eq!(seen.iter().filter(|&&s| s).count(), list.len);
+        let mut free = list.free;
+        while free != NONE {
+            assert!(!seen[free as usize]);
+            seen[free as usize] = true;
+            match list.slots[free as usize].entry {
+                super::Entry::Free { next_free } => free = next_free,
+                super::Entry::Occupied(_) => panic!("occupied slot on the free list"),
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    // every list's chain is linked right both ways and every node on it is tagged with it, and
+    // the lists plus the free slots make up the whole slab
+    fn check_arena<T>(arena: &Arena<T>) {
+        let mut linked = 0;
+        for (slot, list) in arena.lists.iter().enumerate() {
+            if list.chain == NONE {
+                continue;
+            }
+            let chain = arena.chains[list.chain as usize];
+            assert_eq!(chain.list, slot as u32);
+            let mut prev = NONE;
+            let mut cur = chain.front;
+            let mut len = 0;
+            while cur != NONE {
+                assert_eq!(arena.chain_of[cur as usize], list.chain);
+                assert_eq!(arena.slab.node(cur).prev, prev);
+                prev = cur;
+                cur = arena.slab.node(cur).next;
+                len += 1;
+            }
+            assert_eq!(chain.back, prev);
+            assert_eq!(chain.len, len);
+            linked += len;
+        }
+        let mut free = arena.slab.free;
+        while free != NONE {
+            linked += 1;
+            match arena.slab.slots[free as usize].entry {
+                super::Entry::Free { next_free } => free = next_free,
+                super::Entry::Occupied(_) => panic!("occupied slot on the free list"),
+            }
+        }
+        assert_eq!(linked, arena.slab.slots.len());
+        assert_eq!(arena.slab.len, 0);
+    }
+
+    fn arena_contents<T: Clone>(arena: &Arena<T>, list: ListId) -> Vec<T> {
+        arena.iter(list).cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = ArenaList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_back(2);
+        m.push_front(1);
+        m.push_back(3);
+        assert_eq!(contents(&m), &[1, 2, 3]);
+        *m.front_mut().unwrap() = 10;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(m.front(), Some(&10));
+        assert_eq!(m.back(), Some(&30));
+        for x in &mut m {
+            *x += 1;
+        }
+        assert_eq!(
+            m.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            &[31, 3, 11]
+        );
+        assert_eq!(m.pop_back(), Some(31));
+        assert_eq!(m.pop_front(), Some(11));
+        assert_eq!(m.pop_front(), Some(3));
+        assert!(m.is_empty());
+        check_links(&m);
+
+        m.extend(0..4);
+        assert_eq!(format!("{m:?}"), "[0, 1, 2, 3]");
+        assert_eq!(
+            m.clone().into_iter().rev().collect::<Vec<_>>(),
+            &[3, 2, 1, 0]
+        );
+        assert_eq!(m, list_from(&[0, 1, 2, 3]));
+        assert!(m < list_from(&[0, 1, 3]));
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_indices() {
+        let mut m = ArenaList::new();
+        let a = m.push_back('a');
+        let c = m.push_back('c');
+        let b = m.insert_before(c, 'b').unwrap();
+        let d = m.insert_after(c, 'd').unwrap();
+        assert_eq!(contents(&m), &['a', 'b', 'c', 'd']);
+        assert_eq!(m.front_index(), Some(a));
+        assert_eq!(m.back_index(), Some(d));
+        *m.get_mut(b).unwrap() = 'B';
+        assert_eq!(m.get(b), Some(&'B'));
+
+        assert_eq!(m.remove(b), Some('B'));
+        assert_eq!(m.remove(b), None);
+        assert!(!m.contains_index(b));
+        assert_eq!(m.insert_after(b, 'x'), Err('x'));
+        // b's slot gets used again, but b stays dead
+        let e = m.push_front('e');
+        assert_eq!(e.slot, b.slot);
+        assert_eq!(m.get(b), None);
+        assert_eq!(m.get(e), Some(&'e'));
+
+        // the clone is a slab of its own, indices don't carry over
+        let n = m.clone();
+        assert_eq!(n.get(c), None);
+        assert_eq!(n.front_index().map(|i| n.get(i)), Some(Some(&'e')));
+        assert_eq!(contents(&n), &['e', 'a', 'c', 'd']);
+        check_links(&m);
+
+        m.clear();
+        let f = m.push_back('f');
+        assert_eq!(f.slot, a.slot);
+        assert_eq!(m.get(a), None);
+        assert_eq!(m.get(e), None);
+    }
+
+    #[test]
+    fn test_indices_across_slabs() {
+        // a list that got a whole slab handed over doesn't take the Indices of its old one
+        let mut m = ArenaList::new();
+        let a = m.push_back("a");
+        m.remove(a);
+        m.append(&mut list_from(&["b"]));
+        assert_eq!(m.get(a), None);
+        assert_eq!(contents(&m), &["b"]);
+
+        // and the empty slab left behind is a new one
+        let mut dst = ArenaList::new();
+        let mut other = ArenaList::new();
+        let x = other.push_back("x");
+        dst.append(&mut other);
+        assert_eq!(dst.get(x), Some(&"x"));
+        let y = other.push_back("y");
+        assert_eq!(other.get(x), None);
+        assert_eq!(dst.get(y), None);
+
+        // same when the elements get copied over instead
+        let x = other.push_back("x");
+        dst.append(&mut other);
+        other.push_back("y");
+        assert_eq!(other.get(x), None);
+        assert_eq!(dst.get(x), None);
+        assert_eq!(contents(&dst), &["x", "y", "x"]);
+
+        // clones go their own ways, the same slot and generation is a different element in each
+        let mut a = list_from(&[1]);
+        let mut b = a.clone();
+        let two = a.push_back(2);
+        let three = b.push_back(3);
+        assert_eq!((two.slot, two.generation), (three.slot, three.generation));
+        assert_eq!(b.get(two), None);
+        assert_eq!(a.get(three), None);
+        assert_eq!(a.get(two), Some(&2));
+
+        // an Index from another list never resolves, even on the same slot and generation
+        let n = list_from(&["z"]);
+        assert_eq!(n.get(dst.front_index().unwrap()), None);
+
+        // what stays behind on a split keeps its Indices, the whole list going goes with its own
+        let mut m = ArenaList::new();
+        let first = m.push_back(1);
+        let second = m.push_back(2);
+        let tail = m.split_off(1);
+        assert_eq!(m.get(first), Some(&1));
+        assert_eq!(tail.get(second), None);
+        let whole = m.split_off(0);
+        assert_eq!(whole.get(first), Some(&1));
+        assert_eq!(m.get(first), None);
+        check_links(&m);
+        check_links(&whole);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut m = ArenaList::with_capacity(8);
+        assert!(m.capacity() >= 8);
+        let indices: Vec<_> = (0..8).map(|x| m.push_back(x)).collect();
+        // the total, like Vec's, not what's left
+        let capacity = m.capacity();
+        assert!(capacity >= 8);
+        // freed slots get reused, so taking elements out doesn't change it
+        for &index in &indices[4..] {
+            m.remove(index);
+        }
+        m.remove(indices[1]);
+        assert_eq!(m.capacity(), capacity);
+        m.reserve(5);
+        assert_eq!(m.capacity(), capacity);
+        m.shrink_to_fit();
+        // the free slot in the middle has to stay, only the ones at the end go
+        assert_eq!(m.slots.len(), 4);
+        check_links(&m);
+        assert_eq!(contents(&m), &[0, 2, 3]);
+
+        // new slots where the old ones were don't bring their indices back
+        let x = m.push_back(10);
+        let y = m.push_back(11);
+        assert_eq!(x.slot, indices[1].slot);
+        assert_eq!(y.slot, indices[4].slot);
+        assert_eq!(m.get(indices[4]), None);
+        assert_eq!(m.get(y), Some(&11));
+        assert_eq!(m.get(indices[0]), Some(&0));
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_cursor_move() {
+        let m = list_from(&[0, 1, 2, 3, 4]);
+        let mut cursor = m.cursor();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&0));
+        assert_eq!(cursor.peek_prev(), Some(&4));
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(4));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        for i in [3, 0, 5, 1, 4, 2] {
+            cursor.seek(i);
+            assert_eq!(cursor.index(), (i < 5).then_some(i));
+            assert_eq!(cursor.current(), (i < 5).then_some(&i));
+        }
+        let index = m.cursor_at(3).current_index().unwrap();
+        let from = m.cursor_from(index).unwrap();
+        assert_eq!((from.index(), from.current()), (Some(3), Some(&3)));
+    }
+
+    #[test]
+    fn test_cursor_edit() {
+        let mut m = list_from(&[1, 2, 4]);
+        let mut cursor = m.cursor_at_mut(2);
+        let three = cursor.insert_before(3);
+        assert_eq!(cursor.index(), Some(3));
+        cursor.insert_after(5);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(0);
+        cursor.insert_before(6);
+        assert_eq!(contents(&m), &[0, 1, 2, 3, 4, 5, 6]);
+        check_links(&m);
+
+        let mut cursor = m.cursor_from_mut(three).unwrap();
+        assert_eq!(cursor.index(), Some(3));
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current_index(), Some(three));
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), Some(6));
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(contents(&m), &[0, 1, 2, 4, 5, 6]);
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_take_insert_list() {
+        let mut m = list_from(&[0, 1, 2, 3, 4, 5]);
+        let mut cursor = m.cursor_at_mut(2);
+        let before = cursor.take_before();
+        assert_eq!(cursor.index(), Some(0));
+        let after = cursor.take_after();
+        assert_eq!(contents(&before), &[0, 1]);
+        assert_eq!(contents(&after), &[3, 4, 5]);
+        cursor.insert_list_after(after);
+        cursor.insert_list_before(before);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(contents(&m), &[0, 1, 2, 3, 4, 5]);
+        check_links(&m);
+
+        // a whole list changes hands with its slab, so its indices keep working
+        let mut n = ArenaList::new();
+        let seven = n.push_back(7);
+        let mut cursor = m.cursor_mut();
+        cursor.insert_list_after(list_from(&[-1]));
+        cursor.insert_list_before(list_from(&[6]));
+        let mut whole = cursor.take_after();
+        assert!(m.is_empty());
+        m.cursor_mut().insert_list_before(n);
+        assert_eq!(m.get(seven), Some(&7));
+        whole.append(&mut m);
+        assert_eq!(contents(&whole), &[-1, 0, 1, 2, 3, 4, 5, 6, 7]);
+        assert!(m.is_empty());
+
+        let mut tail = whole.split_off(6);
+        assert_eq!(contents(&tail), &[5, 6, 7]);
+        assert_eq!(whole.split_off(6), ArenaList::new());
+        let head = tail.split_off(0);
+        assert!(tail.is_empty());
+        assert_eq!(contents(&head), &[5, 6, 7]);
+        check_links(&whole);
+        check_links(&head);
+    }
+
+    #[test]
+    fn test_arena() {
+        let mut arena = Arena::new();
+        let a = arena.new_list();
+        let b = arena.new_list();
+        let one = arena.push_back(a, 1);
+        arena.push_front(a, 0);
+        let ten = arena.push_back(b, 10);
+        let three = arena.insert_after(one, 3).unwrap();
+        arena.insert_before(three, 2).unwrap();
+        arena.insert_before(ten, 9).unwrap();
+        assert_eq!(arena_contents(&arena, a), &[0, 1, 2, 3]);
+        assert_eq!(arena_contents(&arena, b), &[9, 10]);
+        assert_eq!((arena.len(a), arena.len(b)), (4, 2));
+        assert_eq!((arena.front(a), arena.back(b)), (Some(&0), Some(&10)));
+        assert_eq!(arena.list_of(three), Some(a));
+        assert_eq!(arena.list_of(ten), Some(b));
+        check_arena(&arena);
+
+        for x in arena.iter_mut(b) {
+            *x *= 10;
+        }
+        assert_eq!(arena.get(ten), Some(&100));
+        assert_eq!(arena.remove(three), Some(3));
+        assert_eq!(arena.remove(three), None);
+        assert_eq!(arena.list_of(three), None);
+        assert_eq!(arena.pop_back(a), Some(2));
+        assert_eq!(arena.pop_front(b), Some(90));
+        *arena.get_mut(one).unwrap() = -1;
+        assert_eq!(arena_contents(&arena, a), &[0, -1]);
+        check_arena(&arena);
+
+        // a removed list takes its elements with it, and its id doesn't come back
+        arena.remove_list(b);
+        assert!(!arena.contains_list(b));
+        assert!(!arena.contains_index(ten));
+        let c = arena.new_list();
+        assert_eq!(c.slot, b.slot);
+        assert_ne!(c, b);
+        assert!(arena.is_empty(c));
+        assert_eq!(arena_contents(&arena, a), &[0, -1]);
+        check_arena(&arena);
+
+        // ids and indices don't work on another arena
+        let mut other = Arena::new();
+        let d = other.new_list();
+        other.push_back(d, 5);
+        assert_eq!((d.slot, d.generation), (a.slot, a.generation));
+        assert!(!arena.contains_list(d));
+        assert_eq!(other.get(one), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_arena_removed_list() {
+        let mut arena = Arena::new();
+        let a = arena.new_list();
+        arena.remove_list(a);
+        arena.push_back(a, 1);
+    }
+
+    #[test]
+    fn test_arena_relink() {
+        let mut arena = Arena::with_capacity(16);
+        let a = arena.new_list();
+        let b = arena.new_list();
+        let ia: Vec<_> = (0..5).map(|x| arena.push_back(a, x)).collect();
+        let ib: Vec<_> = (5..7).map(|x| arena.push_back(b, x)).collect();
+        let capacity = arena.capacity();
+
+        // the shorter src gets retagged
+        arena.append(a, b);
+        assert_eq!(arena_contents(&arena, a), &[0, 1, 2, 3, 4, 5, 6]);
+        assert!(arena.is_empty(b));
+        check_arena(&arena);
+        // nothing was copied, every index still works and knows where it is now
+        for (x, &index) in ia.iter().chain(&ib).enumerate() {
+            assert_eq!(arena.get(index), Some(&(x as i32)));
+            assert_eq!(arena.list_of(index), Some(a));
+        }
+        assert_eq!(arena.capacity(), capacity);
+
+        // the shorter dst gets retagged and the chains trade lists
+        arena.push_back(b, -1);
+        arena.append(b, a);
+        assert_eq!(arena_contents(&arena, b), &[-1, 0, 1, 2, 3, 4, 5, 6]);
+        assert!(arena.is_empty(a));
+        assert_eq!(arena.list_of(ia[0]), Some(b));
+        check_arena(&arena);
+        arena.append(a, b);
+        assert_eq!(arena_contents(&arena, a), &[-1, 0, 1, 2, 3, 4, 5, 6]);
+        arena.append(a, b);
+        check_arena(&arena);
+
+        // splitting off a short tail retags the tail
+        let c = arena.split_off(a, 6);
+        assert_eq!(arena_contents(&arena, a), &[-1, 0, 1, 2, 3, 4]);
+        assert_eq!(arena_contents(&arena, c), &[5, 6]);
+        assert_eq!(arena.list_of(ib[0]), Some(c));
+        assert_eq!(arena.list_of(ia[4]), Some(a));
+        check_arena(&arena);
+        // a long tail, the head is retagged and the chains trade
+        let d = arena.split_off(a, 2);
+        assert_eq!(arena_contents(&arena, a), &[-1, 0]);
+        assert_eq!(arena_contents(&arena, d), &[1, 2, 3, 4]);
+        assert_eq!(arena.list_of(ia[0]), Some(a));
+        assert_eq!(arena.list_of(ia[1]), Some(d));
+        check_arena(&arena);
+        // the edges
+        let e = arena.split_off(d, 4);
+        assert!(arena.is_empty(e));
+        let f = arena.split_off(d, 0);
+        assert!(arena.is_empty(d));
+        assert_eq!(arena_contents(&arena, f), &[1, 2, 3, 4]);
+        assert_eq!(
+            arena.iter(f).rev().cloned().collect::<Vec<_>>(),
+            &[4, 3, 2, 1]
+        );
+        check_arena(&arena);
+
+        // removing by index fixes up whichever list it's in now
+        assert_eq!(arena.remove(ia[4]), Some(4));
+        assert_eq!(arena.remove(ib[1]), Some(6));
+        assert_eq!(arena.back(f), Some(&3));
+        assert_eq!(arena.back(c), Some(&5));
+        assert_eq!(arena.len(f), 3);
+        check_arena(&arena);
+    }
+}
@@ -0,0 +1,785 @@
+// a sixth::LinkedList that never gets longer than its capacity
+// every way of adding elements checks the capacity first, and what happens when they don't fit is
+// the list's Overflow policy:
+//
+// * Reject: nothing changes and the elements come back as the Err
+// * Evict: they go in anyway and the list drops elements off the other end to make room, handing
+//   them back. pushing on the back drops from the front and pushing on the front drops from the
+//   back. inserts and splices through a cursor aren't at either end, so they always drop from
+//   the front (back is the newest end, like a "last N events" buffer)
+//
+// there is no blocking policy on BoundedList itself: we have the only &mut to it, so nobody could
+// ever make room while we wait. BlockingList at the bottom of the file is the shared version that
+// does block.
+use crate::sixth::{self, LinkedList, NodeHandle};
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    sync::{Condvar, Mutex, MutexGuard},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    Reject,
+    Evict,
+}
+
+pub struct BoundedList<T> {
+    list: LinkedList<T>,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl<T> BoundedList<T> {
+    // a list that can't hold anything is always a bug, so capacity 0 panics
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        assert!(capacity > 0, "BoundedList needs a capacity of at least 1");
+        BoundedList {
+            list: LinkedList::new(),
+            capacity,
+            overflow,
+        }
+    }
+
+    // takes over a list that already fits, or gives it back if it doesn't
+    pub fn from_list(
+        list: LinkedList<T>,
+        capacity: usize,
+        overflow: Overflow,
+    ) -> Result<Self, LinkedList<T>> {
+        if list.len() > capacity {
+            return Err(list);
+        }
+        let mut bounded = Self::new(capacity, overflow);
+        bounded.list = list;
+        Ok(bounded)
+    }
+
+    pub fn into_list(self) -> LinkedList<T> {
+        self.list
+    }
+
+    // read only, anything that could add elements has to go through us
+    pub fn as_list(&self) -> &LinkedList<T> {
+        &self.list
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.list.len() == self.capacity
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    // Ok(None) if there was room, Ok(Some(evicted)) if something had to go to make room, or
+    // Err(elem) if the policy is Reject and we're full
+    pub fn push_back(&mut self, elem: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Reject => return Err(elem),
+                Overflow::Evict => self.list.pop_front(),
+            }
+        } else {
+            None
+        };
+        self.list.push_back(elem);
+        Ok(evicted)
+    }
+
+    pub fn push_front(&mut self, elem: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Reject => return Err(elem),
+                Overflow::Evict => self.list.pop_back(),
+            }
+        } else {
+            None
+        };
+        self.list.push_front(elem);
+        Ok(evicted)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.list.front_mut()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.list.back_mut()
+    }
+
+    pub fn iter(&self) -> sixth::Iter<'_, T> {
+        self.list.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> sixth::IterMut<'_, T> {
+        self.list.iter_mut()
+    }
+}
+
+impl<T: Clone> Clone for BoundedList<T> {
+    fn clone(&self) -> Self {
+        BoundedList {
+            list: self.list.clone(),
+            capacity: self.capacity,
+            overflow: self.overflow,
+        }
+    }
+}
+
+impl<T: Debug> Debug for BoundedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+// only the elements count, like comparing two Vecs with different capacities
+impl<T: PartialEq> PartialEq for BoundedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+impl<T: Eq> Eq for BoundedList<T> {}
+
+impl<T: Hash> Hash for BoundedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.list.hash(state);
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BoundedList<T> {
+    type IntoIter = sixth::Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut BoundedList<T> {
+    type IntoIter = sixth::IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for BoundedList<T> {
+    type IntoIter = sixth::IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+// cursors
+// reading through a cursor can't break anything, so the read only one is just sixth's. the
+// mutable one wraps sixth's and checks everything that adds elements. it keeps its own count of
+// the list's len since sixth's cursor doesn't hand that out
+//
+// split_at_current is the one thing we don't wrap. the two halves would have to share the
+// capacity between them while f holds both, and with Evict an insert into the second half has to
+// drop the front of the whole list, which only the first half's cursor can reach. for a split
+// take the list out with into_list and put it back with from_list
+pub struct CursorMut<'a, T> {
+    cursor: sixth::CursorMut<'a, T>,
+    len: usize,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl<T> BoundedList<T> {
+    pub fn cursor(&self) -> sixth::Cursor<'_, T> {
+        self.list.cursor()
+    }
+
+    pub fn cursor_front(&self) -> sixth::Cursor<'_, T> {
+        self.list.cursor_front()
+    }
+
+    pub fn cursor_back(&self) -> sixth::Cursor<'_, T> {
+        self.list.cursor_back()
+    }
+
+    pub fn cursor_at(&self, index: usize) -> sixth::Cursor<'_, T> {
+        self.list.cursor_at(index)
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        self.wrap(|list| list.cursor_mut())
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        self.wrap(|list| list.cursor_front_mut())
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        self.wrap(|list| list.cursor_back_mut())
+    }
+
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T> {
+        self.wrap(move |list| list.cursor_at_mut(index))
+    }
+
+    fn wrap<'a>(
+        &'a mut self,
+        f: impl FnOnce(&'a mut LinkedList<T>) -> sixth::CursorMut<'a, T>,
+    ) -> CursorMut<'a, T> {
+        CursorMut {
+            len: self.list.len(),
+            capacity: self.capacity,
+            overflow: self.overflow,
+            cursor: f(&mut self.list),
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.cursor.index()
+    }
+
+    pub fn as_cursor(&self) -> sixth::Cursor<'_, T> {
+        self.cursor.as_cursor()
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.cursor.seek(index);
+    }
+
+    pub fn move_next(&mut self) {
+        self.cursor.move_next();
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cursor.move_prev();
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cursor.current()
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.cursor.peek_next()
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.cursor.peek_prev()
+    }
+
+    pub fn current_handle(&mut self) -> Option<NodeHandle> {
+        self.cursor.current_handle()
+    }
+
+    pub fn move_current_to_front(&mut self) {
+        self.cursor.move_current_to_front();
+    }
+
+    pub fn move_current_to_back(&mut self) {
+        self.cursor.move_current_to_back();
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        let elem = self.cursor.remove_current()?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T>> {
+        let list = self.cursor.remove_current_as_list()?;
+        self.len -= 1;
+        Some(list)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let elem = self.cursor.pop_front()?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let elem = self.cursor.pop_back()?;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        let output = self.cursor.split_before();
+        self.len -= output.len();
+        output
+    }
+
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let output = self.cursor.split_after();
+        self.len -= output.len();
+        output
+    }
+
+    // the same results as BoundedList::push_back. with Evict the new element goes in wherever we
+    // are and the front is dropped, which can be the new element itself if we're at the front
+    pub fn insert_before(&mut self, elem: T) -> Result<Option<T>, T> {
+        if self.len == self.capacity && self.overflow == Overflow::Reject {
+            return Err(elem);
+        }
+        self.cursor.insert_before(elem);
+        self.len += 1;
+        Ok(self
+            .evict_front(self.len - self.len.min(self.capacity))
+            .pop_front())
+    }
+
+    pub fn insert_after(&mut self, elem: T) -> Result<Option<T>, T> {
+        if self.len == self.capacity && self.overflow == Overflow::Reject {
+            return Err(elem);
+        }
+        self.cursor.insert_after(elem);
+        self.len += 1;
+        Ok(self
+            .evict_front(self.len - self.len.min(self.capacity))
+            .pop_front())
+    }
+
+    // the same, plus a handle to the new node. if the new node is what got evicted the handle
+    // comes back already stale
+    pub fn insert_before_handle(&mut self, elem: T) -> Result<(NodeHandle, Option<T>), T> {
+        if self.len == self.capacity && self.overflow == Overflow::Reject {
+            return Err(elem);
+        }
+        let handle = self.cursor.insert_before_handle(elem);
+        self.len += 1;
+        let evicted = self
+            .evict_front(self.len - self.len.min(self.capacity))
+            .pop_front();
+        Ok((handle, evicted))
+    }
+
+    pub fn insert_after_handle(&mut self, elem: T) -> Result<(NodeHandle, Option<T>), T> {
+        if self.len == self.capacity && self.overflow == Overflow::Reject {
+            return Err(elem);
+        }
+        let handle = self.cursor.insert_after_handle(elem);
+        self.len += 1;
+        let evicted = self
+            .evict_front(self.len - self.len.min(self.capacity))
+            .pop_front();
+        Ok((handle, evicted))
+    }
+
+    // splices are all or nothing with Reject: if the whole input doesn't fit none of it goes in
+    // and it comes back as the Err. with Evict all of it goes in and the front is dropped until
+    // we fit again, the dropped elements come back in order (and may well include some of the
+    // input, or be longer than the input if it was longer than the whole capacity)
+    pub fn splice_before(&mut self, input: LinkedList<T>) -> Result<LinkedList<T>, LinkedList<T>> {
+        if self.len + input.len() > self.capacity && self.overflow == Overflow::Reject {
+            return Err(input);
+        }
+        self.len += input.len();
+        self.cursor.splice_before(input);
+        Ok(self.evict_front(self.len - self.len.min(self.capacity)))
+    }
+
+    pub fn splice_after(&mut self, input: LinkedList<T>) -> Result<LinkedList<T>, LinkedList<T>> {
+        if self.len + input.len() > self.capacity && self.overflow == Overflow::Reject {
+            return Err(input);
+        }
+        self.len += input.len();
+        self.cursor.splice_after(input);
+        Ok(self.evict_front(self.len - self.len.min(self.capacity)))
+    }
+
+    // drops the first n nodes without moving the cursor off the rest. if it was on one of the
+    // dropped nodes it ends up on the new front, same as remove_current moving on to the next.
+    // sixth's cursor pops the front wherever it is, so evicting one element is O(1) and not a
+    // seek to the front and back. any handles to the rest stay good
+    fn evict_front(&mut self, n: usize) -> LinkedList<T> {
+        let mut evicted = LinkedList::new();
+        for _ in 0..n {
+            evicted.push_back(self.cursor.pop_front().unwrap());
+        }
+        self.len -= n;
+        evicted
+    }
+}
+
+// blocking
+// the Block policy. the list lives behind a Mutex so other threads can get at it, a push into a
+// full list waits until someone pops, and a pop from an empty one waits until someone pushes.
+// it starts out Reject, but with_list can switch it to Evict. then pushes never wait, they drop
+// the other end like BoundedList does and hand it back, so every push returns what it evicted
+pub struct BlockingList<T> {
+    list: Mutex<BoundedList<T>>,
+    not_full: Condvar,
+    not_empty: Condvar,
+}
+
+impl<T> BlockingList<T> {
+    pub fn new(capacity: usize) -> Self {
+        BlockingList {
+            list: Mutex::new(BoundedList::new(capacity, Overflow::Reject)),
+            not_full: Condvar::new(),
+            not_empty: Condvar::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> BoundedList<T> {
+        self.list.into_inner().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BoundedList<T>> {
+        self.list.lock().unwrap()
+    }
+
+    fn wait_not_full(&self) -> MutexGuard<'_, BoundedList<T>> {
+        self.not_full
+            .wait_while(self.lock(), |list| {
+                list.is_full() && list.overflow() == Overflow::Reject
+            })
+            .unwrap()
+    }
+
+    fn wait_not_empty(&self) -> MutexGuard<'_, BoundedList<T>> {
+        self.not_empty
+            .wait_while(self.lock(), |list| list.is_empty())
+            .unwrap()
+    }
+
+    // after the wait there is room or we evict, so the push can't be rejected
+    pub fn push_back(&self, elem: T) -> Option<T> {
+        let evicted = self.wait_not_full().push_back(elem).ok().unwrap();
+        self.not_empty.notify_one();
+        evicted
+    }
+
+    pub fn push_front(&self, elem: T) -> Option<T> {
+        let evicted = self.wait_not_full().push_front(elem).ok().unwrap();
+        self.not_empty.notify_one();
+        evicted
+    }
+
+    pub fn pop_front(&self) -> T {
+        let elem = self.wait_not_empty().pop_front().unwrap();
+        self.not_full.notify_one();
+        elem
+    }
+
+    pub fn pop_back(&self) -> T {
+        let elem = self.wait_not_empty().pop_back().unwrap();
+        self.not_full.notify_one();
+        elem
+    }
+
+    // the non-blocking versions, Reject instead of waiting. same results as BoundedList's
+    pub fn try_push_back(&self, elem: T) -> Result<Option<T>, T> {
+        let evicted = self.lock().push_back(elem)?;
+        self.not_empty.notify_one();
+        Ok(evicted)
+    }
+
+    pub fn try_push_front(&self, elem: T) -> Result<Option<T>, T> {
+        let evicted = self.lock().push_front(elem)?;
+        self.not_empty.notify_one();
+        Ok(evicted)
+    }
+
+    pub fn try_pop_front(&self) -> Option<T> {
+        let elem = self.lock().pop_front()?;
+        self.not_full.notify_one();
+        Some(elem)
+    }
+
+    pub fn try_pop_back(&self) -> Option<T> {
+        let elem = self.lock().pop_back()?;
+        self.not_full.notify_one();
+        Some(elem)
+    }
+
+    // anything else (cursors, iterating, changing the policy...) happens under the lock in f.
+    // since we can't tell what f did everybody waiting gets woken up afterwards
+    pub fn with_list<R>(&self, f: impl FnOnce(&mut BoundedList<T>) -> R) -> R {
+        let result = f(&mut self.lock());
+        self.not_full.notify_all();
+        self.not_empty.notify_all();
+        result
+    }
+}
+
+impl<T: Debug> Debug for BlockingList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.lock().fmt(f)
+    }
+}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<BoundedList<i32>>();
+    is_sync::<BoundedList<i32>>();
+
+    is_send::<BlockingList<i32>>();
+    is_sync::<BlockingList<i32>>();
+
+    fn bounded_list_covariant<'a, T>(x: BoundedList<&'static T>) -> BoundedList<&'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BlockingList, BoundedList, Overflow};
+    use crate::sixth::LinkedList;
+    use std::{sync::Arc, thread};
+
+    fn contents<T: Clone>(list: &BoundedList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    fn list_from<T: Clone>(v: &[T]) -> LinkedList<T> {
+        v.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut m = BoundedList::new(3, Overflow::Reject);
+        assert_eq!(m.push_back(1), Ok(None));
+        assert_eq!(m.push_back(2), Ok(None));
+        assert_eq!(m.push_front(0), Ok(None));
+        assert!(m.is_full());
+        assert_eq!(m.push_back(3), Err(3));
+        assert_eq!(m.push_front(-1), Err(-1));
+        assert_eq!(contents(&m), &[0, 1, 2]);
+        assert_eq!(m.pop_front(), Some(0));
+        assert_eq!(m.push_back(3), Ok(None));
+        assert_eq!(contents(&m), &[1, 2, 3]);
+        assert_eq!(m.capacity(), 3);
+
+        assert!(BoundedList::from_list(list_from(&[1, 2, 3, 4]), 3, Overflow::Reject).is_err());
+        let m = BoundedList::from_list(list_from(&[1, 2]), 3, Overflow::Reject).unwrap();
+        assert_eq!(format!("{m:?}"), "[1, 2]");
+    }
+
+    #[test]
+    fn test_evict() {
+        // the last 3 events
+        let mut m = BoundedList::new(3, Overflow::Evict);
+        let evicted: Vec<_> = (0..6).map(|x| m.push_back(x).unwrap()).collect();
+        assert_eq!(evicted, &[None, None, None, Some(0), Some(1), Some(2)]);
+        assert_eq!(contents(&m), &[3, 4, 5]);
+        // the other way round drops the back
+        assert_eq!(m.push_front(2), Ok(Some(5)));
+        assert_eq!(contents(&m), &[2, 3, 4]);
+
+        m.set_overflow(Overflow::Reject);
+        assert_eq!(m.push_back(5), Err(5));
+        assert_eq!(m.clone().into_iter().collect::<Vec<_>>(), &[2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        BoundedList::<i32>::new(0, Overflow::Evict);
+    }
+
+    #[test]
+    fn test_cursor_reject() {
+        let mut m = BoundedList::new(4, Overflow::Reject);
+        m.push_back(1).unwrap();
+        m.push_back(3).unwrap();
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.insert_after(2), Ok(None));
+        assert_eq!(cursor.index(), Some(0));
+        // all or nothing
+        assert_eq!(
+            cursor.splice_before(list_from(&[-1, 0])),
+            Err(list_from(&[-1, 0]))
+        );
+        assert_eq!(cursor.splice_before(list_from(&[0])), Ok(LinkedList::new()));
+        assert_eq!(cursor.insert_before(-1), Err(-1));
+        assert_eq!(cursor.index(), Some(1));
+        // making room through the cursor counts
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.insert_before(1), Ok(None));
+        assert_eq!(cursor.split_after(), list_from(&[3]));
+        assert_eq!(cursor.insert_after(5), Ok(None));
+        assert_eq!(cursor.insert_after(4), Err(4));
+        assert_eq!(contents(&m), &[0, 1, 2, 5]);
+    }
+
+    #[test]
+    fn test_cursor_evict() {
+        let mut m = BoundedList::new(4, Overflow::Evict);
+        for x in 0..4 {
+            m.push_back(x).unwrap();
+        }
+        let mut cursor = m.cursor_at_mut(2);
+        assert_eq!(cursor.insert_after(10), Ok(Some(0)));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(
+            cursor.splice_before(list_from(&[20, 21])),
+            Ok(list_from(&[1, 20]))
+        );
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(contents(&m), &[21, 2, 10, 3]);
+
+        // at the front, the new element is what falls off
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.insert_before(30), Ok(Some(30)));
+        assert_eq!(cursor.current(), Some(&mut 21));
+        // the front itself falls off, we move on to the new front
+        assert_eq!(cursor.insert_after(31), Ok(Some(21)));
+        assert_eq!(cursor.current(), Some(&mut 31));
+        assert_eq!(cursor.index(), Some(0));
+        // on the ghost we stay on the ghost
+        cursor.move_prev();
+        assert_eq!(
+            cursor.splice_before(list_from(&[40, 41])),
+            Ok(list_from(&[31, 2]))
+        );
+        assert_eq!(cursor.index(), None);
+        // more than the whole capacity
+        assert_eq!(
+            cursor.splice_after(list_from(&[0, 1, 2, 3, 4, 5])),
+            Ok(list_from(&[0, 1, 2, 3, 4, 5]))
+        );
+        assert_eq!(cursor.index(), None);
+        assert_eq!(contents(&m), &[10, 3, 40, 41]);
+    }
+
+    #[test]
+    fn test_cursor_handles() {
+        let mut m = BoundedList::new(3, Overflow::Evict);
+        m.push_back(1).unwrap();
+        let mut cursor = m.cursor_front_mut();
+        let handle = cursor.current_handle().unwrap();
+        cursor.insert_after(2).unwrap();
+        cursor.insert_after(3).unwrap();
+        cursor.move_next();
+        let kept = cursor.current_handle().unwrap();
+        // evicting the front only loses the handle to the front
+        assert_eq!(cursor.insert_before(4), Ok(Some(1)));
+        assert_eq!(m.as_list().get_by_handle(handle), None);
+        assert_eq!(m.as_list().get_by_handle(kept), Some(&3));
+
+        // inserting with a handle checks the capacity the same way
+        let mut cursor = m.cursor_back_mut();
+        let (five, evicted) = cursor.insert_after_handle(5).unwrap();
+        assert_eq!(evicted, Some(4));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        // on the front the new node is what falls off, and its handle with it
+        let mut cursor = m.cursor_front_mut();
+        let (six, evicted) = cursor.insert_before_handle(6).unwrap();
+        assert_eq!(evicted, Some(6));
+        m.set_overflow(Overflow::Reject);
+        assert_eq!(m.cursor_front_mut().insert_after_handle(7), Err(7));
+        assert_eq!(m.as_list().get_by_handle(five), Some(&5));
+        assert_eq!(m.as_list().get_by_handle(six), None);
+        assert_eq!(contents(&m), &[3, 2, 5]);
+    }
+
+    #[test]
+    fn test_blocking() {
+        let list = Arc::new(BlockingList::new(2));
+        let producer = {
+            let list = Arc::clone(&list);
+            thread::spawn(move || {
+                for x in 0..100 {
+                    assert_eq!(list.push_back(x), None);
+                }
+            })
+        };
+        let consumed: Vec<_> = (0..100).map(|_| list.pop_front()).collect();
+        producer.join().unwrap();
+        assert_eq!(consumed, (0..100).collect::<Vec<_>>());
+        assert!(list.is_empty());
+
+        assert_eq!(list.try_pop_back(), None);
+        assert_eq!(list.try_push_front(1), Ok(None));
+        assert_eq!(list.try_push_front(0), Ok(None));
+        assert_eq!(list.try_push_back(2), Err(2));
+        assert_eq!(list.len(), 2);
+
+        // a push waits for room made under with_list
+        let pusher = {
+            let list = Arc::clone(&list);
+            thread::spawn(move || list.push_back(2))
+        };
+        list.with_list(|m| {
+            let mut cursor = m.cursor_front_mut();
+            cursor.remove_current()
+        });
+        pusher.join().unwrap();
+        assert_eq!(format!("{list:?}"), "[1, 2]");
+        assert_eq!(list.pop_back(), 2);
+        assert_eq!(list.try_pop_front(), Some(1));
+        assert_eq!(Arc::into_inner(list).unwrap().into_inner().capacity(), 2);
+    }
+
+    #[test]
+    fn test_blocking_evict() {
+        let list = BlockingList::new(2);
+        list.push_back(1);
+        list.push_back(2);
+        list.with_list(|m| m.set_overflow(Overflow::Evict));
+        // full, but evicting instead of waiting, and nothing gets lost
+        assert_eq!(list.push_back(3), Some(1));
+        assert_eq!(list.push_front(0), Some(3));
+        assert_eq!(list.try_push_back(4), Ok(Some(0)));
+        assert_eq!(list.try_push_front(5), Ok(Some(4)));
+        assert_eq!(format!("{list:?}"), "[5, 2]");
+    }
+}
@@ -0,0 +1,997 @@
+// a circular doubly linked list
+// the back's next is the front and the front's prev is the back, so there are no ends at all,
+// every node always has two neighbours (a list of one is its own neighbour both ways). that means
+// the links don't need to be Options, and there is no ghost: a cursor that walks off the back
+// just keeps going at the front.
+//
+// the list only remembers one node, head, which is where the list "starts" for iterators, indices,
+// front and back (the back is head.prev). moving the head around doesn't touch any node, so
+// rotating by one is O(1), and making the cursor's node the head is O(1) too.
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+pub struct CircularList<T> {
+    head: Option<NonNull<Node<T>>>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = NonNull<Node<T>>;
+
+struct Node<T> {
+    prev: Link<T>,
+    next: Link<T>,
+    elem: T,
+}
+
+// the raw ring surgery, none of these know about the list
+//
+// SAFETY (for all three): every node passed in is alive and in a well formed ring
+
+// puts the chain first..=last in between prev and next, which are neighbours (or the same node,
+// if that's a ring of one)
+unsafe fn link_between<T>(prev: Link<T>, next: Link<T>, first: Link<T>, last: Link<T>) {
+    unsafe {
+        (*prev.as_ptr()).next = first;
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        (*next.as_ptr()).prev = last;
+    }
+}
+
+// the ring starting at head goes in right after node
+unsafe fn splice_ring<T>(node: Link<T>, head: Link<T>) {
+    unsafe {
+        let last = (*head.as_ptr()).prev;
+        link_between(node, (*node.as_ptr()).next, head, last);
+    }
+}
+
+// takes first..=last out of their ring and closes them into a ring of their own. they can't be
+// the whole ring, there has to be something left to close up
+unsafe fn cut_out<T>(first: Link<T>, last: Link<T>) {
+    unsafe {
+        let prev = (*first.as_ptr()).prev;
+        let next = (*last.as_ptr()).next;
+        (*prev.as_ptr()).next = next;
+        (*next.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = first;
+        (*first.as_ptr()).prev = last;
+    }
+}
+
+impl<T> CircularList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // a ring of one
+    fn new_node(elem: T) -> Link<T> {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: NonNull::dangling(),
+                next: NonNull::dangling(),
+                elem,
+            })));
+            (*new.as_ptr()).prev = new;
+            (*new.as_ptr()).next = new;
+            new
+        }
+    }
+
+    // takes node out of the ring and frees it, the head has to be moved off it first
+    unsafe fn unlink(&mut self, node: Link<T>) -> T {
+        unsafe {
+            let boxed_node = Box::from_raw(node.as_ptr());
+            if self.len == 1 {
+                self.head = None;
+            } else {
+                (*boxed_node.prev.as_ptr()).next = boxed_node.next;
+                (*boxed_node.next.as_ptr()).prev = boxed_node.prev;
+            }
+            self.len -= 1;
+            boxed_node.elem
+        }
+    }
+
+    // goes in right before the head, which is the back
+    pub fn push_back(&mut self, elem: T) {
+        let new = Self::new_node(elem);
+        match self.head {
+            Some(head) => unsafe { splice_ring((*head.as_ptr()).prev, new) },
+            None => self.head = Some(new),
+        }
+        self.len += 1;
+    }
+
+    // same spot as push_back, the only difference is that it becomes the head
+    pub fn push_front(&mut self, elem: T) {
+        self.push_back(elem);
+        self.rotate_right(1);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        unsafe {
+            self.head = Some((*head.as_ptr()).next);
+            Some(self.unlink(head))
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let head = self.head?;
+        unsafe { Some(self.unlink((*head.as_ptr()).prev)) }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.head.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe {
+            self.head
+                .map(|node| &(*(*node.as_ptr()).prev.as_ptr()).elem)
+        }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe {
+            self.head
+                .map(|node| &mut (*(*node.as_ptr()).prev.as_ptr()).elem)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // rotate_left(1) makes the second element the head, like VecDeque. unlike VecDeque any
+    // amount is fine, it just goes round. every step is moving the head one node, and we go
+    // whichever way round is shorter, so this is O(min(n, len - n)) after wrapping n
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        let n = n % self.len;
+        if n <= self.len / 2 {
+            for _ in 0..n {
+                self.head = self.head.map(|head| unsafe { (*head.as_ptr()).next });
+            }
+        } else {
+            for _ in n..self.len {
+                self.head = self.head.map(|head| unsafe { (*head.as_ptr()).prev });
+            }
+        }
+    }
+
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.len == 0 {
+            return;
+        }
+        self.rotate_left(self.len - n % self.len);
+    }
+}
+
+impl<T> Default for CircularList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for CircularList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for CircularList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for CircularList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for CircularList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+// comparisons go from the heads, so two rings with the same elements but different heads are
+// different lists
+impl<T: PartialEq> PartialEq for CircularList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for CircularList<T> {}
+
+impl<T: PartialOrd> PartialOrd for CircularList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for CircularList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for CircularList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for CircularList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+// iterators go once round, from the head, and len says when to stop
+pub struct Iter<'a, T> {
+    front: Option<Link<T>>,
+    back: Option<Link<T>>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<T> CircularList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.head,
+            back: self.head.map(|head| unsafe { (*head.as_ptr()).prev }),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CircularList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = Some((*node.as_ptr()).next);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = Some((*node.as_ptr()).prev);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Option<Link<T>>,
+    back: Option<Link<T>>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<T> CircularList<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.head,
+            back: self.head.map(|head| unsafe { (*head.as_ptr()).prev }),
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut CircularList<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = Some((*node.as_ptr()).next);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = Some((*node.as_ptr()).prev);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: CircularList<T>,
+}
+
+impl<T> IntoIterator for CircularList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// cursors
+// no ghost here: a cursor is on a node whenever the list isn't empty, and moving past the back
+// lands on the head. index() counts from the head, so it goes len - 1 -> 0 when we wrap
+pub struct Cursor<'a, T> {
+    cur: Option<Link<T>>,
+    list: &'a CircularList<T>,
+    index: Option<usize>,
+}
+
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    cur: Option<Link<T>>,
+    list: &'a mut CircularList<T>,
+    index: Option<usize>,
+}
+
+impl<T> CircularList<T> {
+    // both start on the head
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.head,
+            list: self,
+            index: self.head.map(|_| 0),
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: self.head,
+            index: self.head.map(|_| 0),
+            list: self,
+        }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { Some((*cur.as_ptr()).next) };
+            self.index = self.index.map(|index| (index + 1) % self.list.len);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { Some((*cur.as_ptr()).prev) };
+            self.index = self
+                .index
+                .map(|index| index.checked_sub(1).unwrap_or(self.list.len - 1));
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*(*node.as_ptr()).next.as_ptr()).elem) }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*(*node.as_ptr()).prev.as_ptr()).elem) }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { Some((*cur.as_ptr()).next) };
+            self.index = self.index.map(|index| (index + 1) % self.list.len);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { Some((*cur.as_ptr()).prev) };
+            self.index = self
+                .index
+                .map(|index| index.checked_sub(1).unwrap_or(self.list.len - 1));
+        }
+    }
+
+    // with one element next and prev are the current one too
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            self.cur
+                .map(|node| &mut (*(*node.as_ptr()).next.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            self.cur
+                .map(|node| &mut (*(*node.as_ptr()).prev.as_ptr()).elem)
+        }
+    }
+
+    // the whole list turns so that we're on the head, O(1)
+    pub fn make_head(&mut self) {
+        if self.cur.is_some() {
+            self.list.head = self.cur;
+            self.index = Some(0);
+        }
+    }
+
+    // the spot before the head is the back, so splicing before the head adds on to the back and
+    // the head (and our index 0) stays put. on an empty list the input becomes the list and we go
+    // to its head
+    pub fn splice_before(&mut self, mut input: CircularList<T>) {
+        let Some(input_head) = input.head.take() else {
+            return;
+        };
+        let input_len = std::mem::take(&mut input.len);
+        match self.cur {
+            Some(cur) => unsafe {
+                splice_ring((*cur.as_ptr()).prev, input_head);
+                if let Some(index) = self.index.as_mut()
+                    && *index > 0
+                {
+                    *index += input_len;
+                }
+            },
+            None => {
+                self.list.head = Some(input_head);
+                self.cur = Some(input_head);
+                self.index = Some(0);
+            }
+        }
+        self.list.len += input_len;
+    }
+
+    pub fn splice_after(&mut self, mut input: CircularList<T>) {
+        let Some(input_head) = input.head.take() else {
+            return;
+        };
+        let input_len = std::mem::take(&mut input.len);
+        match self.cur {
+            Some(cur) => unsafe { splice_ring(cur, input_head) },
+            None => {
+                self.list.head = Some(input_head);
+                self.cur = Some(input_head);
+                self.index = Some(0);
+            }
+        }
+        self.list.len += input_len;
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        self.splice_before(Some(elem).into_iter().collect());
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        self.splice_after(Some(elem).into_iter().collect());
+    }
+
+    // takes the current node out and moves on to the next one. if that was the head, the next
+    // one is the head now
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        let index = self.index.unwrap();
+        unsafe {
+            let next = (*cur.as_ptr()).next;
+            if self.list.head == Some(cur) {
+                self.list.head = Some(next);
+            }
+            let elem = self.list.unlink(cur);
+            if self.list.is_empty() {
+                self.cur = None;
+                self.index = None;
+            } else {
+                self.cur = Some(next);
+                // the back wraps round to the head
+                if index == self.list.len {
+                    self.index = Some(0);
+                }
+            }
+            Some(elem)
+        }
+    }
+
+    // splits go by the order from the head: split_before takes head..cur (and we become the
+    // head), split_after takes cur+1..=back. both give back a ring of their own
+    pub fn split_before(&mut self) -> CircularList<T> {
+        let (Some(cur), Some(index)) = (self.cur, self.index) else {
+            return CircularList::new();
+        };
+        if index == 0 {
+            return CircularList::new();
+        }
+        unsafe {
+            let first = self.list.head.unwrap();
+            cut_out(first, (*cur.as_ptr()).prev);
+            self.list.head = Some(cur);
+            self.list.len -= index;
+            self.index = Some(0);
+            CircularList {
+                head: Some(first),
+                len: index,
+                _boo: PhantomData,
+            }
+        }
+    }
+
+    pub fn split_after(&mut self) -> CircularList<T> {
+        let (Some(cur), Some(index)) = (self.cur, self.index) else {
+            return CircularList::new();
+        };
+        let after_len = self.list.len - index - 1;
+        if after_len == 0 {
+            return CircularList::new();
+        }
+        unsafe {
+            let first = (*cur.as_ptr()).next;
+            let last = (*self.list.head.unwrap().as_ptr()).prev;
+            cut_out(first, last);
+            self.list.len -= after_len;
+            CircularList {
+                head: Some(first),
+                len: after_len,
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send> Send for CircularList<T> {}
+unsafe impl<T: Sync> Sync for CircularList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for CursorMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for CursorMut<'a, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<CircularList<i32>>();
+    is_sync::<CircularList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    is_send::<Cursor<i32>>();
+    is_sync::<Cursor<i32>>();
+
+    fn circular_list_covariant<'a, T>(x: CircularList<&'static T>) -> CircularList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+    fn cursor_covariant<'i, 'a, T>(x: Cursor<'i, &'static T>) -> Cursor<'i, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CircularList;
+
+    fn list_from<T: Clone>(v: &[T]) -> CircularList<T> {
+        v.iter().cloned().collect()
+    }
+
+    fn contents<T: Clone>(list: &CircularList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = CircularList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_front(1);
+        assert_eq!(m.front(), Some(&1));
+        assert_eq!(m.back(), Some(&1));
+        assert_eq!(m.pop_back(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        m.push_front(1);
+        assert_eq!(contents(&m), &[1, 2, 3]);
+        *m.front_mut().unwrap() = 10;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(m.pop_front(), Some(10));
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(2));
+        assert!(m.is_empty());
+        check_ring(&m);
+
+        m.extend(0..5);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.front(), None);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut m = list_from(&[0, 1, 2, 3, 4]);
+        m.rotate_left(1);
+        assert_eq!(contents(&m), &[1, 2, 3, 4, 0]);
+        m.rotate_right(2);
+        assert_eq!(contents(&m), &[4, 0, 1, 2, 3]);
+        m.rotate_left(4);
+        assert_eq!(contents(&m), &[3, 4, 0, 1, 2]);
+        // goes all the way round as many times as it likes
+        m.rotate_left(12);
+        assert_eq!(contents(&m), &[0, 1, 2, 3, 4]);
+        m.rotate_right(5);
+        assert_eq!(contents(&m), &[0, 1, 2, 3, 4]);
+        // pushes and pops are relative to wherever the head is now
+        m.rotate_left(2);
+        m.push_back(5);
+        m.push_front(6);
+        assert_eq!(contents(&m), &[6, 2, 3, 4, 0, 1, 5]);
+        assert_eq!(m.pop_back(), Some(5));
+        check_ring(&m);
+
+        let mut empty: CircularList<i32> = CircularList::new();
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m: CircularList<i32> = (0..10).collect();
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            (0..10).rev().collect::<Vec<_>>()
+        );
+        let mut it = m.iter();
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&9));
+        assert_eq!(it.len(), 8);
+
+        for x in m.iter_mut() {
+            *x *= 2;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.next_back(), Some(&mut 18));
+        assert_eq!(it.next(), Some(&mut 0));
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(18));
+        assert_eq!(it.len(), 8);
+        assert_eq!(format!("{:?}", list_from(&[1, 2, 3])), "[1, 2, 3]");
+
+        // same ring, different head
+        let a = list_from(&[1, 2, 3]);
+        let mut b = list_from(&[2, 3, 1]);
+        assert_ne!(a, b);
+        b.rotate_right(1);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_cursor_wraps() {
+        let mut m = list_from(&[1, 2, 3]);
+        let mut cursor = m.cursor();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&3));
+        let mut seen = Vec::new();
+        for _ in 0..7 {
+            seen.push((*cursor.current().unwrap(), cursor.index().unwrap()));
+            cursor.move_next();
+        }
+        assert_eq!(
+            seen,
+            &[(1, 0), (2, 1), (3, 2), (1, 0), (2, 1), (3, 2), (1, 0)]
+        );
+        // we're on the 2 now, backwards from there wraps too
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+        assert_eq!(cursor.index(), Some(2));
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        cursor.make_head();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&2));
+        assert_eq!(contents(&m), &[3, 1, 2]);
+
+        let mut one = list_from(&[1]);
+        let mut cursor = one.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+
+        let mut empty: CircularList<i32> = CircularList::new();
+        let mut cursor = empty.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+    }
+
+    #[test]
+    fn test_round_robin() {
+        // every task runs in turn, takes one unit off what it needs, and leaves when done
+        let mut tasks = list_from(&[("a", 3), ("b", 1), ("c", 2)]);
+        let mut order = Vec::new();
+        let mut cursor = tasks.cursor_mut();
+        while let Some((name, left)) = cursor.current() {
+            order.push(*name);
+            *left -= 1;
+            if *left == 0 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(order, &["a", "b", "c", "a", "c", "a"]);
+        assert!(tasks.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_edits() {
+        let mut m = list_from(&[1, 2, 3]);
+        let mut cursor = m.cursor_mut();
+        // before the head is the back
+        cursor.insert_before(4);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.insert_after(5);
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_before(6);
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_ring(&m);
+        assert_eq!(contents(&m), &[1, 5, 6, 2, 3, 4]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(4));
+        // wrapped round to the head
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 5));
+        check_ring(&m);
+        assert_eq!(contents(&m), &[5, 6, 2, 3]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        let after = cursor.split_after();
+        check_ring(&m);
+        check_ring(&before);
+        check_ring(&after);
+        assert_eq!(contents(&before), &[5, 6]);
+        assert_eq!(contents(&m), &[2]);
+        assert_eq!(contents(&after), &[3]);
+
+        let mut cursor = m.cursor_mut();
+        assert!(cursor.split_before().is_empty());
+        assert!(cursor.split_after().is_empty());
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(0));
+        check_ring(&m);
+        assert_eq!(contents(&m), &[2, 3, 5, 6]);
+
+        let mut empty = CircularList::new();
+        let mut cursor = empty.cursor_mut();
+        cursor.splice_before(list_from(&[7, 8]));
+        assert_eq!(cursor.current(), Some(&mut 7));
+        cursor.insert_after(9);
+        check_ring(&empty);
+        assert_eq!(contents(&empty), &[7, 9, 8]);
+    }
+
+    #[test]
+    fn test_drops() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+        let mut m: CircularList<Rc<()>> = (0..10).map(|_| rc.clone()).collect();
+        {
+            let mut cursor = m.cursor_mut();
+            cursor.remove_current();
+            cursor.move_prev();
+            let _ = cursor.split_before();
+        }
+        assert_eq!(Rc::strong_count(&rc), 2);
+        drop(m);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    // following next len times gets back to the head, and prev undoes next everywhere
+    fn check_ring<T>(list: &CircularList<T>) {
+        let Some(head) = list.head else {
+            assert_eq!(list.len, 0);
+            return;
+        };
+        unsafe {
+            let mut cur = head;
+            for i in 0..list.len {
+                let next = (*cur.as_ptr()).next;
+                assert_eq!((*next.as_ptr()).prev, cur);
+                cur = next;
+                assert!(cur != head || i == list.len - 1);
+            }
+            assert_eq!(cur, head);
+        }
+    }
+}
@@ -0,0 +1,831 @@
+// a fixed capacity list that never allocates
+// all N nodes live inline in the list itself, in an array, and they link to each other by their
+// index in that array instead of by pointer. that's what lets the whole thing work without a heap:
+// a StaticList can sit on the stack or in a static (new is a const fn), and moving it is fine
+// because nothing points at an address.
+//
+// the links are u32 by default, or u16 to make the nodes smaller, and the biggest index (MAX) is
+// the null link, so N can be MAX at most. slots that aren't holding an element are either on
+// the free list (linked through their next field, nothing else in them is initialized) or have
+// never been used at all, those are unused..N. so a new list doesn't have to touch its array.
+//
+// everything here comes from core, so the module works as is in a no_std crate.
+use core::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::MaybeUninit,
+};
+
+// the integer types a StaticList can link with. the unsafe code below trusts to_usize to give
+// back what from_usize got and NONE to be the one index no node has, so nobody else gets to
+// implement it: the trait is sealed and u16 and u32 are all there is
+pub trait LinkIndex: sealed::Sealed + Copy + Eq + Debug {
+    const ZERO: Self;
+    // the null link
+    const NONE: Self;
+    // NONE as a usize, which is as big as N can go
+    const MAX_CAPACITY: usize;
+
+    fn from_usize(i: usize) -> Self;
+    fn to_usize(self) -> usize;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+impl LinkIndex for u16 {
+    const ZERO: Self = 0;
+    const NONE: Self = u16::MAX;
+    const MAX_CAPACITY: usize = u16::MAX as usize;
+
+    fn from_usize(i: usize) -> Self {
+        i as u16
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl LinkIndex for u32 {
+    const ZERO: Self = 0;
+    const NONE: Self = u32::MAX;
+    const MAX_CAPACITY: usize = u32::MAX as usize;
+
+    fn from_usize(i: usize) -> Self {
+        i as u32
+    }
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+pub struct StaticList<T, const N: usize, I: LinkIndex = u32> {
+    nodes: [MaybeUninit<Node<T, I>>; N],
+    front: I,
+    back: I,
+    // head of the free list
+    free: I,
+    // slots from here on have never been handed out
+    unused: I,
+    len: usize,
+}
+
+struct Node<T, I> {
+    prev: I,
+    next: I,
+    elem: T,
+}
+
+impl<T, const N: usize, I: LinkIndex> StaticList<T, N, I> {
+    pub const fn new() -> Self {
+        const {
+            assert!(
+                N <= I::MAX_CAPACITY,
+                "StaticList capacity doesn't fit its index type"
+            )
+        };
+        Self {
+            nodes: [const { MaybeUninit::uninit() }; N],
+            front: I::NONE,
+            back: I::NONE,
+            free: I::NONE,
+            unused: I::ZERO,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    // slots
+    // only ever called on slots that hold an element
+    fn node(&self, i: I) -> &Node<T, I> {
+        unsafe { self.nodes[i.to_usize()].assume_init_ref() }
+    }
+
+    fn node_mut(&mut self, i: I) -> &mut Node<T, I> {
+        unsafe { self.nodes[i.to_usize()].assume_init_mut() }
+    }
+
+    // a slot for elem, from the free list if there's anything on it. not linked in yet
+    fn alloc(&mut self, elem: T) -> Result<I, T> {
+        let i = if self.free != I::NONE {
+            let i = self.free;
+            // free slots only have their next initialized
+            self.free = unsafe { (&raw const (*self.nodes[i.to_usize()].as_ptr()).next).read() };
+            i
+        } else if self.unused.to_usize() < N {
+            let i = self.unused;
+            self.unused = I::from_usize(i.to_usize() + 1);
+            i
+        } else {
+            return Err(elem);
+        };
+        self.nodes[i.to_usize()].write(Node {
+            prev: I::NONE,
+            next: I::NONE,
+            elem,
+        });
+        Ok(i)
+    }
+
+    // moves the element out and puts the slot on the free list. it has to be unlinked already
+    fn dealloc(&mut self, i: I) -> T {
+        let free = self.free;
+        let slot = &mut self.nodes[i.to_usize()];
+        let node = unsafe { slot.assume_init_read() };
+        unsafe { (&raw mut (*slot.as_mut_ptr()).next).write(free) };
+        self.free = i;
+        node.elem
+    }
+
+    // links slot i in between prev and next, which are neighbours. NONE for prev or next means
+    // that end of the list
+    fn link_between(&mut self, prev: I, next: I, i: I) {
+        let node = self.node_mut(i);
+        node.prev = prev;
+        node.next = next;
+        if prev == I::NONE {
+            self.front = i;
+        } else {
+            self.node_mut(prev).next = i;
+        }
+        if next == I::NONE {
+            self.back = i;
+        } else {
+            self.node_mut(next).prev = i;
+        }
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, i: I) -> T {
+        let Node { prev, next, .. } = *self.node(i);
+        if prev == I::NONE {
+            self.front = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+        if next == I::NONE {
+            self.back = prev;
+        } else {
+            self.node_mut(next).prev = prev;
+        }
+        self.len -= 1;
+        self.dealloc(i)
+    }
+
+    // the ends
+    pub fn try_push_front(&mut self, elem: T) -> Result<(), T> {
+        let i = self.alloc(elem)?;
+        self.link_between(I::NONE, self.front, i);
+        Ok(())
+    }
+
+    pub fn try_push_back(&mut self, elem: T) -> Result<(), T> {
+        let i = self.alloc(elem)?;
+        self.link_between(self.back, I::NONE, i);
+        Ok(())
+    }
+
+    // these panic when the list is full
+    pub fn push_front(&mut self, elem: T) {
+        if self.try_push_front(elem).is_err() {
+            panic!("StaticList is full (capacity {N})");
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        if self.try_push_back(elem).is_err() {
+            panic!("StaticList is full (capacity {N})");
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        (self.front != I::NONE).then(|| self.unlink(self.front))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        (self.back != I::NONE).then(|| self.unlink(self.back))
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        (self.front != I::NONE).then(|| &self.node(self.front).elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        (self.front != I::NONE).then(|| &mut self.node_mut(self.front).elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        (self.back != I::NONE).then(|| &self.node(self.back).elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        (self.back != I::NONE).then(|| &mut self.node_mut(self.back).elem)
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T, const N: usize, I: LinkIndex> Default for StaticList<T, N, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// a panicking clone leaves a half built list behind, and dropping that drops exactly the clones
+// that made it in
+impl<T: Clone, const N: usize, I: LinkIndex> Clone for StaticList<T, N, I> {
+    fn clone(&self) -> Self {
+        let mut new_list = Self::new();
+        for elem in self {
+            new_list.push_back(elem.clone());
+        }
+        new_list
+    }
+}
+
+// panics if it doesn't fit, use try_push_back to find out nicely
+impl<T, const N: usize, I: LinkIndex> Extend<T> for StaticList<T, N, I> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T, const N: usize, I: LinkIndex> FromIterator<T> for StaticList<T, N, I> {
+    fn from_iter<It: IntoIterator<Item = T>>(iter: It) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug, const N: usize, I: LinkIndex> Debug for StaticList<T, N, I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, I: LinkIndex> PartialEq for StaticList<T, N, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize, I: LinkIndex> Eq for StaticList<T, N, I> {}
+
+impl<T: PartialOrd, const N: usize, I: LinkIndex> PartialOrd for StaticList<T, N, I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, const N: usize, I: LinkIndex> Ord for StaticList<T, N, I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash, const N: usize, I: LinkIndex> Hash for StaticList<T, N, I> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+// the array is only partly initialized, so only the slots that are linked in get dropped
+impl<T, const N: usize, I: LinkIndex> Drop for StaticList<T, N, I> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+pub struct Iter<'a, T, const N: usize, I: LinkIndex = u32> {
+    list: &'a StaticList<T, N, I>,
+    front: I,
+    back: I,
+    len: usize,
+}
+
+impl<T, const N: usize, I: LinkIndex> StaticList<T, N, I> {
+    pub fn iter(&self) -> Iter<'_, T, N, I> {
+        Iter {
+            list: self,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> IntoIterator for &'a StaticList<T, N, I> {
+    type IntoIter = Iter<'a, T, N, I>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> Iterator for Iter<'a, T, N, I> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.list.node(self.front);
+            self.len -= 1;
+            self.front = node.next;
+            Some(&node.elem)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> DoubleEndedIterator for Iter<'a, T, N, I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.list.node(self.back);
+            self.len -= 1;
+            self.back = node.prev;
+            Some(&node.elem)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> ExactSizeIterator for Iter<'a, T, N, I> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// handing out &mut to different elements of the same array needs a raw pointer to it. we only
+// ever read the links through it (by field, never a reference to a whole node) and each element
+// is handed out at most once
+pub struct IterMut<'a, T, const N: usize, I: LinkIndex = u32> {
+    nodes: *mut Node<T, I>,
+    front: I,
+    back: I,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<T, const N: usize, I: LinkIndex> StaticList<T, N, I> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N, I> {
+        IterMut {
+            nodes: self.nodes.as_mut_ptr().cast(),
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> IntoIterator for &'a mut StaticList<T, N, I> {
+    type IntoIter = IterMut<'a, T, N, I>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> Iterator for IterMut<'a, T, N, I> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            unsafe {
+                let node = self.nodes.add(self.front.to_usize());
+                self.len -= 1;
+                self.front = (*node).next;
+                Some(&mut (*node).elem)
+            }
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> DoubleEndedIterator for IterMut<'a, T, N, I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            unsafe {
+                let node = self.nodes.add(self.back.to_usize());
+                self.len -= 1;
+                self.back = (*node).prev;
+                Some(&mut (*node).elem)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> ExactSizeIterator for IterMut<'a, T, N, I> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T, const N: usize, I: LinkIndex = u32> {
+    list: StaticList<T, N, I>,
+}
+
+impl<T, const N: usize, I: LinkIndex> IntoIterator for StaticList<T, N, I> {
+    type IntoIter = IntoIter<T, N, I>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T, const N: usize, I: LinkIndex> Iterator for IntoIter<T, N, I> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, const N: usize, I: LinkIndex> DoubleEndedIterator for IntoIter<T, N, I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, const N: usize, I: LinkIndex> ExactSizeIterator for IntoIter<T, N, I> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// cursors
+// same ghost as sixth: walking off either end puts us on the ghost (cur is NONE), and from the
+// ghost we go round to the other end
+pub struct CursorMut<'a, T, const N: usize, I: LinkIndex = u32> {
+    list: &'a mut StaticList<T, N, I>,
+    cur: I,
+    index: Option<usize>,
+}
+
+impl<T, const N: usize, I: LinkIndex> StaticList<T, N, I> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, N, I> {
+        CursorMut {
+            list: self,
+            cur: I::NONE,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, N, I> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, N, I> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+}
+
+impl<'a, T, const N: usize, I: LinkIndex> CursorMut<'a, T, N, I> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if self.cur != I::NONE {
+            self.cur = self.list.node(self.cur).next;
+            self.index = self.index.map(|index| index + 1);
+            if self.cur == I::NONE {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if self.cur != I::NONE {
+            self.cur = self.list.node(self.cur).prev;
+            self.index = self.index.and_then(|index| index.checked_sub(1));
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    // index == len is the ghost
+    pub fn seek(&mut self, index: usize) {
+        let len = self.list.len;
+        assert!(
+            index <= len,
+            "cursor index (is {index}) should be <= len (is {len})"
+        );
+        self.cur = I::NONE;
+        self.index = None;
+        if index < len / 2 {
+            for _ in 0..=index {
+                self.move_next();
+            }
+        } else {
+            for _ in index..len {
+                self.move_prev();
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        (self.cur != I::NONE).then(|| &mut self.list.node_mut(self.cur).elem)
+    }
+
+    fn next_slot(&self) -> I {
+        if self.cur == I::NONE {
+            self.list.front
+        } else {
+            self.list.node(self.cur).next
+        }
+    }
+
+    fn prev_slot(&self) -> I {
+        if self.cur == I::NONE {
+            self.list.back
+        } else {
+            self.list.node(self.cur).prev
+        }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = self.next_slot();
+        (next != I::NONE).then(|| &mut self.list.node_mut(next).elem)
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = self.prev_slot();
+        (prev != I::NONE).then(|| &mut self.list.node_mut(prev).elem)
+    }
+
+    // on the ghost insert_before puts the element at the back and insert_after at the front,
+    // same as sixth. a full list hands the element back
+    pub fn try_insert_before(&mut self, elem: T) -> Result<(), T> {
+        let i = self.list.alloc(elem)?;
+        self.list.link_between(self.prev_slot(), self.cur, i);
+        if let Some(index) = self.index.as_mut() {
+            *index += 1;
+        }
+        Ok(())
+    }
+
+    pub fn try_insert_after(&mut self, elem: T) -> Result<(), T> {
+        let i = self.list.alloc(elem)?;
+        self.list.link_between(self.cur, self.next_slot(), i);
+        Ok(())
+    }
+
+    // takes out the current element and moves on to the next one, which takes over its index
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.cur == I::NONE {
+            return None;
+        }
+        let next = self.list.node(self.cur).next;
+        let elem = self.list.unlink(self.cur);
+        self.cur = next;
+        if next == I::NONE {
+            self.index = None;
+        }
+        Some(elem)
+    }
+}
+
+// IterMut is the only one with a raw pointer, so it's the only one that has to opt back in
+unsafe impl<'a, T: Send, const N: usize, I: LinkIndex> Send for IterMut<'a, T, N, I> {}
+unsafe impl<'a, T: Sync, const N: usize, I: LinkIndex> Sync for IterMut<'a, T, N, I> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<StaticList<i32, 4>>();
+    is_sync::<StaticList<i32, 4>>();
+
+    is_send::<IntoIter<i32, 4>>();
+    is_sync::<IntoIter<i32, 4>>();
+
+    is_send::<Iter<i32, 4>>();
+    is_sync::<Iter<i32, 4>>();
+
+    is_send::<IterMut<i32, 4>>();
+    is_sync::<IterMut<i32, 4>>();
+
+    is_send::<CursorMut<i32, 4>>();
+    is_sync::<CursorMut<i32, 4>>();
+
+    fn static_list_covariant<'a, T>(x: StaticList<&'static T, 4>) -> StaticList<&'a T, 4> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T, 4>) -> Iter<'i, &'a T, 4> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticList;
+    use std::{cell::Cell, panic, rc::Rc};
+
+    fn contents<T: Clone, const N: usize>(list: &StaticList<T, N, u16>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = StaticList::<i32, 4, u16>::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_back(2);
+        m.push_front(1);
+        m.push_back(3);
+        assert_eq!(m.try_push_front(0), Ok(()));
+        assert!(m.is_full());
+        assert_eq!(m.try_push_back(4), Err(4));
+        assert_eq!(m.try_push_front(4), Err(4));
+        assert_eq!(contents(&m), &[0, 1, 2, 3]);
+        *m.front_mut().unwrap() = 10;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(m.front(), Some(&10));
+        assert_eq!(m.back(), Some(&30));
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(10));
+        // the freed slots get used again
+        m.push_back(3);
+        m.push_front(0);
+        assert_eq!(m.try_push_back(4), Err(4));
+        assert_eq!(contents(&m), &[0, 1, 2, 3]);
+        for x in &mut m {
+            *x *= 2;
+        }
+        assert_eq!(
+            m.iter_mut().rev().map(|x| *x).collect::<Vec<_>>(),
+            &[6, 4, 2, 0]
+        );
+        assert_eq!(
+            m.clone().into_iter().rev().collect::<Vec<_>>(),
+            &[6, 4, 2, 0]
+        );
+        assert_eq!(format!("{m:?}"), "[0, 2, 4, 6]");
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.capacity(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_full() {
+        let mut m: StaticList<i32, 2> = (0..2).collect();
+        m.push_back(2);
+    }
+
+    #[test]
+    fn test_static() {
+        // no heap involved, it can be built at compile time
+        static EMPTY: StaticList<u8, 16, u16> = StaticList::new();
+        assert!(EMPTY.is_empty());
+        assert_eq!(EMPTY.iter().next(), None);
+        // u16 links keep the nodes small
+        assert!(size_of::<StaticList<u16, 8, u16>>() < size_of::<StaticList<u16, 8>>());
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut m: StaticList<i32, 6, u16> = [1, 3].into_iter().collect();
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.try_insert_after(2), Ok(()));
+        assert_eq!(cursor.try_insert_before(0), Ok(()));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), Some(&mut 0));
+        cursor.seek(3);
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+        assert_eq!(cursor.peek_prev(), Some(&mut 3));
+        // the ghost puts insert_before at the back and insert_after at the front
+        assert_eq!(cursor.try_insert_before(4), Ok(()));
+        assert_eq!(cursor.try_insert_after(-1), Ok(()));
+        assert_eq!(cursor.try_insert_after(-2), Err(-2));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(-1));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.seek(2);
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(contents(&m), &[0, 1, 3]);
+    }
+
+    #[test]
+    fn test_drop() {
+        // only what's in the list gets dropped, never a free or unused slot
+        let drops = Rc::new(Cell::new(0));
+        struct Noisy(Rc<Cell<usize>>);
+        impl Drop for Noisy {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let mut m = StaticList::<Noisy, 8, u16>::new();
+        for _ in 0..5 {
+            m.push_back(Noisy(drops.clone()));
+        }
+        drop(m.pop_front());
+        drop(m.cursor_front_mut().remove_current());
+        assert_eq!(drops.get(), 2);
+        drop(m);
+        assert_eq!(drops.get(), 5);
+
+        // a clone that panics halfway only drops the clones it made
+        struct PanicClone(Rc<Cell<usize>>, bool);
+        impl Clone for PanicClone {
+            fn clone(&self) -> Self {
+                assert!(!self.1);
+                PanicClone(self.0.clone(), false)
+            }
+        }
+        impl Drop for PanicClone {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        drops.set(0);
+        let mut m = StaticList::<PanicClone, 4>::new();
+        m.push_back(PanicClone(drops.clone(), false));
+        m.push_back(PanicClone(drops.clone(), false));
+        m.push_back(PanicClone(drops.clone(), true));
+        let m = panic::AssertUnwindSafe(m);
+        assert!(panic::catch_unwind(|| m.clone()).is_err());
+        assert_eq!(drops.get(), 2);
+        drop(m);
+        assert_eq!(drops.get(), 5);
+    }
+}
@@ -0,0 +1,655 @@
+// GHOSTCELL
+// fourth::List pays for every borrow at runtime (RefCell counts its borrows) and can't even give
+// out a plain Iter, because every element reference has to stay wrapped in a Ref guard. the
+// GhostCell idea is to split the permission from the data: the cells don't track anything, and
+// whoever holds the one GhostToken for their brand may read all of them (&token) or write any one
+// of them (&mut token). the brand is a lifetime that GhostToken::scope makes up fresh for its
+// closure, so it can't be mixed up with any other token's, and the borrow checker does all the
+// checking at compile time.
+//
+// the list below owns its token, so its &self methods can read every node and its &mut self
+// methods can write them, just like any owned collection. nodes are Rc'd both ways (there's no
+// RefCell anywhere) and NodeRef hands out a node for O(1) access later. a NodeRef carries the
+// brand, so the compiler won't let it near any other list.
+//
+// the only unsafe is in GhostCell itself and in IterMut (which hands out &mut to many cells for
+// the one token, see there). nothing a user calls needs unsafe.
+use std::{cell::UnsafeCell, fmt::Debug, marker::PhantomData, rc::Rc};
+
+// invariant in 'id, so one brand can never be turned into another
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+pub struct GhostToken<'id> {
+    _brand: Brand<'id>,
+}
+
+impl<'id> GhostToken<'id> {
+    // the only way to get a token. f has to work for any brand at all, so the one it gets is
+    // unlike every other, and nothing carrying it can leave f
+    pub fn scope<R>(f: impl for<'new> FnOnce(GhostToken<'new>) -> R) -> R {
+        f(GhostToken {
+            _brand: PhantomData,
+        })
+    }
+}
+
+pub struct GhostCell<'id, T: ?Sized> {
+    _brand: Brand<'id>,
+    value: UnsafeCell<T>,
+}
+
+impl<'id, T> GhostCell<'id, T> {
+    pub const fn new(value: T) -> Self {
+        GhostCell {
+            _brand: PhantomData,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<'id, T: ?Sized> GhostCell<'id, T> {
+    pub fn borrow<'a>(&'a self, _token: &'a GhostToken<'id>) -> &'a T {
+        // SAFETY: there is only one token for 'id, and we hold it shared for 'a, so nobody can
+        // borrow_mut any cell of this brand for as long as this lives
+        unsafe { &*self.value.get() }
+    }
+
+    pub fn borrow_mut<'a>(&'a self, _token: &'a mut GhostToken<'id>) -> &'a mut T {
+        // SAFETY: we hold the only token for 'id exclusively for 'a, so there is no other borrow
+        // of any cell of this brand while this lives
+        unsafe { &mut *self.value.get() }
+    }
+
+    // owning the cell is permission enough
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+// a cell is only ever read or written by whoever holds the token, so this is RwLock's rule
+unsafe impl<'id, T: ?Sized + Send> Send for GhostCell<'id, T> {}
+unsafe impl<'id, T: ?Sized + Send + Sync> Sync for GhostCell<'id, T> {}
+
+// THE LIST
+type NodeRc<'id, T> = Rc<GhostCell<'id, Node<'id, T>>>;
+type Link<'id, T> = Option<NodeRc<'id, T>>;
+
+struct Node<'id, T> {
+    prev: Link<'id, T>,
+    next: Link<'id, T>,
+    // None once the node is out of the list. a NodeRef can keep a node alive after that, so the
+    // element can't stay behind in it
+    elem: Option<T>,
+}
+
+pub struct GhostList<'id, T> {
+    token: GhostToken<'id>,
+    front: Link<'id, T>,
+    back: Link<'id, T>,
+    len: usize,
+}
+
+// a node of the list with brand 'id, for getting at it again later without walking. it keeps the
+// node alive but not the element: once that's removed the NodeRef just doesn't find anything
+pub struct NodeRef<'id, T>(NodeRc<'id, T>);
+
+impl<'id, T> Clone for NodeRef<'id, T> {
+    fn clone(&self) -> Self {
+        NodeRef(Rc::clone(&self.0))
+    }
+}
+
+// the same node, not the same element
+impl<'id, T> PartialEq for NodeRef<'id, T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<'id, T> Eq for NodeRef<'id, T> {}
+
+impl<'id, T> Debug for NodeRef<'id, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("NodeRef")
+            .field(&Rc::as_ptr(&self.0))
+            .finish()
+    }
+}
+
+impl<'id, T> GhostList<'id, T> {
+    // the list takes the token for its brand, so it's the only list with that brand
+    pub fn new(token: GhostToken<'id>) -> Self {
+        GhostList {
+            token,
+            front: None,
+            back: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // puts a new node in between prev and next, which are neighbours. None for prev or next
+    // means that end of the list
+    fn link_between(&mut self, prev: Link<'id, T>, next: Link<'id, T>, elem: T) -> NodeRef<'id, T> {
+        let new = Rc::new(GhostCell::new(Node {
+            prev: prev.clone(),
+            next: next.clone(),
+            elem: Some(elem),
+        }));
+        match prev {
+            Some(prev) => prev.borrow_mut(&mut self.token).next = Some(Rc::clone(&new)),
+            None => self.front = Some(Rc::clone(&new)),
+        }
+        match next {
+            Some(next) => next.borrow_mut(&mut self.token).prev = Some(Rc::clone(&new)),
+            None => self.back = Some(Rc::clone(&new)),
+        }
+        self.len += 1;
+        NodeRef(new)
+    }
+
+    // takes the node out and its element with it. the node's own links are cleared too, so no Rc
+    // cycle is left behind for anyone still holding on to it
+    fn unlink(&mut self, node: &NodeRc<'id, T>) -> T {
+        let node = node.borrow_mut(&mut self.token);
+        let (prev, next) = (node.prev.take(), node.next.take());
+        let elem = node.elem.take().unwrap();
+        match &next {
+            Some(next) => next.borrow_mut(&mut self.token).prev = prev.clone(),
+            None => self.back = prev.clone(),
+        }
+        match prev {
+            Some(prev) => prev.borrow_mut(&mut self.token).next = next,
+            None => self.front = next,
+        }
+        self.len -= 1;
+        elem
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.push_front_node(elem);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.push_back_node(elem);
+    }
+
+    pub fn push_front_node(&mut self, elem: T) -> NodeRef<'id, T> {
+        self.link_between(None, self.front.clone(), elem)
+    }
+
+    pub fn push_back_node(&mut self, elem: T) -> NodeRef<'id, T> {
+        self.link_between(self.back.clone(), None, elem)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let front = self.front.clone()?;
+        Some(self.unlink(&front))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let back = self.back.clone()?;
+        Some(self.unlink(&back))
+    }
+
+    // these are plain references, as many at once as we like, no guards
+    pub fn front(&self) -> Option<&T> {
+        self.front
+            .as_ref()
+            .map(|node| node.borrow(&self.token).elem.as_ref().unwrap())
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.back
+            .as_ref()
+            .map(|node| node.borrow(&self.token).elem.as_ref().unwrap())
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.front
+            .as_ref()
+            .map(|node| node.borrow_mut(&mut self.token).elem.as_mut().unwrap())
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.back
+            .as_ref()
+            .map(|node| node.borrow_mut(&mut self.token).elem.as_mut().unwrap())
+    }
+
+    // nodes
+    pub fn front_node(&self) -> Option<NodeRef<'id, T>> {
+        self.front.clone().map(NodeRef)
+    }
+
+    pub fn back_node(&self) -> Option<NodeRef<'id, T>> {
+        self.back.clone().map(NodeRef)
+    }
+
+    pub fn contains_node(&self, node: &NodeRef<'id, T>) -> bool {
+        self.get(node).is_some()
+    }
+
+    // None once the element has been removed
+    pub fn get<'a>(&'a self, node: &'a NodeRef<'id, T>) -> Option<&'a T> {
+        node.0.borrow(&self.token).elem.as_ref()
+    }
+
+    pub fn get_mut<'a>(&'a mut self, node: &'a NodeRef<'id, T>) -> Option<&'a mut T> {
+        node.0.borrow_mut(&mut self.token).elem.as_mut()
+    }
+
+    pub fn remove(&mut self, node: &NodeRef<'id, T>) -> Option<T> {
+        if !self.contains_node(node) {
+            return None;
+        }
+        Some(self.unlink(&node.0))
+    }
+
+    // a node that's been removed has no neighbours any more, so the element comes back
+    pub fn insert_before(&mut self, node: &NodeRef<'id, T>, elem: T) -> Result<NodeRef<'id, T>, T> {
+        if !self.contains_node(node) {
+            return Err(elem);
+        }
+        let prev = node.0.borrow(&self.token).prev.clone();
+        Ok(self.link_between(prev, Some(Rc::clone(&node.0)), elem))
+    }
+
+    pub fn insert_after(&mut self, node: &NodeRef<'id, T>, elem: T) -> Result<NodeRef<'id, T>, T> {
+        if !self.contains_node(node) {
+            return Err(elem);
+        }
+        let next = node.0.borrow(&self.token).next.clone();
+        Ok(self.link_between(Some(Rc::clone(&node.0)), next, elem))
+    }
+
+    // the nodes either side, None at the ends and for removed nodes
+    pub fn next_node(&self, node: &NodeRef<'id, T>) -> Option<NodeRef<'id, T>> {
+        node.0.borrow(&self.token).next.clone().map(NodeRef)
+    }
+
+    pub fn prev_node(&self, node: &NodeRef<'id, T>) -> Option<NodeRef<'id, T>> {
+        node.0.borrow(&self.token).prev.clone().map(NodeRef)
+    }
+}
+
+impl<'id, T> Extend<T> for GhostList<'id, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<'id, T: Debug> Debug for GhostList<'id, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<'id, T: PartialEq> PartialEq for GhostList<'id, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<'id, T: Eq> Eq for GhostList<'id, T> {}
+
+// the links are Rc's both ways, which is a cycle for every pair of neighbours, so they have to be
+// taken apart by hand
+impl<'id, T> Drop for GhostList<'id, T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+pub struct Iter<'a, 'id, T> {
+    token: &'a GhostToken<'id>,
+    front: Option<&'a NodeRc<'id, T>>,
+    back: Option<&'a NodeRc<'id, T>>,
+    len: usize,
+}
+
+impl<'id, T> GhostList<'id, T> {
+    pub fn iter(&self) -> Iter<'_, 'id, T> {
+        Iter {
+            token: &self.token,
+            front: self.front.as_ref(),
+            back: self.back.as_ref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, 'id, T> IntoIterator for &'a GhostList<'id, T> {
+    type IntoIter = Iter<'a, 'id, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, 'id, T> Iterator for Iter<'a, 'id, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.front?.borrow(self.token);
+            self.len -= 1;
+            self.front = node.next.as_ref();
+            node.elem.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, 'id, T> DoubleEndedIterator for Iter<'a, 'id, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.back?.borrow(self.token);
+            self.len -= 1;
+            self.back = node.prev.as_ref();
+            node.elem.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, 'id, T> ExactSizeIterator for Iter<'a, 'id, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// borrow_mut would give us one &mut for the whole &mut token, but an iterator has to hand out a
+// new one every time while the old ones are still around. so this goes to the cells directly:
+// holding the token exclusively for 'a means nobody else can touch any node, and front and back
+// meet after len elements so every element is handed out at most once. the links are only ever
+// read, through shared references to just the link fields
+pub struct IterMut<'a, 'id, T> {
+    _token: &'a mut GhostToken<'id>,
+    front: Option<&'a NodeRc<'id, T>>,
+    back: Option<&'a NodeRc<'id, T>>,
+    len: usize,
+}
+
+impl<'id, T> GhostList<'id, T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, 'id, T> {
+        IterMut {
+            _token: &mut self.token,
+            front: self.front.as_ref(),
+            back: self.back.as_ref(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, 'id, T> IntoIterator for &'a mut GhostList<'id, T> {
+    type IntoIter = IterMut<'a, 'id, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, 'id, T> Iterator for IterMut<'a, 'id, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.front?.as_ptr();
+            self.len -= 1;
+            // SAFETY: see IterMut
+            unsafe {
+                self.front = (*node).next.as_ref();
+                (*node).elem.as_mut()
+            }
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, 'id, T> DoubleEndedIterator for IterMut<'a, 'id, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            let node = self.back?.as_ptr();
+            self.len -= 1;
+            // SAFETY: see IterMut
+            unsafe {
+                self.back = (*node).prev.as_ref();
+                (*node).elem.as_mut()
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, 'id, T> ExactSizeIterator for IterMut<'a, 'id, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<'id, T> {
+    list: GhostList<'id, T>,
+}
+
+impl<'id, T> IntoIterator for GhostList<'id, T> {
+    type IntoIter = IntoIter<'id, T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'id, T> Iterator for IntoIter<'id, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<'id, T> DoubleEndedIterator for IntoIter<'id, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<'id, T> ExactSizeIterator for IntoIter<'id, T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    // the token is just a brand, it can go anywhere. the list is Rc based so it stays put
+    is_send::<GhostToken<'static>>();
+    is_sync::<GhostToken<'static>>();
+
+    is_send::<GhostCell<'static, i32>>();
+    is_sync::<GhostCell<'static, i32>>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GhostCell, GhostList, GhostToken};
+
+    fn contents<T: Clone>(list: &GhostList<'_, T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_cell() {
+        GhostToken::scope(|mut token| {
+            let a = GhostCell::new(1);
+            let b = GhostCell::new(2);
+            // any number of shared borrows at once
+            let (x, y) = (a.borrow(&token), b.borrow(&token));
+            assert_eq!(x + y, 3);
+            *a.borrow_mut(&mut token) += 10;
+            *b.borrow_mut(&mut token) += 20;
+            assert_eq!((a.borrow(&token), b.borrow(&token)), (&11, &22));
+            let mut c = GhostCell::new(String::from("c"));
+            c.get_mut().push('!');
+            assert_eq!(c.into_inner(), "c!");
+        });
+    }
+
+    #[test]
+    fn test_basic() {
+        GhostToken::scope(|token| {
+            let mut m = GhostList::new(token);
+            assert_eq!(m.pop_front(), None);
+            assert_eq!(m.pop_back(), None);
+            m.push_back(2);
+            m.push_front(1);
+            m.push_back(3);
+            assert_eq!(contents(&m), &[1, 2, 3]);
+            // plain references to both ends at once
+            let (front, back) = (m.front().unwrap(), m.back().unwrap());
+            assert_eq!(front + back, 4);
+            *m.front_mut().unwrap() = 10;
+            *m.back_mut().unwrap() = 30;
+            assert_eq!(m.pop_front(), Some(10));
+            assert_eq!(m.pop_back(), Some(30));
+            assert_eq!(m.pop_back(), Some(2));
+            assert!(m.is_empty());
+            assert_eq!(m.front(), None);
+
+            m.extend(0..4);
+            assert_eq!(format!("{m:?}"), "[0, 1, 2, 3]");
+            assert_eq!(m.len(), 4);
+            m.clear();
+            assert!(m.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_iter() {
+        GhostToken::scope(|token| {
+            let mut m = GhostList::new(token);
+            m.extend(0..6);
+            let mut iter = m.iter();
+            assert_eq!(iter.len(), 6);
+            assert_eq!(iter.next(), Some(&0));
+            assert_eq!(iter.next_back(), Some(&5));
+            // two iterators over the same list at once
+            assert!(m.iter().skip(1).take(4).eq(m.iter().skip(1).take(4)));
+            assert_eq!(m.iter().len(), 6);
+            assert_eq!(iter.rev().collect::<Vec<_>>(), &[&4, &3, &2, &1]);
+
+            for x in &mut m {
+                *x *= 10;
+            }
+            let mut iter = m.iter_mut();
+            let first = iter.next().unwrap();
+            let last = iter.next_back().unwrap();
+            // both still usable while the iterator carries on
+            *first += 1;
+            *last += 1;
+            assert_eq!(iter.map(|x| *x).collect::<Vec<_>>(), &[10, 20, 30, 40]);
+            assert_eq!(contents(&m), &[1, 10, 20, 30, 40, 51]);
+            assert_eq!(
+                m.into_iter().rev().collect::<Vec<_>>(),
+                &[51, 40, 30, 20, 10, 1]
+            );
+        });
+    }
+
+    #[test]
+    fn test_nodes() {
+        GhostToken::scope(|token| {
+            let mut m = GhostList::new(token);
+            let a = m.push_back_node('a');
+            let c = m.push_back_node('c');
+            let b = m.insert_before(&c, 'b').unwrap();
+            let d = m.insert_after(&c, 'd').unwrap();
+            assert_eq!(contents(&m), &['a', 'b', 'c', 'd']);
+            assert_eq!(m.front_node(), Some(a.clone()));
+            assert_eq!(m.back_node(), Some(d.clone()));
+            assert_eq!(m.next_node(&a), Some(b.clone()));
+            assert_eq!(m.prev_node(&a), None);
+            *m.get_mut(&b).unwrap() = 'B';
+            // node lookups are shared borrows too
+            assert_eq!((m.get(&a), m.get(&b)), (Some(&'a'), Some(&'B')));
+
+            assert_eq!(m.remove(&b), Some('B'));
+            assert_eq!(m.remove(&b), None);
+            assert!(!m.contains_node(&b));
+            assert_eq!(m.get(&b), None);
+            assert_eq!(m.next_node(&b), None);
+            assert_eq!(m.insert_after(&b, 'x'), Err('x'));
+            assert_eq!(m.prev_node(&c), Some(a.clone()));
+
+            // the ends come out through pops as well
+            assert_eq!(m.pop_front(), Some('a'));
+            assert_eq!(m.get(&a), None);
+            assert_eq!(m.front_node(), Some(c.clone()));
+            let e = m.insert_before(&c, 'e').unwrap();
+            assert_eq!(m.front_node(), Some(e));
+            assert_eq!(contents(&m), &['e', 'c', 'd']);
+            assert_eq!(m.iter().rev().collect::<String>(), "dce");
+        });
+    }
+
+    #[test]
+    fn test_drop() {
+        use std::rc::Rc;
+        let elem = Rc::new(());
+        GhostToken::scope(|token| {
+            let mut m = GhostList::new(token);
+            for _ in 0..10 {
+                m.push_back(Rc::clone(&elem));
+            }
+            // a node kept around after its list is gone holds no element and no neighbours
+            let kept = m.push_back_node(Rc::clone(&elem));
+            assert_eq!(Rc::strong_count(&elem), 12);
+            drop(m);
+            assert_eq!(Rc::strong_count(&elem), 1);
+            assert_eq!(Rc::strong_count(&kept.0), 1);
+        });
+    }
+}
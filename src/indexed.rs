@@ -0,0 +1,1177 @@
+// a list where finding index i doesn't mean walking i nodes
+// sixth::LinkedList has to walk for get(i), insert(i) and remove(i), which is O(n) no matter how
+// clever we are about which end to start from. to do better the nodes need to know something
+// about how many nodes are around them, so here the nodes aren't in a chain at all, they are in a
+// tree where every node knows how many nodes its subtree has (size). the list order is the
+// in order walk of the tree (left subtree, node, right subtree), so
+// - the node at index i: if i < size(left) go left, if i == size(left) it's this one, otherwise go
+//   right looking for i - size(left) - 1
+// - the index of a node: size(left) plus, for every parent we are the right child of, the size of
+//   its left subtree + 1
+// both are O(height).
+//
+// to keep the height O(log n) the tree is a treap: every node also gets a random priority and
+// parents always have a bigger priority than their children. random priorities give a tree of
+// expected height O(log n) whatever order things get inserted in. and treaps have two very handy
+// operations
+// - split(t, k): cut t into the first k nodes and the rest
+// - join(a, b): glue two trees together, a's nodes first
+// both O(log n), and those are exactly split_before/split_after and splice_before/splice_after.
+// insert and remove are a couple of splits and joins too.
+//
+// the cursor remembers its index, so index() stays O(1). moving to the next node is walking to
+// the in order successor, which is O(1) amortized over a walk through the list
+use crate::rng::{self, new_seed};
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+pub struct IndexedList<T> {
+    root: Link<T>,
+    // state of the random number generator for priorities
+    rng: u64,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    parent: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+    // number of nodes in the subtree rooted here, including this one
+    size: usize,
+    priority: u64,
+    elem: T,
+}
+
+// the tree surgery. all of these work on raw subtrees and never look at the list itself
+//
+// SAFETY (for every function down to the impl): every link passed in points to a live node of a
+// well formed tree that nobody else is looking at
+unsafe fn size<T>(link: Link<T>) -> usize {
+    unsafe { link.map_or(0, |node| (*node.as_ptr()).size) }
+}
+
+// recompute size after the children changed
+unsafe fn update<T>(node: NonNull<Node<T>>) {
+    unsafe {
+        (*node.as_ptr()).size = 1 + size((*node.as_ptr()).left) + size((*node.as_ptr()).right);
+    }
+}
+
+unsafe fn set_left<T>(node: NonNull<Node<T>>, child: Link<T>) {
+    unsafe {
+        (*node.as_ptr()).left = child;
+        if let Some(child) = child {
+            (*child.as_ptr()).parent = Some(node);
+        }
+    }
+}
+
+unsafe fn set_right<T>(node: NonNull<Node<T>>, child: Link<T>) {
+    unsafe {
+        (*node.as_ptr()).right = child;
+        if let Some(child) = child {
+            (*child.as_ptr()).parent = Some(node);
+        }
+    }
+}
+
+// everything in a comes before everything in b. the one with the bigger priority at the top
+// becomes the root and we keep joining down its inner side
+unsafe fn merge<T>(a: Link<T>, b: Link<T>) -> Link<T> {
+    unsafe {
+        match (a, b) {
+            (None, b) => b,
+            (a, None) => a,
+            (Some(x), Some(y)) => {
+                if (*x.as_ptr()).priority > (*y.as_ptr()).priority {
+                    set_right(x, merge((*x.as_ptr()).right, Some(y)));
+                    update(x);
+                    Some(x)
+                } else {
+                    set_left(y, merge(Some(x), (*y.as_ptr()).left));
+                    update(y);
+                    Some(y)
+                }
+            }
+        }
+    }
+}
+
+// the first k nodes go left, the rest right
+unsafe fn split<T>(t: Link<T>, k: usize) -> (Link<T>, Link<T>) {
+    unsafe {
+        match t {
+            None => (None, None),
+            Some(node) => {
+                let left_size = size((*node.as_ptr()).left);
+                if k <= left_size {
+                    let (l, r) = split((*node.as_ptr()).left, k);
+                    set_left(node, r);
+                    update(node);
+                    (l, Some(node))
+                } else {
+                    let (l, r) = split((*node.as_ptr()).right, k - left_size - 1);
+                    set_right(node, l);
+                    update(node);
+                    (Some(node), r)
+                }
+            }
+        }
+    }
+}
+
+// merge and split leave whatever parent the new roots used to have, roots have none
+unsafe fn make_root<T>(link: Link<T>) -> Link<T> {
+    unsafe {
+        if let Some(node) = link {
+            (*node.as_ptr()).parent = None;
+        }
+        link
+    }
+}
+
+unsafe fn join<T>(a: Link<T>, b: Link<T>) -> Link<T> {
+    unsafe { make_root(merge(a, b)) }
+}
+
+unsafe fn cut<T>(t: Link<T>, k: usize) -> (Link<T>, Link<T>) {
+    unsafe {
+        let (l, r) = split(t, k);
+        (make_root(l), make_root(r))
+    }
+}
+
+unsafe fn node_at<T>(t: Link<T>, mut k: usize) -> NonNull<Node<T>> {
+    unsafe {
+        let mut node = t.unwrap();
+        loop {
+            let left_size = size((*node.as_ptr()).left);
+            match k.cmp(&left_size) {
+                Ordering::Less => node = (*node.as_ptr()).left.unwrap(),
+                Ordering::Equal => return node,
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    node = (*node.as_ptr()).right.unwrap();
+                }
+            }
+        }
+    }
+}
+
+unsafe fn first<T>(t: Link<T>) -> Link<T> {
+    unsafe {
+        let mut node = t?;
+        while let Some(left) = (*node.as_ptr()).left {
+            node = left;
+        }
+        Some(node)
+    }
+}
+
+unsafe fn last<T>(t: Link<T>) -> Link<T> {
+    unsafe {
+        let mut node = t?;
+        while let Some(right) = (*node.as_ptr()).right {
+            node = right;
+        }
+        Some(node)
+    }
+}
+
+// in order successor: the first node of the right subtree, or else the first parent we reach
+// coming up from a left child
+unsafe fn next_node<T>(node: NonNull<Node<T>>) -> Link<T> {
+    unsafe {
+        if (*node.as_ptr()).right.is_some() {
+            return first((*node.as_ptr()).right);
+        }
+        let mut child = node;
+        while let Some(parent) = (*child.as_ptr()).parent {
+            if (*parent.as_ptr()).left == Some(child) {
+                return Some(parent);
+            }
+            child = parent;
+        }
+        None
+    }
+}
+
+unsafe fn prev_node<T>(node: NonNull<Node<T>>) -> Link<T> {
+    unsafe {
+        if (*node.as_ptr()).left.is_some() {
+            return last((*node.as_ptr()).left);
+        }
+        let mut child = node;
+        while let Some(parent) = (*child.as_ptr()).parent {
+            if (*parent.as_ptr()).right == Some(child) {
+                return Some(parent);
+            }
+            child = parent;
+        }
+        None
+    }
+}
+
+impl<T> IndexedList<T> {
+    pub fn new() -> Self {
+        IndexedList {
+            root: None,
+            rng: new_seed(),
+            _boo: PhantomData,
+        }
+    }
+
+    // a list made out of an already built tree
+    fn from_root(root: Link<T>) -> Self {
+        IndexedList {
+            root,
+            rng: new_seed(),
+            _boo: PhantomData,
+        }
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        rng::next(&mut self.rng)
+    }
+
+    fn new_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        let priority = self.next_priority();
+        unsafe {
+            NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                parent: None,
+                left: None,
+                right: None,
+                size: 1,
+                priority,
+                elem,
+            })))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { size(self.root) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let new = self.new_node(elem);
+        unsafe {
+            self.root = join(Some(new), self.root);
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let new = self.new_node(elem);
+        unsafe {
+            self.root = join(self.root, Some(new));
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        unsafe {
+            let (front, rest) = cut(self.root, 1);
+            self.root = rest;
+            let boxed_node = Box::from_raw(front.unwrap().as_ptr());
+            Some(boxed_node.elem)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        unsafe {
+            let (rest, back) = cut(self.root, len - 1);
+            self.root = rest;
+            let boxed_node = Box::from_raw(back.unwrap().as_ptr());
+            Some(boxed_node.elem)
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { first(self.root).map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { first(self.root).map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { last(self.root).map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { last(self.root).map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // the index based api, all O(log n)
+    pub fn get(&self, at: usize) -> Option<&T> {
+        if at < self.len() {
+            unsafe { Some(&(*node_at(self.root, at).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, at: usize) -> Option<&mut T> {
+        if at < self.len() {
+            unsafe { Some(&mut (*node_at(self.root, at).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, at: usize, elem: T) {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "insertion index (is {at}) should be <= len (is {len})"
+        );
+        let new = self.new_node(elem);
+        unsafe {
+            let (l, r) = cut(self.root, at);
+            self.root = join(join(l, Some(new)), r);
+        }
+    }
+
+    pub fn remove(&mut self, at: usize) -> T {
+        let len = self.len();
+        assert!(
+            at < len,
+            "removal index (is {at}) should be < len (is {len})"
+        );
+        unsafe {
+            let (l, r) = cut(self.root, at);
+            let (node, r) = cut(r, 1);
+            self.root = join(l, r);
+            let boxed_node = Box::from_raw(node.unwrap().as_ptr());
+            boxed_node.elem
+        }
+    }
+
+    // everything from index at onwards is returned and we keep [0, at)
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "split index (is {at}) should be <= len (is {len})"
+        );
+        unsafe {
+            let (l, r) = cut(self.root, at);
+            self.root = l;
+            Self::from_root(r)
+        }
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        unsafe {
+            self.root = join(self.root, other.root.take());
+        }
+    }
+}
+
+// traits, same set as sixth
+impl<T> Default for IndexedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for IndexedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for IndexedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for IndexedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for IndexedList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for IndexedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for IndexedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for IndexedList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for IndexedList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for IndexedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+// tearing the whole tree down without recursion and without popping one by one (each pop is a
+// split). go down taking the child pointer out of the parent as we go, free leaves, and climb
+// back up through the parent pointer. the child we came from is already gone from the parent
+impl<T> Drop for IndexedList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.root.take();
+        unsafe {
+            while let Some(node) = cur {
+                if let Some(left) = (*node.as_ptr()).left.take() {
+                    cur = Some(left);
+                } else if let Some(right) = (*node.as_ptr()).right.take() {
+                    cur = Some(right);
+                } else {
+                    cur = (*node.as_ptr()).parent;
+                    drop(Box::from_raw(node.as_ptr()));
+                }
+            }
+        }
+    }
+}
+
+// iterators walk successors from both ends, and like sixth the len says when they meet
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<T> IndexedList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        unsafe {
+            Iter {
+                front: first(self.root),
+                back: last(self.root),
+                len: self.len(),
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IndexedList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = next_node(node);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = prev_node(node);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<T> IndexedList<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        unsafe {
+            IterMut {
+                front: first(self.root),
+                back: last(self.root),
+                len: self.len(),
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut IndexedList<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = next_node(node);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = prev_node(node);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: IndexedList<T>,
+}
+
+impl<T> IntoIterator for IndexedList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+}
+
+// cursors
+// same rules as sixth: there is a ghost between the back and the front, index is None on the
+// ghost, walking off either end lands on the ghost
+pub struct CursorMut<'a, T> {
+    cur: Link<T>,
+    list: &'a mut IndexedList<T>,
+    index: Option<usize>,
+}
+
+impl<T> IndexedList<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+
+    // index == len puts us on the ghost, anything past that panics
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.seek(index);
+        cursor
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // O(log n) from anywhere, no need to be clever about where to start from like sixth
+    pub fn seek(&mut self, index: usize) {
+        let len = self.list.len();
+        assert!(
+            index <= len,
+            "cursor index (is {index}) should be <= len (is {len})"
+        );
+        if index == len {
+            self.cur = None;
+            self.index = None;
+        } else {
+            self.cur = unsafe { Some(node_at(self.list.root, index)) };
+            self.index = Some(index);
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { next_node(cur) };
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() += 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = unsafe { first(self.list.root) };
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { prev_node(cur) };
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() -= 1;
+            } else {
+                self.index = None;
+            }
+        } else if !self.list.is_empty() {
+            self.cur = unsafe { last(self.list.root) };
+            self.index = Some(self.list.len() - 1);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => next_node(cur),
+                None => first(self.list.root),
+            };
+            next.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => prev_node(cur),
+                None => last(self.list.root),
+            };
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    // splits and splices are a cut at our index and joins, our node stays where it is so cur
+    // is still good afterwards
+    pub fn split_before(&mut self) -> IndexedList<T> {
+        match self.index {
+            Some(index) => unsafe {
+                let (before, rest) = cut(self.list.root, index);
+                self.list.root = rest;
+                self.index = Some(0);
+                IndexedList::from_root(before)
+            },
+            None => std::mem::take(self.list),
+        }
+    }
+
+    pub fn split_after(&mut self) -> IndexedList<T> {
+        match self.index {
+            Some(index) => unsafe {
+                let (rest, after) = cut(self.list.root, index + 1);
+                self.list.root = rest;
+                IndexedList::from_root(after)
+            },
+            None => std::mem::take(self.list),
+        }
+    }
+
+    // on the ghost splice_before goes on the back and splice_after on the front, like sixth
+    pub fn splice_before(&mut self, mut input: IndexedList<T>) {
+        let input_len = input.len();
+        let input = input.root.take();
+        unsafe {
+            match self.index {
+                Some(index) => {
+                    let (before, rest) = cut(self.list.root, index);
+                    self.list.root = join(join(before, input), rest);
+                    self.index = Some(index + input_len);
+                }
+                None => self.list.root = join(self.list.root, input),
+            }
+        }
+    }
+
+    pub fn splice_after(&mut self, mut input: IndexedList<T>) {
+        let input = input.root.take();
+        unsafe {
+            match self.index {
+                Some(index) => {
+                    let (rest, after) = cut(self.list.root, index + 1);
+                    self.list.root = join(join(rest, input), after);
+                }
+                None => self.list.root = join(input, self.list.root),
+            }
+        }
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        let new = self.list.new_node(elem);
+        self.splice_before(IndexedList::from_root(Some(new)));
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let new = self.list.new_node(elem);
+        self.splice_after(IndexedList::from_root(Some(new)));
+    }
+
+    // takes the current node out and moves on to the next one, which takes over our index
+    pub fn remove_current(&mut self) -> Option<T> {
+        self.remove_current_as_list()
+            .map(|mut list| list.pop_front().unwrap())
+    }
+
+    pub fn remove_current_as_list(&mut self) -> Option<IndexedList<T>> {
+        let cur = self.cur?;
+        let index = self.index.unwrap();
+        unsafe {
+            let next = next_node(cur);
+            let (before, rest) = cut(self.list.root, index);
+            let (removed, after) = cut(rest, 1);
+            self.list.root = join(before, after);
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+            Some(IndexedList::from_root(removed))
+        }
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send> Send for IndexedList<T> {}
+unsafe impl<T: Sync> Sync for IndexedList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<IndexedList<i32>>();
+    is_sync::<IndexedList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    fn indexed_list_covariant<'a, T>(x: IndexedList<&'static T>) -> IndexedList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IndexedList, Link, size};
+    use crate::rng::TestRng;
+
+    fn list_from<T: Clone>(v: &[T]) -> IndexedList<T> {
+        v.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = IndexedList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.front(), Some(&2));
+        assert_eq!(m.back(), Some(&3));
+        *m.front_mut().unwrap() = 20;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(20));
+        assert!(m.is_empty());
+        check_tree(&m);
+
+        m.extend([1, 2, 3]);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.front(), None);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m: IndexedList<i32> = (0..10).collect();
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            (0..10).rev().collect::<Vec<_>>()
+        );
+        let mut it = m.iter();
+        assert_eq!(it.size_hint(), (10, Some(10)));
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&9));
+        assert_eq!(it.len(), 8);
+
+        for x in m.iter_mut() {
+            *x *= 2;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.next_back(), Some(&mut 18));
+        assert_eq!(it.next(), Some(&mut 0));
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(18));
+        assert_eq!(it.len(), 8);
+        assert_eq!(format!("{:?}", list_from(&[1, 2, 3])), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_traits() {
+        let a = list_from(&[1, 2, 3]);
+        let b = list_from(&[1, 2, 4]);
+        assert!(a != b);
+        assert!(a < b);
+        assert_eq!(a, a.clone());
+        let mut map = std::collections::HashMap::new();
+        map.insert(a.clone(), "a");
+        assert_eq!(map.get(&a), Some(&"a"));
+    }
+
+    #[test]
+    fn test_index_ops() {
+        let mut m: IndexedList<i32> = IndexedList::new();
+        m.insert(0, 2);
+        m.insert(0, 0);
+        m.insert(1, 1);
+        m.insert(3, 4);
+        m.insert(3, 3);
+        check_tree(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+        assert_eq!(m.get(4), Some(&4));
+        assert_eq!(m.get(5), None);
+        *m.get_mut(2).unwrap() = 20;
+        assert_eq!(m.remove(2), 20);
+        assert_eq!(m.remove(0), 0);
+        check_tree(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3, 4]);
+
+        let mut tail = m.split_off(1);
+        check_tree(&m);
+        check_tree(&tail);
+        assert_eq!(tail.iter().cloned().collect::<Vec<_>>(), &[3, 4]);
+        m.append(&mut tail);
+        assert!(tail.is_empty());
+        check_tree(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 3) should be < len (is 3)")]
+    fn test_remove_out_of_bounds() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.remove(3);
+    }
+
+    #[test]
+    fn test_random_ops_against_vec() {
+        let mut rng = TestRng(0x2545F4914F6CDD1D);
+        let mut m: IndexedList<usize> = IndexedList::new();
+        let mut v: Vec<usize> = Vec::new();
+        for step in 0..2000 {
+            match rng.below(5) {
+                0 | 1 => {
+                    let at = rng.below(v.len() + 1);
+                    m.insert(at, step);
+                    v.insert(at, step);
+                }
+                2 if !v.is_empty() => {
+                    let at = rng.below(v.len());
+                    assert_eq!(m.remove(at), v.remove(at));
+                }
+                3 if !v.is_empty() => {
+                    let at = rng.below(v.len());
+                    assert_eq!(m.get(at), v.get(at));
+                }
+                _ => {
+                    let at = rng.below(v.len() + 1);
+                    let mut tail = m.split_off(at);
+                    m.append(&mut tail);
+                }
+            }
+            assert_eq!(m.len(), v.len());
+        }
+        check_tree(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    fn test_stays_shallow() {
+        // pushing in order is the worst case for a tree without balancing
+        let m: IndexedList<u32> = (0..4096).collect();
+        check_tree(&m);
+        // expected height is about 3 * log2(n) = 36, a chain would be 4096
+        assert!(height(m.root) < 100);
+    }
+
+    #[test]
+    fn test_cursor_move_peek() {
+        let mut m: IndexedList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.index(), Some(4));
+
+        for i in 0..6 {
+            let mut cursor = m.cursor_at_mut(i);
+            assert_eq!(cursor.index(), Some(i));
+            assert_eq!(cursor.current(), Some(&mut (i as u32 + 1)));
+            cursor.seek(5 - i);
+            assert_eq!(cursor.current(), Some(&mut (6 - i as u32)));
+        }
+        assert_eq!(m.cursor_at_mut(6).current(), None);
+        assert_eq!(m.cursor_front_mut().current(), Some(&mut 1));
+        assert_eq!(m.cursor_back_mut().current(), Some(&mut 6));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert() {
+        // same story as sixth's test
+        let mut m: IndexedList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        assert_eq!(cursor.index(), Some(1));
+        check_tree(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[7, 1, 8, 2, 3, 4, 5, 6]
+        );
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        check_tree(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
+        );
+
+        let mut cursor = m.cursor_mut();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(7));
+        assert_eq!(cursor.index(), Some(1));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        check_tree(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 8, 2, 3, 4, 5, 6]
+        );
+
+        let mut cursor = m.cursor_at_mut(1);
+        cursor.insert_before(100);
+        cursor.insert_after(200);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        let after = cursor.split_after();
+        check_tree(&m);
+        check_tree(&before);
+        check_tree(&after);
+        assert_eq!(before.iter().cloned().collect::<Vec<_>>(), &[1, 100]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[8]);
+        assert_eq!(
+            after.iter().cloned().collect::<Vec<_>>(),
+            &[200, 2, 3, 4, 5, 6]
+        );
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(2));
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(removed.iter().cloned().collect::<Vec<_>>(), &[8]);
+        assert_eq!(cursor.current(), Some(&mut 200));
+        check_tree(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 100, 200, 2, 3, 4, 5, 6]
+        );
+    }
+
+    fn height<T>(link: Link<T>) -> usize {
+        unsafe {
+            link.map_or(0, |node| {
+                1 + height((*node.as_ptr()).left).max(height((*node.as_ptr()).right))
+            })
+        }
+    }
+
+    // sizes add up, parents point back at their children, priorities are a heap, and the root
+    // has no parent
+    fn check_tree<T>(list: &IndexedList<T>) {
+        fn check<T>(link: Link<T>) {
+            unsafe {
+                if let Some(node) = link {
+                    let n = &*node.as_ptr();
+                    assert_eq!(n.size, 1 + size(n.left) + size(n.right));
+                    for child in [n.left, n.right].into_iter().flatten() {
+                        assert_eq!((*child.as_ptr()).parent, Some(node));
+                        assert!((*child.as_ptr()).priority <= n.priority);
+                    }
+                    check(n.left);
+                    check(n.right);
+                }
+            }
+        }
+        if let Some(root) = list.root {
+            unsafe {
+                assert_eq!((*root.as_ptr()).parent, None);
+            }
+        }
+        check(list.root);
+        assert_eq!(list.iter().count(), list.len());
+        assert_eq!(list.iter().rev().count(), list.len());
+    }
+}
@@ -0,0 +1,861 @@
+// INTRUSIVE
+// every other list in here allocates a node per element and puts the element inside it. an
+// intrusive list turns that around: the links live inside the user's own struct (a Links field)
+// and the list only strings those together. no allocation per element, and an object with two
+// Links fields can sit in two lists at once.
+//
+// the list owns the objects through some pointer (Pin<Box>, Pin<Rc>, Pin<Arc> or Pin<&'static>).
+// the Pin is the important bit: our neighbours point straight at the Links inside an object, so
+// that object must never move while it's linked. the pointer is turned into a raw pointer when
+// it goes into the list and back into the same pointer when it comes out, like sixth does with
+// its Box'd nodes.
+//
+// the Adapter says which field of which type holds the Links, so one type can have several.
+// intrusive_adapter! writes one for you.
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    marker::{PhantomData, PhantomPinned},
+    pin::Pin,
+    ptr::NonNull,
+    rc::Rc,
+    sync::Arc,
+};
+
+// the links are Cells because with Rc or &'static we only ever get shared references to the
+// objects, and we still have to rewire them
+pub struct Links {
+    prev: Cell<Option<NonNull<Links>>>,
+    next: Cell<Option<NonNull<Links>>>,
+    linked: Cell<bool>,
+    _pin: PhantomPinned,
+}
+
+impl Links {
+    pub const fn new() -> Self {
+        Links {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            linked: Cell::new(false),
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.linked.get()
+    }
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// a copy of an object isn't in any list, so its links start out empty. this lets the user's
+// struct derive Clone
+impl Clone for Links {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Links {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Links")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+// the links are only touched by the list that owns the object, and that goes wherever the list
+// goes
+unsafe impl Send for Links {}
+
+// an owning pointer the list can hold on to as a raw pointer while the object is linked
+/// # Safety
+/// into_raw must give a pointer that stays valid (and put) until from_raw takes it back
+pub unsafe trait Pointer {
+    type Target;
+
+    fn into_raw(self) -> *const Self::Target;
+
+    /// # Safety
+    /// ptr has to come from into_raw of this same pointer type, and only once
+    unsafe fn from_raw(ptr: *const Self::Target) -> Self;
+}
+
+unsafe impl<T> Pointer for Pin<Box<T>> {
+    type Target = T;
+
+    fn into_raw(self) -> *const T {
+        // we never move out of the Box, we only hand it back pinned
+        Box::into_raw(unsafe { Pin::into_inner_unchecked(self) })
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        unsafe { Pin::new_unchecked(Box::from_raw(ptr as *mut T)) }
+    }
+}
+
+unsafe impl<T> Pointer for Pin<Rc<T>> {
+    type Target = T;
+
+    fn into_raw(self) -> *const T {
+        Rc::into_raw(unsafe { Pin::into_inner_unchecked(self) })
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        unsafe { Pin::new_unchecked(Rc::from_raw(ptr)) }
+    }
+}
+
+unsafe impl<T> Pointer for Pin<Arc<T>> {
+    type Target = T;
+
+    fn into_raw(self) -> *const T {
+        Arc::into_raw(unsafe { Pin::into_inner_unchecked(self) })
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        unsafe { Pin::new_unchecked(Arc::from_raw(ptr)) }
+    }
+}
+
+unsafe impl<T> Pointer for Pin<&'static T> {
+    type Target = T;
+
+    fn into_raw(self) -> *const T {
+        Pin::get_ref(self)
+    }
+
+    unsafe fn from_raw(ptr: *const T) -> Self {
+        unsafe { Pin::new_unchecked(&*ptr) }
+    }
+}
+
+type Value<A> = <<A as Adapter>::Pointer as Pointer>::Target;
+
+// ties a pointer type to the Links field the list should use
+/// # Safety
+/// get_links has to find the same Links field inside value every time, and get_value has to be
+/// its exact inverse
+pub unsafe trait Adapter {
+    type Pointer: Pointer;
+
+    /// # Safety
+    /// value points to a live object
+    unsafe fn get_links(value: *const Value<Self>) -> *const Links;
+
+    /// # Safety
+    /// links came from get_links
+    unsafe fn get_value(links: *const Links) -> *const Value<Self>;
+}
+
+// intrusive_adapter!(pub ByName = Pin<Box<Item>> { by_name });
+// makes a unit struct ByName that is an Adapter for Pin<Box<Item>> through Item::by_name, which
+// has to be a Links
+#[macro_export]
+macro_rules! intrusive_adapter {
+    ($vis:vis $name:ident = $ptr:ty { $field:ident }) => {
+        $vis struct $name;
+
+        unsafe impl $crate::intrusive::Adapter for $name {
+            type Pointer = $ptr;
+
+            unsafe fn get_links(
+                value: *const <$ptr as $crate::intrusive::Pointer>::Target,
+            ) -> *const $crate::intrusive::Links {
+                unsafe { &raw const (*value).$field }
+            }
+
+            unsafe fn get_value(
+                links: *const $crate::intrusive::Links,
+            ) -> *const <$ptr as $crate::intrusive::Pointer>::Target {
+                let offset =
+                    ::std::mem::offset_of!(<$ptr as $crate::intrusive::Pointer>::Target, $field);
+                unsafe { links.byte_sub(offset).cast() }
+            }
+        }
+    };
+}
+
+pub struct IntrusiveList<A: Adapter> {
+    front: Link,
+    back: Link,
+    len: usize,
+    // we own one A::Pointer for every object in the list
+    _boo: PhantomData<A::Pointer>,
+}
+
+type Link = Option<NonNull<Links>>;
+
+impl<A: Adapter> IntrusiveList<A> {
+    pub fn new() -> Self {
+        IntrusiveList {
+            front: None,
+            back: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // the object behind some links. it lives at least as long as we keep it in the list
+    unsafe fn value<'a>(links: NonNull<Links>) -> &'a Value<A> {
+        unsafe { &*A::get_value(links.as_ptr()) }
+    }
+
+    // puts ptr's object in between prev and next, which are neighbours, None meaning that end
+    // an object can only be in one list per Links field, so it has to come in unlinked
+    unsafe fn link_between(&mut self, prev: Link, next: Link, ptr: A::Pointer) -> NonNull<Links> {
+        let value = ptr.into_raw();
+        unsafe {
+            let links = NonNull::new_unchecked(A::get_links(value) as *mut Links);
+            if links.as_ref().is_linked() {
+                drop(A::Pointer::from_raw(value));
+                panic!("object is already linked through this field");
+            }
+            links.as_ref().prev.set(prev);
+            links.as_ref().next.set(next);
+            links.as_ref().linked.set(true);
+            match prev {
+                Some(prev) => prev.as_ref().next.set(Some(links)),
+                None => self.front = Some(links),
+            }
+            match next {
+                Some(next) => next.as_ref().prev.set(Some(links)),
+                None => self.back = Some(links),
+            }
+            self.len += 1;
+            links
+        }
+    }
+
+    // takes the object out in O(1) and hands back the pointer we were holding for it
+    unsafe fn unlink(&mut self, links: NonNull<Links>) -> A::Pointer {
+        unsafe {
+            let prev = links.as_ref().prev.take();
+            let next = links.as_ref().next.take();
+            links.as_ref().linked.set(false);
+            match next {
+                Some(next) => next.as_ref().prev.set(prev),
+                None => self.back = prev,
+            }
+            match prev {
+                Some(prev) => prev.as_ref().next.set(next),
+                None => self.front = next,
+            }
+            self.len -= 1;
+            A::Pointer::from_raw(A::get_value(links.as_ptr()))
+        }
+    }
+
+    pub fn push_front(&mut self, ptr: A::Pointer) {
+        unsafe {
+            self.link_between(None, self.front, ptr);
+        }
+    }
+
+    pub fn push_back(&mut self, ptr: A::Pointer) {
+        unsafe {
+            self.link_between(self.back, None, ptr);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<A::Pointer> {
+        self.front.map(|front| unsafe { self.unlink(front) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<A::Pointer> {
+        self.back.map(|back| unsafe { self.unlink(back) })
+    }
+
+    // objects are pinned, so there is no front_mut. anything that should change while an
+    // object is linked needs its own Cell
+    pub fn front(&self) -> Option<&Value<A>> {
+        self.front.map(|front| unsafe { Self::value(front) })
+    }
+
+    pub fn back(&self) -> Option<&Value<A>> {
+        self.back.map(|back| unsafe { Self::value(back) })
+    }
+
+    // just the links to rewire at the seam, nothing gets allocated or moved
+    pub fn append(&mut self, other: &mut Self) {
+        let (Some(back), Some(other_front)) = (self.back, other.front) else {
+            if self.is_empty() {
+                std::mem::swap(self, other);
+            }
+            return;
+        };
+        unsafe {
+            back.as_ref().next.set(Some(other_front));
+            other_front.as_ref().prev.set(Some(back));
+        }
+        self.back = other.back.take();
+        self.len += std::mem::take(&mut other.len);
+        other.front = None;
+    }
+}
+
+impl<A: Adapter> Default for IntrusiveList<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Adapter> Extend<A::Pointer> for IntrusiveList<A> {
+    fn extend<I: IntoIterator<Item = A::Pointer>>(&mut self, iter: I) {
+        for ptr in iter {
+            self.push_back(ptr);
+        }
+    }
+}
+
+impl<A: Adapter> FromIterator<A::Pointer> for IntrusiveList<A> {
+    fn from_iter<I: IntoIterator<Item = A::Pointer>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<A: Adapter> Debug for IntrusiveList<A>
+where
+    Value<A>: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+// giving every pointer back lets Box free the object, Rc drop its count, and so on
+impl<A: Adapter> Drop for IntrusiveList<A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// we own A::Pointers and hand out &Value, same rules as a Vec<A::Pointer>
+unsafe impl<A: Adapter> Send for IntrusiveList<A> where A::Pointer: Send {}
+unsafe impl<A: Adapter> Sync for IntrusiveList<A> where A::Pointer: Sync {}
+
+pub struct Iter<'a, A: Adapter> {
+    front: Link,
+    back: Link,
+    len: usize,
+    _boo: PhantomData<&'a IntrusiveList<A>>,
+}
+
+impl<A: Adapter> IntrusiveList<A> {
+    pub fn iter(&self) -> Iter<'_, A> {
+        Iter {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, A: Adapter> IntoIterator for &'a IntrusiveList<A> {
+    type IntoIter = Iter<'a, A>;
+    type Item = &'a Value<A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, A: Adapter> Iterator for Iter<'a, A> {
+    type Item = &'a Value<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|links| unsafe {
+                self.len -= 1;
+                self.front = links.as_ref().next.get();
+                IntrusiveList::<A>::value(links)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, A: Adapter> DoubleEndedIterator for Iter<'a, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|links| unsafe {
+                self.len -= 1;
+                self.back = links.as_ref().prev.get();
+                IntrusiveList::<A>::value(links)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, A: Adapter> ExactSizeIterator for Iter<'a, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<A: Adapter> {
+    list: IntrusiveList<A>,
+}
+
+impl<A: Adapter> IntoIterator for IntrusiveList<A> {
+    type IntoIter = IntoIter<A>;
+    type Item = A::Pointer;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<A: Adapter> Iterator for IntoIter<A> {
+    type Item = A::Pointer;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<A: Adapter> DoubleEndedIterator for IntoIter<A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<A: Adapter> ExactSizeIterator for IntoIter<A> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// cursors
+// same ghost as sixth: None is the spot between back and front. there is no index, because a
+// cursor made straight from an object has no cheap way of knowing where it is
+pub struct Cursor<'a, A: Adapter> {
+    cur: Link,
+    list: &'a IntrusiveList<A>,
+}
+
+pub struct CursorMut<'a, A: Adapter> {
+    cur: Link,
+    list: &'a mut IntrusiveList<A>,
+}
+
+impl<'a, A: Adapter> Clone for Cursor<'a, A> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+        }
+    }
+}
+
+impl<A: Adapter> IntrusiveList<A> {
+    pub fn cursor(&self) -> Cursor<'_, A> {
+        Cursor {
+            cur: None,
+            list: self,
+        }
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, A> {
+        Cursor {
+            cur: self.front,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, A> {
+        Cursor {
+            cur: self.back,
+            list: self,
+        }
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, A> {
+        CursorMut {
+            cur: None,
+            list: self,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, A> {
+        CursorMut {
+            cur: self.front,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, A> {
+        CursorMut {
+            cur: self.back,
+            list: self,
+        }
+    }
+
+    // this is the point of links inside the object: having the object is as good as having its
+    // spot in the list, so from here it's O(1) to unlink it or insert next to it
+    /// # Safety
+    /// value has to be linked into this list through A's field right now, not another list and
+    /// not through another field
+    pub unsafe fn cursor_from_ptr(&self, value: *const Value<A>) -> Cursor<'_, A> {
+        Cursor {
+            cur: Some(unsafe { NonNull::new_unchecked(A::get_links(value) as *mut Links) }),
+            list: self,
+        }
+    }
+
+    /// # Safety
+    /// same as cursor_from_ptr
+    pub unsafe fn cursor_mut_from_ptr(&mut self, value: *const Value<A>) -> CursorMut<'_, A> {
+        CursorMut {
+            cur: Some(unsafe { NonNull::new_unchecked(A::get_links(value) as *mut Links) }),
+            list: self,
+        }
+    }
+}
+
+// next and prev from cur, where None is the ghost
+fn step_next(cur: Link, front: Link) -> Link {
+    match cur {
+        Some(cur) => unsafe { cur.as_ref().next.get() },
+        None => front,
+    }
+}
+
+fn step_prev(cur: Link, back: Link) -> Link {
+    match cur {
+        Some(cur) => unsafe { cur.as_ref().prev.get() },
+        None => back,
+    }
+}
+
+impl<'a, A: Adapter> Cursor<'a, A> {
+    pub fn move_next(&mut self) {
+        self.cur = step_next(self.cur, self.list.front);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cur = step_prev(self.cur, self.list.back);
+    }
+
+    pub fn current(&self) -> Option<&'a Value<A>> {
+        self.cur
+            .map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+
+    pub fn peek_next(&self) -> Option<&'a Value<A>> {
+        step_next(self.cur, self.list.front)
+            .map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a Value<A>> {
+        step_prev(self.cur, self.list.back).map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+}
+
+impl<'a, A: Adapter> CursorMut<'a, A> {
+    pub fn as_cursor(&self) -> Cursor<'_, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        self.cur = step_next(self.cur, self.list.front);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cur = step_prev(self.cur, self.list.back);
+    }
+
+    pub fn current(&self) -> Option<&Value<A>> {
+        self.cur
+            .map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+
+    pub fn peek_next(&self) -> Option<&Value<A>> {
+        step_next(self.cur, self.list.front)
+            .map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+
+    pub fn peek_prev(&self) -> Option<&Value<A>> {
+        step_prev(self.cur, self.list.back).map(|links| unsafe { IntrusiveList::<A>::value(links) })
+    }
+
+    // unlinks the current object in O(1) and moves on to the next one (or the ghost), just like
+    // sixth. on the ghost there is nothing to remove
+    pub fn remove_current(&mut self) -> Option<A::Pointer> {
+        let cur = self.cur?;
+        unsafe {
+            self.cur = cur.as_ref().next.get();
+            Some(self.list.unlink(cur))
+        }
+    }
+
+    // on the ghost, insert_before puts the object at the back and insert_after at the front
+    pub fn insert_before(&mut self, ptr: A::Pointer) {
+        let next = self.cur;
+        let prev = step_prev(self.cur, self.list.back);
+        unsafe {
+            self.list.link_between(prev, next, ptr);
+        }
+    }
+
+    pub fn insert_after(&mut self, ptr: A::Pointer) {
+        let prev = self.cur;
+        let next = step_next(self.cur, self.list.front);
+        unsafe {
+            self.list.link_between(prev, next, ptr);
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+
+    struct Item {
+        links: Links,
+    }
+    intrusive_adapter!(ItemBox = Pin<Box<Item>> { links });
+
+    // a Box'd object goes where the list goes. nothing here is Sync: the Cells in Links mean an
+    // object can't be shared across threads while it's linked
+    is_send::<Links>();
+    is_send::<IntrusiveList<ItemBox>>();
+    is_send::<IntoIter<ItemBox>>();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IntrusiveList, Links};
+    use std::{cell::Cell, pin::Pin, rc::Rc, sync::Arc};
+
+    #[derive(Debug, Default)]
+    struct Task {
+        id: u32,
+        // a task is always on the all list, and also on a run queue while it is runnable
+        all: Links,
+        run: Links,
+        polls: Cell<u32>,
+    }
+
+    impl Task {
+        fn new(id: u32) -> Self {
+            Task {
+                id,
+                ..Task::default()
+            }
+        }
+    }
+
+    crate::intrusive_adapter!(BoxAdapter = Pin<Box<Task>> { all });
+    crate::intrusive_adapter!(AllTasks = Pin<Rc<Task>> { all });
+    crate::intrusive_adapter!(RunQueue = Pin<Rc<Task>> { run });
+    crate::intrusive_adapter!(ArcAdapter = Pin<Arc<Task>> { all });
+    crate::intrusive_adapter!(StaticAdapter = Pin<&'static Task> { all });
+
+    fn ids<A: super::Adapter<Pointer: super::Pointer<Target = Task>>>(
+        list: &IntrusiveList<A>,
+    ) -> Vec<u32> {
+        list.iter().map(|task| task.id).collect()
+    }
+
+    // only for counting, we don't move anything
+    fn strong_count(task: Pin<Rc<Task>>) -> usize {
+        Rc::strong_count(&unsafe { Pin::into_inner_unchecked(task) })
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut list = IntrusiveList::<BoxAdapter>::new();
+        assert_eq!(list.pop_front().map(|t| t.id), None);
+        list.push_back(Box::pin(Task::new(2)));
+        list.push_front(Box::pin(Task::new(1)));
+        list.push_back(Box::pin(Task::new(3)));
+        assert_eq!(ids(&list), &[1, 2, 3]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front().map(|t| t.id), Some(1));
+        assert_eq!(list.back().map(|t| t.id), Some(3));
+        assert_eq!(
+            list.iter().rev().map(|t| t.id).collect::<Vec<_>>(),
+            &[3, 2, 1]
+        );
+
+        let task = list.pop_front().unwrap();
+        assert!(!task.all.is_linked());
+        assert_eq!(task.id, 1);
+        assert_eq!(list.pop_back().map(|t| t.id), Some(3));
+        assert!(list.front().unwrap().all.is_linked());
+
+        let mut other: IntrusiveList<BoxAdapter> = (4..7).map(|i| Box::pin(Task::new(i))).collect();
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(ids(&list), &[2, 4, 5, 6]);
+        other.append(&mut list);
+        assert_eq!(ids(&other), &[2, 4, 5, 6]);
+        assert_eq!(
+            other.into_iter().rev().map(|t| t.id).collect::<Vec<_>>(),
+            &[6, 5, 4, 2]
+        );
+    }
+
+    #[test]
+    fn test_two_lists() {
+        let mut all = IntrusiveList::<AllTasks>::new();
+        let mut run = IntrusiveList::<RunQueue>::new();
+        for id in 0..5 {
+            let task = Rc::pin(Task::new(id));
+            if id % 2 == 0 {
+                run.push_back(Pin::clone(&task));
+            }
+            all.push_back(task);
+        }
+        assert_eq!(ids(&all), &[0, 1, 2, 3, 4]);
+        assert_eq!(ids(&run), &[0, 2, 4]);
+
+        // the same objects, seen through either list
+        for task in &run {
+            task.polls.set(task.polls.get() + 1);
+        }
+        assert_eq!(
+            all.iter().map(|t| t.polls.get()).collect::<Vec<_>>(),
+            &[1, 0, 1, 0, 1]
+        );
+
+        // taking a task off the run queue leaves it on the all list
+        let task = run.pop_front().unwrap();
+        assert!(!task.run.is_linked());
+        assert!(task.all.is_linked());
+        assert_eq!(strong_count(task), 2);
+        assert_eq!(ids(&all), &[0, 1, 2, 3, 4]);
+
+        drop(all);
+        assert_eq!(ids(&run), &[2, 4]);
+        let task = run.pop_back().unwrap();
+        assert_eq!(strong_count(task), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "already linked")]
+    fn test_double_link() {
+        let mut list = IntrusiveList::<AllTasks>::new();
+        let task = Rc::pin(Task::new(0));
+        list.push_back(Pin::clone(&task));
+        list.push_back(task);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut list: IntrusiveList<ArcAdapter> = (0..5).map(|i| Arc::pin(Task::new(i))).collect();
+
+        let mut cursor = list.cursor();
+        assert_eq!(cursor.current().map(|t| t.id), None);
+        assert_eq!(cursor.peek_next().map(|t| t.id), Some(0));
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(4));
+        cursor.move_prev();
+        assert_eq!(cursor.current().map(|t| t.id), Some(4));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current().map(|t| t.id), Some(0));
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current().map(|t| t.id), Some(1));
+        assert_eq!(cursor.current().map(|t| t.id), Some(2));
+        cursor.insert_before(Arc::pin(Task::new(10)));
+        cursor.insert_after(Arc::pin(Task::new(11)));
+        assert_eq!(cursor.as_cursor().peek_prev().map(|t| t.id), Some(10));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        // on the ghost
+        assert_eq!(cursor.remove_current().map(|t| t.id), None);
+        cursor.insert_before(Arc::pin(Task::new(20)));
+        cursor.insert_after(Arc::pin(Task::new(21)));
+        assert_eq!(ids(&list), &[21, 0, 10, 2, 11, 3, 4, 20]);
+
+        // removing the back lands on the ghost, from where move_prev wraps to the new back
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_current().map(|t| t.id), Some(20));
+        assert_eq!(cursor.current().map(|t| t.id), None);
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current().map(|t| t.id), Some(4));
+        assert_eq!(ids(&list), &[21, 0, 10, 2, 11, 3]);
+    }
+
+    #[test]
+    fn test_unlink_by_ptr() {
+        // 'static for as long as the test runs, we give the memory back at the end
+        let raw = Box::into_raw((0..5).map(Task::new).collect::<Box<[Task]>>());
+        let tasks: &'static [Task] = unsafe { &*raw };
+        let mut list = IntrusiveList::<StaticAdapter>::new();
+        for task in tasks {
+            list.push_back(Pin::static_ref(task));
+        }
+
+        // all we have is the object, no walking
+        let third = &tasks[2];
+        let mut cursor = unsafe { list.cursor_mut_from_ptr(third) };
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(1));
+        assert!(std::ptr::eq(&*cursor.remove_current().unwrap(), third));
+        assert!(!third.all.is_linked());
+        let cursor = unsafe { list.cursor_from_ptr(&tasks[4]) };
+        assert_eq!(cursor.peek_next().map(|t| t.id), None);
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(3));
+        assert_eq!(ids(&list), &[0, 1, 3, 4]);
+
+        // a &'static object can go back in once it's out
+        list.push_front(Pin::static_ref(third));
+        assert_eq!(ids(&list), &[2, 0, 1, 3, 4]);
+        list.clear();
+        assert!(tasks.iter().all(|task| !task.all.is_linked()));
+        drop(list);
+        drop(unsafe { Box::from_raw(raw) });
+    }
+
+    #[test]
+    fn test_drop() {
+        let task = Rc::pin(Task::new(0));
+        let mut list = IntrusiveList::<AllTasks>::new();
+        list.push_back(Pin::clone(&task));
+        list.extend((1..4).map(|i| Rc::pin(Task::new(i))));
+        assert_eq!(
+            format!("{:?}", list.iter().map(|t| t.id).collect::<Vec<_>>()),
+            "[0, 1, 2, 3]"
+        );
+        drop(list);
+        assert!(!task.all.is_linked());
+        assert_eq!(strong_count(task), 1);
+    }
+}
@@ -1,6 +1,19 @@
+pub mod arena;
+pub mod bounded;
+pub mod circular;
 pub mod fifth;
 pub mod first;
+pub mod fixed;
 pub mod fourth;
+pub mod ghost;
+pub mod indexed;
+pub mod intrusive;
+mod rng;
 pub mod second;
+pub mod selforg;
 pub mod sixth;
+pub mod skiplist;
+pub mod sorted;
 pub mod third;
+pub mod unrolled;
+pub mod xor;
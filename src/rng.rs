@@ -0,0 +1,39 @@
+// the bit of randomness indexed and skiplist need. nothing here has to be unpredictable, it only
+// has to not line up with the order things are inserted in
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// every list gets its own seed so two of them don't hand out the same numbers
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0);
+
+// splitmix64 to spread the seed around
+pub(crate) fn new_seed() -> u64 {
+    let mut z = NEXT_SEED
+        .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    // xorshift gets stuck on 0
+    (z ^ (z >> 31)) | 1
+}
+
+// xorshift64* for the actual numbers, state comes from new_seed
+pub(crate) fn next(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+// a tiny xorshift so the random tests are the same every run
+#[cfg(test)]
+pub(crate) struct TestRng(pub u64);
+
+#[cfg(test)]
+impl TestRng {
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
@@ -0,0 +1,901 @@
+// a self-organizing list
+// a plain doubly linked list that you search front to back, except that every time a search finds
+// something the list shuffles that node towards the front, so the things that get looked up a lot
+// end up being cheap to find. how it shuffles is up to the Policy:
+//
+// * MoveToFront: the node goes straight to the front
+// * Transpose: the node swaps places with the one in front of it
+// * FrequencyCount: every node counts its hits, and a node moves up past everything with fewer
+//   hits than it has, so the list stays sorted by hit count
+//
+// moving a node is always just relinking it, nothing gets allocated or moved in memory. the list
+// also keeps Stats on every lookup (how many, how many found something, how many nodes got looked
+// at) so different policies can be compared on the same traffic.
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+pub struct SelfOrgList<T, P = MoveToFront> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    policy: P,
+    stats: Stats,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    prev: Link<T>,
+    next: Link<T>,
+    // how many lookups have found this node
+    hits: u64,
+    elem: T,
+}
+
+// what the list has seen since it was made (or since reset_stats)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    // calls to find and find_mut
+    pub lookups: u64,
+    // lookups that found something
+    pub hits: u64,
+    // nodes the predicate was run on, over all lookups. this is the real cost of the lookups
+    pub probes: u64,
+    // hits that made the policy move the node
+    pub moves: u64,
+}
+
+impl Stats {
+    pub fn misses(&self) -> u64 {
+        self.lookups - self.hits
+    }
+
+    // how many nodes a lookup looks at on average, the number to compare policies by
+    pub fn probes_per_lookup(&self) -> f64 {
+        if self.lookups == 0 {
+            0.0
+        } else {
+            self.probes as f64 / self.lookups as f64
+        }
+    }
+}
+
+// decides what happens to a node after a lookup finds it. the node's hit count has already been
+// bumped by the time reorder is called
+pub trait Policy {
+    fn reorder<T>(&mut self, hit: &mut Hit<'_, T>);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MoveToFront;
+
+impl Policy for MoveToFront {
+    fn reorder<T>(&mut self, hit: &mut Hit<'_, T>) {
+        hit.move_to_front();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Transpose;
+
+impl Policy for Transpose {
+    fn reorder<T>(&mut self, hit: &mut Hit<'_, T>) {
+        hit.move_forward(1);
+    }
+}
+
+// only ever passes nodes the lookup just walked over, so it doesn't change what a lookup costs.
+// the list stays sorted by hits as long as new elements go on the back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FrequencyCount;
+
+impl Policy for FrequencyCount {
+    fn reorder<T>(&mut self, hit: &mut Hit<'_, T>) {
+        let hits = hit.hits();
+        hit.move_forward_while(|prev_hits| prev_hits < hits);
+    }
+}
+
+// the node a lookup just found, for the policy to move around. it can only ever move towards the
+// front, the list behind it is left alone
+pub struct Hit<'a, T> {
+    front: &'a mut Link<T>,
+    back: &'a mut Link<T>,
+    node: NonNull<Node<T>>,
+    index: usize,
+    moved: bool,
+}
+
+impl<'a, T> Hit<'a, T> {
+    pub fn elem(&self) -> &T {
+        unsafe { &(*self.node.as_ptr()).elem }
+    }
+
+    // including the lookup that found it
+    pub fn hits(&self) -> u64 {
+        unsafe { (*self.node.as_ptr()).hits }
+    }
+
+    // where the node is right now, 0 is the front
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn move_to_front(&mut self) {
+        if let Some(front) = *self.front {
+            unsafe { self.relink_before(front, self.index) };
+        }
+    }
+
+    // moves up n places, or to the front if there aren't that many
+    pub fn move_forward(&mut self, n: usize) {
+        let mut left = n;
+        self.move_forward_while(|_| {
+            if left == 0 {
+                return false;
+            }
+            left -= 1;
+            true
+        });
+    }
+
+    // moves up past every node before it for which pred(that node's hits) is true, stopping at the
+    // first one where it isn't
+    pub fn move_forward_while(&mut self, mut pred: impl FnMut(u64) -> bool) {
+        unsafe {
+            let mut target = None;
+            let mut passed = 0;
+            let mut prev = (*self.node.as_ptr()).prev;
+            while let Some(node) = prev {
+                if !pred((*node.as_ptr()).hits) {
+                    break;
+                }
+                target = Some(node);
+                passed += 1;
+                prev = (*node.as_ptr()).prev;
+            }
+            if let Some(target) = target {
+                self.relink_before(target, passed);
+            }
+        }
+    }
+
+    // takes our node out and puts it back in right before target, which is `passed` places in
+    // front of it
+    unsafe fn relink_before(&mut self, target: NonNull<Node<T>>, passed: usize) {
+        if passed == 0 {
+            return;
+        }
+        unsafe {
+            let node = self.node.as_ptr();
+            let (prev, next) = ((*node).prev, (*node).next);
+            // we're not the front, there's at least target before us
+            (*prev.unwrap().as_ptr()).next = next;
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => *self.back = prev,
+            }
+
+            let before = (*target.as_ptr()).prev;
+            (*node).prev = before;
+            (*node).next = Some(target);
+            (*target.as_ptr()).prev = Some(self.node);
+            match before {
+                Some(before) => (*before.as_ptr()).next = Some(self.node),
+                None => *self.front = Some(self.node),
+            }
+        }
+        self.index -= passed;
+        self.moved = true;
+    }
+}
+
+impl<T> SelfOrgList<T> {
+    pub fn new() -> Self {
+        Self::with_policy(MoveToFront)
+    }
+}
+
+impl<T, P: Policy> SelfOrgList<T, P> {
+    pub fn with_policy(policy: P) -> Self {
+        Self {
+            front: None,
+            back: None,
+            len: 0,
+            policy,
+            stats: Stats::default(),
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // only the list's Stats, the nodes keep their hit counts (FrequencyCount needs those)
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // new elements start with no hits
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: None,
+                next: self.front,
+                hits: 0,
+                elem,
+            })));
+            match self.front {
+                Some(old) => (*old.as_ptr()).prev = Some(new),
+                None => self.back = Some(new),
+            }
+            self.front = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: self.back,
+                next: None,
+                hits: 0,
+                elem,
+            })));
+            match self.back {
+                Some(old) => (*old.as_ptr()).next = Some(new),
+                None => self.front = Some(new),
+            }
+            self.back = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.front.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.back.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.front.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.back.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    // takes node out of the list and frees it
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        unsafe {
+            let boxed_node = Box::from_raw(node.as_ptr());
+            match boxed_node.prev {
+                Some(prev) => (*prev.as_ptr()).next = boxed_node.next,
+                None => self.front = boxed_node.next,
+            }
+            match boxed_node.next {
+                Some(next) => (*next.as_ptr()).prev = boxed_node.prev,
+                None => self.back = boxed_node.prev,
+            }
+            self.len -= 1;
+            boxed_node.elem
+        }
+    }
+
+    // the first node pred likes, after the policy has had its go at moving it
+    fn lookup(&mut self, mut pred: impl FnMut(&T) -> bool) -> Option<NonNull<Node<T>>> {
+        self.stats.lookups += 1;
+        let mut cur = self.front;
+        let mut index = 0;
+        while let Some(node) = cur {
+            self.stats.probes += 1;
+            unsafe {
+                if pred(&(*node.as_ptr()).elem) {
+                    (*node.as_ptr()).hits += 1;
+                    self.stats.hits += 1;
+                    let mut hit = Hit {
+                        front: &mut self.front,
+                        back: &mut self.back,
+                        node,
+                        index,
+                        moved: false,
+                    };
+                    self.policy.reorder(&mut hit);
+                    if hit.moved {
+                        self.stats.moves += 1;
+                    }
+                    return Some(node);
+                }
+                cur = (*node.as_ptr()).next;
+            }
+            index += 1;
+        }
+        None
+    }
+
+    pub fn find(&mut self, pred: impl FnMut(&T) -> bool) -> Option<&T> {
+        unsafe { self.lookup(pred).map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn find_mut(&mut self, pred: impl FnMut(&T) -> bool) -> Option<&mut T> {
+        unsafe { self.lookup(pred).map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    // takes out the first element pred likes. this isn't a lookup, it doesn't touch the stats
+    pub fn remove(&mut self, mut pred: impl FnMut(&T) -> bool) -> Option<T> {
+        let mut cur = self.front;
+        while let Some(node) = cur {
+            unsafe {
+                if pred(&(*node.as_ptr()).elem) {
+                    return Some(self.unlink(node));
+                }
+                cur = (*node.as_ptr()).next;
+            }
+        }
+        None
+    }
+}
+
+impl<T, P: Policy + Default> Default for SelfOrgList<T, P> {
+    fn default() -> Self {
+        Self::with_policy(P::default())
+    }
+}
+
+// hit counts and stats come along, so the clone organizes itself the same way from here on
+impl<T: Clone, P: Policy + Clone> Clone for SelfOrgList<T, P> {
+    fn clone(&self) -> Self {
+        let mut new_list = Self::with_policy(self.policy.clone());
+        for (elem, hits) in self.iter_hits() {
+            new_list.push_back(elem.clone());
+            unsafe { (*new_list.back.unwrap().as_ptr()).hits = hits };
+        }
+        new_list.stats = self.stats;
+        new_list
+    }
+}
+
+impl<T, P: Policy> Extend<T> for SelfOrgList<T, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T, P: Policy + Default> FromIterator<T> for SelfOrgList<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::default();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug, P> Debug for SelfOrgList<T, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// comparisons only look at the elements in their current order
+impl<T: PartialEq, P> PartialEq for SelfOrgList<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, P> Eq for SelfOrgList<T, P> {}
+
+impl<T: PartialOrd, P> PartialOrd for SelfOrgList<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, P> Ord for SelfOrgList<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, P> Hash for SelfOrgList<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T, P> Drop for SelfOrgList<T, P> {
+    fn drop(&mut self) {
+        let mut cur = self.front;
+        while let Some(node) = cur {
+            unsafe {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                cur = boxed_node.next;
+            }
+        }
+    }
+}
+
+// plain iteration doesn't count as a lookup and doesn't move anything
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn next_node(&mut self) -> Option<&'a Node<T>> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).next;
+                &*node.as_ptr()
+            })
+        } else {
+            None
+        }
+    }
+
+    fn next_back_node(&mut self) -> Option<&'a Node<T>> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).prev;
+                &*node.as_ptr()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, P> SelfOrgList<T, P> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+
+    // every element with how many lookups have found it
+    pub fn iter_hits(&self) -> IterHits<'_, T> {
+        IterHits { iter: self.iter() }
+    }
+}
+
+impl<'a, T, P> IntoIterator for &'a SelfOrgList<T, P> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_node().map(|node| &node.elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next_back_node().map(|node| &node.elem)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterHits<'a, T> {
+    iter: Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for IterHits<'a, T> {
+    type Item = (&'a T, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next_node().map(|node| (&node.elem, node.hits))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterHits<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back_node()
+            .map(|node| (&node.elem, node.hits))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterHits<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<'a, T, P> IntoIterator for &'a mut SelfOrgList<T, P> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).next;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).prev;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T, P> {
+    list: SelfOrgList<T, P>,
+}
+
+impl<T, P: Policy> IntoIterator for SelfOrgList<T, P> {
+    type IntoIter = IntoIter<T, P>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T, P: Policy> Iterator for IntoIter<T, P> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, P: Policy> DoubleEndedIterator for IntoIter<T, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, P: Policy> ExactSizeIterator for IntoIter<T, P> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send, P: Send> Send for SelfOrgList<T, P> {}
+unsafe impl<T: Sync, P: Sync> Sync for SelfOrgList<T, P> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<SelfOrgList<i32>>();
+    is_sync::<SelfOrgList<i32>>();
+
+    is_send::<IntoIter<i32, Transpose>>();
+    is_sync::<IntoIter<i32, Transpose>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterHits<i32>>();
+    is_sync::<IterHits<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    fn self_org_list_covariant<'a, T>(x: SelfOrgList<&'static T>) -> SelfOrgList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+    fn into_iter_covariant<'a, T>(
+        x: IntoIter<&'static T, MoveToFront>,
+    ) -> IntoIter<&'a T, MoveToFront> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FrequencyCount, Hit, MoveToFront, Policy, SelfOrgList, Stats, Transpose};
+
+    fn contents<T: Clone, P>(list: &SelfOrgList<T, P>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    // every link agrees with its neighbour's and the ends are where the list thinks they are
+    fn check_links<T, P>(list: &SelfOrgList<T, P>) {
+        let mut prev = None;
+        let mut cur = list.front;
+        let mut len = 0;
+        while let Some(node) = cur {
+            unsafe {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                prev = cur;
+                cur = (*node.as_ptr()).next;
+            }
+            len += 1;
+        }
+        assert_eq!(list.back, prev);
+        assert_eq!(list.len, len);
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = SelfOrgList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.find(|_| true), None);
+        m.push_back(2);
+        m.push_front(1);
+        m.push_back(3);
+        assert_eq!(contents(&m), &[1, 2, 3]);
+        assert_eq!(m.front(), Some(&1));
+        assert_eq!(m.back(), Some(&3));
+        for x in m.iter_mut() {
+            *x *= 10;
+        }
+        assert_eq!(m.remove(|&x| x == 20), Some(20));
+        assert_eq!(m.remove(|&x| x == 20), None);
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(10));
+        assert!(m.is_empty());
+        check_links(&m);
+
+        m.extend(0..4);
+        assert_eq!(format!("{m:?}"), "[0, 1, 2, 3]");
+        assert_eq!(
+            m.clone().into_iter().rev().collect::<Vec<_>>(),
+            &[3, 2, 1, 0]
+        );
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_move_to_front() {
+        let mut m: SelfOrgList<i32> = (0..5).collect();
+        assert_eq!(m.find(|&x| x == 3), Some(&3));
+        assert_eq!(contents(&m), &[3, 0, 1, 2, 4]);
+        check_links(&m);
+        assert_eq!(m.find(|&x| x == 4), Some(&4));
+        assert_eq!(contents(&m), &[4, 3, 0, 1, 2]);
+        check_links(&m);
+        // already at the front, nothing moves
+        assert_eq!(m.find(|&x| x == 4), Some(&4));
+        assert_eq!(m.find(|&x| x == 7), None);
+        assert_eq!(contents(&m), &[4, 3, 0, 1, 2]);
+
+        assert_eq!(
+            m.stats(),
+            Stats {
+                lookups: 4,
+                hits: 3,
+                probes: 4 + 5 + 1 + 5,
+                moves: 2,
+            }
+        );
+        assert_eq!(m.stats().misses(), 1);
+        m.reset_stats();
+        assert_eq!(m.stats(), Stats::default());
+        assert_eq!(m.iter_hits().next(), Some((&4, 2)));
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut m = SelfOrgList::with_policy(Transpose);
+        m.extend(0..5);
+        *m.find_mut(|&x| x == 4).unwrap() = 40;
+        assert_eq!(contents(&m), &[0, 1, 2, 40, 3]);
+        check_links(&m);
+        m.find(|&x| x == 40);
+        m.find(|&x| x == 40);
+        assert_eq!(contents(&m), &[0, 40, 1, 2, 3]);
+        m.find(|&x| x == 40);
+        m.find(|&x| x == 40);
+        assert_eq!(contents(&m), &[40, 0, 1, 2, 3]);
+        check_links(&m);
+        assert_eq!(m.stats().moves, 4);
+    }
+
+    #[test]
+    fn test_frequency_count() {
+        let mut m = SelfOrgList::with_policy(FrequencyCount);
+        m.extend(['a', 'b', 'c', 'd']);
+        m.find(|&x| x == 'c');
+        assert_eq!(contents(&m), &['c', 'a', 'b', 'd']);
+        m.find(|&x| x == 'd');
+        m.find(|&x| x == 'd');
+        assert_eq!(contents(&m), &['d', 'c', 'a', 'b']);
+        // ties stay where they are, b only gets past the ones with no hits
+        m.find(|&x| x == 'b');
+        assert_eq!(contents(&m), &['d', 'c', 'b', 'a']);
+        m.find(|&x| x == 'b');
+        assert_eq!(contents(&m), &['d', 'b', 'c', 'a']);
+        check_links(&m);
+        assert_eq!(
+            m.iter_hits().collect::<Vec<_>>(),
+            &[(&'d', 2), (&'b', 2), (&'c', 1), (&'a', 0)]
+        );
+
+        // the clone keeps the counts and carries on the same way
+        let mut n = m.clone();
+        n.find(|&x| x == 'c');
+        n.find(|&x| x == 'c');
+        assert_eq!(contents(&n), &['c', 'd', 'b', 'a']);
+        assert_eq!(n.stats().lookups, m.stats().lookups + 2);
+        check_links(&n);
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        // moves halfway to the front
+        #[derive(Default)]
+        struct Halfway;
+        impl Policy for Halfway {
+            fn reorder<T>(&mut self, hit: &mut Hit<'_, T>) {
+                let index = hit.index();
+                let n = index.div_ceil(2);
+                hit.move_forward(n);
+                assert_eq!(hit.index(), index - n);
+            }
+        }
+
+        let mut m: SelfOrgList<i32, Halfway> = (0..8).collect();
+        m.find(|&x| x == 6);
+        assert_eq!(contents(&m), &[0, 1, 2, 6, 3, 4, 5, 7]);
+        m.find(|&x| x == 6);
+        assert_eq!(contents(&m), &[0, 6, 1, 2, 3, 4, 5, 7]);
+        m.find(|&x| x == 6);
+        assert_eq!(contents(&m), &[6, 0, 1, 2, 3, 4, 5, 7]);
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_compare_policies() {
+        // a skewed workload: a few keys get most of the lookups, and they start out at the back
+        let keys = 64;
+        let traffic: Vec<u32> = (0..2000u32)
+            .map(|i| match i % 10 {
+                0..=5 => keys - 1,
+                6..=7 => keys - 2,
+                8 => keys - 3,
+                _ => (i / 10) % keys,
+            })
+            .collect();
+
+        fn run<P: Policy + Default>(keys: u32, traffic: &[u32]) -> Stats {
+            let mut m: SelfOrgList<u32, P> = (0..keys).collect();
+            for &key in traffic {
+                assert_eq!(m.find(|&x| x == key), Some(&key));
+            }
+            check_links(&m);
+            m.stats()
+        }
+
+        // never moves anything, the baseline
+        #[derive(Default)]
+        struct Stay;
+        impl Policy for Stay {
+            fn reorder<T>(&mut self, _: &mut Hit<'_, T>) {}
+        }
+
+        let stay = run::<Stay>(keys, &traffic);
+        assert_eq!(stay.moves, 0);
+        for stats in [
+            run::<MoveToFront>(keys, &traffic),
+            run::<Transpose>(keys, &traffic),
+            run::<FrequencyCount>(keys, &traffic),
+        ] {
+            assert_eq!(stats.lookups, stay.lookups);
+            assert_eq!(stats.hits, stay.hits);
+            assert!(stats.probes_per_lookup() * 3.0 < stay.probes_per_lookup());
+        }
+    }
+}
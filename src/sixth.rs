@@ -1,10 +1,13 @@
 // LAY OF THE LAND
 use std::{
     cmp::Ordering,
+    collections::HashMap,
     fmt::Debug,
     hash::{Hash, Hasher},
     marker::PhantomData,
+    ops::{Bound, RangeBounds},
     ptr::NonNull,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
 };
 
 pub struct LinkedList<T> {
@@ -14,6 +17,8 @@ pub struct LinkedList<T> {
     // PhantomDate<T> is a zero sized Type. adding this here tells the compiler that LinkedList acts as tho
     // it stores T
     _boo: PhantomData<T>,
+    // only allocated once someone asks for a NodeHandle, see the handles section
+    handles: Option<Box<HandleTable<T>>>,
 }
 
 type Link<T> = Option<NonNull<Node<T>>>;
@@ -31,6 +36,7 @@ impl<T> LinkedList<T> {
             back: None,
             len: 0,
             _boo: PhantomData,
+            handles: None,
         }
     }
 
@@ -56,6 +62,7 @@ impl<T> LinkedList<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         unsafe {
             self.front.map(|old| {
+                self.forget_node(old);
                 let boxed_node = Box::from_raw(old.as_ptr());
                 let result = boxed_node.elem;
                 self.front = boxed_node.next;
@@ -92,6 +99,7 @@ impl<T> LinkedList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         unsafe {
             self.back.map(|old| {
+                self.forget_node(old);
                 let boxed_node = Box::from_raw(old.as_ptr());
                 let result = boxed_node.elem;
                 self.back = boxed_node.prev;
@@ -133,6 +141,202 @@ impl<T> LinkedList<T> {
     pub fn clear(&mut self) {
         while let Some(_) = self.pop_front() {}
     }
+
+    // moves all of other's nodes onto our back in O(1), other is left empty
+    pub fn append(&mut self, other: &mut Self) {
+        let mut cursor = self.cursor_mut();
+        cursor.splice_before(std::mem::take(other));
+    }
+
+    // same but other's nodes go in front of ours
+    pub fn prepend(&mut self, other: &mut Self) {
+        let mut cursor = self.cursor_mut();
+        cursor.splice_after(std::mem::take(other));
+    }
+
+    // everything from index at onwards is returned and we keep [0, at)
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len;
+        assert!(
+            at <= len,
+            "split index (is {at}) should be <= len (is {len})"
+        );
+        // split after the node before at, so what leaves is what we return and the handle table
+        // stays with the nodes we keep. at == 0 puts the cursor on the ghost, which gives away
+        // everything
+        let mut cursor = self.cursor_at_mut(at);
+        cursor.move_prev();
+        cursor.split_after()
+    }
+
+    // the new element ends up at index at, at == len pushes to the back
+    pub fn insert(&mut self, at: usize, elem: T) {
+        let len = self.len;
+        assert!(
+            at <= len,
+            "insertion index (is {at}) should be <= len (is {len})"
+        );
+        // splice rather than insert_before, a plain insert shouldn't pay for a handle
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        self.cursor_at_mut(at).splice_before(input);
+    }
+
+    pub fn remove(&mut self, at: usize) -> T {
+        let len = self.len;
+        assert!(
+            at < len,
+            "removal index (is {at}) should be < len (is {len})"
+        );
+        self.cursor_at_mut(at).remove_current().unwrap()
+    }
+
+    pub fn get(&self, at: usize) -> Option<&T> {
+        if at < self.len {
+            unsafe { Some(&(*self.walk_to(None, at).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, at: usize) -> Option<&mut T> {
+        if at < self.len {
+            unsafe { Some(&mut (*self.walk_to(None, at).as_ptr()).elem) }
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|e| e == x)
+    }
+
+    // swaps the elements, not the nodes. j is found by walking from i if that is closer
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let len = self.len;
+        assert!(i < len, "swap index (is {i}) should be < len (is {len})");
+        assert!(j < len, "swap index (is {j}) should be < len (is {len})");
+        if i == j {
+            return;
+        }
+        let a = self.walk_to(None, i);
+        let b = self.walk_to(Some((a, i)), j);
+        unsafe {
+            std::ptr::swap(&mut (*a.as_ptr()).elem, &mut (*b.as_ptr()).elem);
+        }
+    }
+
+    // where a cursor seeking to index ends up, as (cur, index). index == len is the ghost,
+    // anything past that panics
+    fn seek_from(
+        &self,
+        from: Option<(NonNull<Node<T>>, usize)>,
+        index: usize,
+    ) -> (Link<T>, Option<usize>) {
+        let len = self.len;
+        assert!(
+            index <= len,
+            "cursor index (is {index}) should be <= len (is {len})"
+        );
+        if index == len {
+            (None, None)
+        } else {
+            (Some(self.walk_to(from, index)), Some(index))
+        }
+    }
+
+    // finds the node at index `at` by walking from whichever of front, back or `from` (a node we
+    // already know the index of, like where a cursor is sitting) is the fewest hops away
+    //
+    // at must be < len, so there is always a node to find
+    fn walk_to(&self, from: Option<(NonNull<Node<T>>, usize)>, at: usize) -> NonNull<Node<T>> {
+        debug_assert!(at < self.len);
+        let mut start = self.front.unwrap();
+        let mut hops = at;
+        let mut forward = true;
+
+        let from_back = self.len - 1 - at;
+        if from_back < hops {
+            start = self.back.unwrap();
+            hops = from_back;
+            forward = false;
+        }
+        if let Some((node, idx)) = from
+            && idx.abs_diff(at) < hops
+        {
+            start = node;
+            hops = idx.abs_diff(at);
+            forward = at > idx;
+        }
+
+        let mut cur = start;
+        unsafe {
+            for _ in 0..hops {
+                cur = if forward {
+                    (*cur.as_ptr()).next.unwrap()
+                } else {
+                    (*cur.as_ptr()).prev.unwrap()
+                };
+            }
+        }
+        cur
+    }
+
+    // takes a node out of the chain and fixes up its neighbours (or front/back if it was at the edge)
+    // the node itself is left detached with no prev/next, the caller decides whether to free it or
+    // hand it to some other list
+    //
+    // SAFETY: node must be a node currently linked into self
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            let prev = (*node.as_ptr()).prev.take();
+            let next = (*node.as_ptr()).next.take();
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.front = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.back = prev,
+            }
+            self.len -= 1;
+        }
+    }
+
+    // the other way around, put a detached node (no prev/next) on either end
+    //
+    // SAFETY: node must be detached and not linked into any list
+    unsafe fn link_front(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            match self.front {
+                Some(old) => {
+                    (*old.as_ptr()).prev = Some(node);
+                    (*node.as_ptr()).next = Some(old);
+                }
+                None => self.back = Some(node),
+            }
+            self.front = Some(node);
+            self.len += 1;
+        }
+    }
+
+    unsafe fn link_back(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            match self.back {
+                Some(old) => {
+                    (*old.as_ptr()).next = Some(node);
+                    (*node.as_ptr()).prev = Some(old);
+                }
+                None => self.front = Some(node),
+            }
+            self.back = Some(node);
+            self.len += 1;
+        }
+    }
 }
 
 // traits in production quality linked list
@@ -371,6 +575,8 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
 
 impl<T> Drop for LinkedList<T> {
     fn drop(&mut self) {
+        // no point keeping the handle table up to date while everything goes away
+        self.handles = None;
         while let Some(_) = self.pop_front() {}
     }
 }
@@ -388,6 +594,137 @@ pub struct CursorMut<'a, T> {
     index: Option<usize>,
 }
 
+// the read only version. it only holds a shared reference to the list so any number of these
+// can walk the same list at once, and everything it hands out lives as long as the list borrow
+// (not just as long as the cursor) because nobody can change the list under us
+pub struct Cursor<'a, T> {
+    cur: Link<T>,
+    list: &'a LinkedList<T>,
+    index: Option<usize>,
+}
+
+// derive would want T: Clone, but we are only copying pointers
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            cur: None,
+            index: None,
+        }
+    }
+
+    // the cursor_* constructors start somewhere useful instead of the ghost
+    // on an empty list front and back are the ghost
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.move_prev();
+        cursor
+    }
+
+    // index == len puts us on the ghost, anything past that panics
+    pub fn cursor_at(&self, index: usize) -> Cursor<'_, T> {
+        let mut cursor = self.cursor();
+        cursor.seek(index);
+        cursor
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // moving is exactly the same dance as CursorMut, walk off either end and we are on the ghost,
+    // move off the ghost and we wrap around to the other end
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).next;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).prev;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).next
+            } else {
+                self.list.front
+            };
+            next.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = if let Some(cur) = self.cur {
+                (*cur.as_ptr()).prev
+            } else {
+                self.list.back
+            };
+            prev.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    // jump to index, walking from the front, the back or where we are now, whichever is closest
+    // seeking to len lands on the ghost
+    pub fn seek(&mut self, index: usize) {
+        (self.cur, self.index) = self.list.seek_from(self.cur.zip(self.index), index);
+    }
+
+    pub fn front(&self) -> Option<&'a T> {
+        self.list.front()
+    }
+
+    pub fn back(&self) -> Option<&'a T> {
+        self.list.back()
+    }
+}
+
 impl<T> LinkedList<T> {
     pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
         CursorMut {
@@ -396,6 +733,24 @@ impl<T> LinkedList<T> {
             index: None,
         }
     }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.seek(index);
+        cursor
+    }
 }
 
 impl<'a, T> CursorMut<'a, T> {
@@ -403,6 +758,22 @@ impl<'a, T> CursorMut<'a, T> {
         self.index
     }
 
+    // a read only view at the same position. it borrows the CursorMut so we can't edit while
+    // it is alive
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    // same as Cursor::seek. if we are on a real element we already know its index so walking
+    // from here is an option too, which is what makes edits near the last position cheap
+    pub fn seek(&mut self, index: usize) {
+        (self.cur, self.index) = self.list.seek_from(self.cur.zip(self.index), index);
+    }
+
     pub fn move_next(&mut self) {
         if let Some(cur) = self.cur {
             // we are on a real element
@@ -479,9 +850,66 @@ impl<'a, T> CursorMut<'a, T> {
         }
     }
 
+    // removing and inserting
+    // remove_current takes out the node under the cursor and moves the cursor to the next node,
+    // which takes over the removed node's index. if there is no next node we land on the ghost.
+    // on the ghost there is nothing to remove so we just return None
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        unsafe {
+            let next = (*cur.as_ptr()).next;
+            self.list.unlink_node(cur);
+            self.list.forget_node(cur);
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+            let boxed_node = Box::from_raw(cur.as_ptr());
+            Some(boxed_node.elem)
+        }
+    }
+
+    // same as remove_current but we don't free the node, we just give it away as a list of one
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T>> {
+        let cur = self.cur?;
+        unsafe {
+            let next = (*cur.as_ptr()).next;
+            self.list.unlink_node(cur);
+            self.list.forget_node(cur);
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+            Some(LinkedList {
+                front: Some(cur),
+                back: Some(cur),
+                len: 1,
+                _boo: PhantomData,
+                handles: None,
+            })
+        }
+    }
+
+    // inserting a single element is just splicing a list of one, and splice already knows how
+    // to handle the ghost and keep the index right
+    // on the ghost, insert_before puts the element at the back and insert_after at the front
+    pub fn insert_before(&mut self, elem: T) {
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        self.splice_before(input);
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        self.splice_after(input);
+    }
+
     // splits
     pub fn split_before(&mut self) -> LinkedList<T> {
         if let Some(cur) = self.cur {
+            // the nodes in front of us are leaving, their handles go stale
+            self.list.forget_span(self.list.front, self.index.unwrap());
             unsafe {
                 let old_len = self.list.len;
                 let old_idx = self.index.unwrap();
@@ -492,8 +920,14 @@ impl<'a, T> CursorMut<'a, T> {
                 let new_back = self.list.back;
                 let new_idx = Some(0);
 
+                // if there is no prev we are at the front and the output is empty. it must not
+                // keep pointing at our front or it would free our nodes when it's dropped
                 let output_len = old_len - new_len;
-                let output_front = self.list.front;
+                let output_front = if prev.is_some() {
+                    self.list.front
+                } else {
+                    None
+                };
                 let output_back = prev;
 
                 if let Some(prev) = prev {
@@ -511,15 +945,21 @@ impl<'a, T> CursorMut<'a, T> {
                     back: output_back,
                     len: output_len,
                     _boo: PhantomData,
+                    handles: None,
                 }
             }
         } else {
-            std::mem::replace(self.list, LinkedList::new())
+            self.list.take_nodes()
         }
     }
 
     pub fn split_after(&mut self) -> LinkedList<T> {
         if let Some(cur) = self.cur {
+            // the nodes after us are leaving, their handles go stale
+            unsafe {
+                let after = self.list.len - 1 - self.index.unwrap();
+                self.list.forget_span((*cur.as_ptr()).next, after);
+            }
             unsafe {
                 let old_len = self.list.len;
                 let old_idx = self.index.unwrap();
@@ -530,9 +970,10 @@ impl<'a, T> CursorMut<'a, T> {
                 let new_back = self.cur;
                 let new_idx = Some(old_idx);
 
+                // same thing as split_before, at the back the output is empty
                 let output_len = old_len - new_len;
                 let output_front = next;
-                let output_back = self.list.back;
+                let output_back = if next.is_some() { self.list.back } else { None };
 
                 if let Some(next) = next {
                     (*cur.as_ptr()).next = None;
@@ -549,10 +990,11 @@ impl<'a, T> CursorMut<'a, T> {
                     back: output_back,
                     len: output_len,
                     _boo: PhantomData,
+                    handles: None,
                 }
             }
         } else {
-            std::mem::replace(self.list, LinkedList::new())
+            self.list.take_nodes()
         }
     }
 
@@ -588,8 +1030,10 @@ impl<'a, T> CursorMut<'a, T> {
                 (*in_front.as_ptr()).prev = Some(back);
                 self.list.back = Some(in_back);
             } else {
-                // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                // We're empty, take over the input's nodes, remain on the ghost. our handle
+                // table stays, so the input's handles go stale like with any other splice
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             self.list.len += input.len;
@@ -647,8 +1091,10 @@ impl<'a, T> CursorMut<'a, T> {
                 (*in_back.as_ptr()).next = Some(front);
                 self.list.front = Some(in_front);
             } else {
-                // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                // We're empty, take over the input's nodes, remain on the ghost. our handle
+                // table stays, so the input's handles go stale like with any other splice
+                self.list.front = input.front.take();
+                self.list.back = input.back.take();
             }
 
             self.list.len += input.len;
@@ -660,60 +1106,1361 @@ impl<'a, T> CursorMut<'a, T> {
     }
 }
 
-// *const T and *mut T opt out of Sync and Send so we have to opt back in
-unsafe impl<T: Send> Send for LinkedList<T> {}
-unsafe impl<T: Sync> Sync for LinkedList<T> {}
+// sorting
+// bottom up merge sort straight on the nodes (Simon Tatham's version for linked lists).
+// we only ever follow and rewrite `next` while merging and leave `prev` as garbage, then one
+// walk at the end puts all the prev pointers, front and back right again. no allocation, no
+// element is moved, only relinked.
+//
+// the comparator is user code and can panic halfway through a merge. at any point the nodes are
+// in exactly one of three places:
+// - the merged output so far, out_head..out_tail
+// - what is left of the left run, psize nodes starting at p
+// - what is left of the right run followed by the rest of the list, everything reachable from q
+// so MergeGuard keeps track of those and its Drop stitches them back into one chain and fixes the
+// prev pointers. on success p and q are empty and out is the whole sorted list, so the very same
+// Drop is what finishes the sort. on a panic the order is unspecified but the list is valid
+struct MergeGuard<'a, T> {
+    list: &'a mut LinkedList<T>,
+    out_head: Link<T>,
+    out_tail: Link<T>,
+    p: Link<T>,
+    psize: usize,
+    q: Link<T>,
+}
 
-unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
-unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+impl<'a, T> MergeGuard<'a, T> {
+    // moves e onto the back of the output, only touches the old tail's next
+    unsafe fn push_out(&mut self, e: NonNull<Node<T>>) {
+        unsafe {
+            match self.out_tail {
+                Some(tail) => (*tail.as_ptr()).next = Some(e),
+                None => self.out_head = Some(e),
+            }
+            self.out_tail = Some(e);
+        }
+    }
+}
 
-unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
-unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+impl<'a, T> Drop for MergeGuard<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            // whatever is left of the left run goes after the output
+            while self.psize > 0 {
+                let p = self.p.unwrap();
+                self.p = (*p.as_ptr()).next;
+                self.psize -= 1;
+                self.push_out(p);
+            }
+            // and then the right run with the rest of the list still hanging off it
+            match self.out_tail {
+                Some(tail) => (*tail.as_ptr()).next = self.q,
+                None => self.out_head = self.q,
+            }
 
-#[allow(dead_code)]
-fn assert_properties() {
-    fn is_send<T: Send>() {}
-    fn is_sync<T: Sync>() {}
+            // one walk to fix up prev and find the back
+            let mut prev = None;
+            let mut cur = self.out_head;
+            while let Some(node) = cur {
+                (*node.as_ptr()).prev = prev;
+                prev = Some(node);
+                cur = (*node.as_ptr()).next;
+            }
+            self.list.front = self.out_head;
+            self.list.back = prev;
+        }
+    }
+}
 
-    is_send::<LinkedList<i32>>();
-    is_sync::<LinkedList<i32>>();
+impl<T> LinkedList<T> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
 
-    is_send::<IntoIter<i32>>();
-    is_sync::<IntoIter<i32>>();
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
 
-    is_send::<Iter<i32>>();
-    is_sync::<Iter<i32>>();
+    // stable, O(n log n) comparisons and O(1) extra space
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.len < 2 {
+            return;
+        }
 
-    is_send::<IterMut<i32>>();
-    is_sync::<IterMut<i32>>();
+        let front = self.front.take();
+        self.back = None;
+        let mut guard = MergeGuard {
+            list: self,
+            out_head: front,
+            out_tail: None,
+            p: None,
+            psize: 0,
+            q: None,
+        };
+
+        let mut insize = 1;
+        unsafe {
+            loop {
+                // last pass' output is this pass' input
+                guard.q = guard.out_head.take();
+                guard.out_tail = None;
+                let mut merges = 0;
+
+                while let Some(start) = guard.q {
+                    merges += 1;
+
+                    // the left run is up to insize nodes starting here, the right run starts
+                    // wherever that ends
+                    let mut q = Some(start);
+                    let mut psize = 0;
+                    while psize < insize {
+                        psize += 1;
+                        q = (*q.unwrap().as_ptr()).next;
+                        if q.is_none() {
+                            break;
+                        }
+                    }
+                    guard.p = Some(start);
+                    guard.psize = psize;
+                    guard.q = q;
+                    let mut qsize = insize;
+
+                    // merge the two runs, ties go to the left run so we stay stable
+                    while guard.psize > 0 || (qsize > 0 && guard.q.is_some()) {
+                        let take_left = if guard.psize == 0 {
+                            false
+                        } else if qsize == 0 || guard.q.is_none() {
+                            true
+                        } else {
+                            let p = guard.p.unwrap();
+                            let q = guard.q.unwrap();
+                            compare(&(*p.as_ptr()).elem, &(*q.as_ptr()).elem) != Ordering::Greater
+                        };
+
+                        let e = if take_left {
+                            let e = guard.p.unwrap();
+                            guard.p = (*e.as_ptr()).next;
+                            guard.psize -= 1;
+                            e
+                        } else {
+                            let e = guard.q.unwrap();
+                            guard.q = (*e.as_ptr()).next;
+                            qsize -= 1;
+                            e
+                        };
+                        guard.push_out(e);
+                    }
+                    // the left run's last next still points into the right run
+                    guard.p = None;
+                }
 
-    // is_send::<Cursor<i32>>();
-    // is_sync::<Cursor<i32>>();
+                // the tail's next is left over from the old order
+                (*guard.out_tail.unwrap().as_ptr()).next = None;
 
-    fn linked_list_covariant<'a, T>(x: LinkedList<&'static T>) -> LinkedList<&'a T> {
-        x
-    }
-    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
-        x
-    }
-    fn into_iter_covariant<'a, T>(x: IntoIter<&'static T>) -> IntoIter<&'a T> {
-        x
+                if merges <= 1 {
+                    break;
+                }
+                insize *= 2;
+            }
+        }
+        // guard dropped here, relinks prev, front and back
     }
-}
 
-/// ```compile-fail
-/// use twomanylinkedlist::sixth::IterMut;
-///
-/// fn iter_mut_covariant<'i, 'a, T>(x: IterMut<'i, &'static T>) -> IterMut<'i, &'a T> { x }
-/// ```
-fn iter_mut_invariant() {}
+    // the key function runs once per element. the keys need somewhere to live so this one is O(n)
+    // extra space, but the nodes are still relinked and never reallocated
+    pub fn sort_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        if self.len < 2 {
+            return;
+        }
 
-#[cfg(test)]
-mod test {
-    use super::LinkedList;
+        // if f or the key comparisons panic we haven't touched the list yet
+        let mut nodes: Vec<(K, NonNull<Node<T>>)> = Vec::with_capacity(self.len);
+        let mut cur = self.front;
+        unsafe {
+            while let Some(node) = cur {
+                nodes.push((f(&(*node.as_ptr()).elem), node));
+                cur = (*node.as_ptr()).next;
+            }
+        }
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
 
-    fn generate_test() -> LinkedList<i32> {
-        list_from(&[0, 1, 2, 3, 4, 5, 6])
+        // relink in the sorted order
+        let mut prev: Link<T> = None;
+        unsafe {
+            for &(_, node) in &nodes {
+                (*node.as_ptr()).prev = prev;
+                match prev {
+                    Some(prev) => (*prev.as_ptr()).next = Some(node),
+                    None => self.front = Some(node),
+                }
+                prev = Some(node);
+            }
+            (*prev.unwrap().as_ptr()).next = None;
+        }
+        self.back = prev;
+    }
+
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.iter().is_sorted()
+    }
+
+    pub fn is_sorted_by<F>(&self, mut compare: F) -> bool
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        self.iter().is_sorted_by(|a, b| compare(a, b))
+    }
+}
+
+// filtering
+// extract_if is a lazy iterator that walks the list front to back and unlinks (and hands back)
+// every element the predicate says yes to. we step past a node before asking about it, so if
+// the predicate panics nothing has been touched yet, and every removal is a single unlink_node
+// so len is always right. dropping it halfway just stops, the rest of the list stays as it is
+pub struct ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    list: &'a mut LinkedList<T>,
+    next: Link<T>,
+    remaining: usize,
+    pred: F,
+}
+
+impl<T> LinkedList<T> {
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            next: self.front,
+            remaining: self.len,
+            list: self,
+            pred,
+        }
+    }
+
+    // keeps only the elements f says yes to, in order
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        for _ in self.extract_if(|elem| !f(elem)) {}
+    }
+}
+
+impl<'a, T, F> Iterator for ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while let Some(node) = self.next {
+                self.next = (*node.as_ptr()).next;
+                self.remaining -= 1;
+                if (self.pred)(&mut (*node.as_ptr()).elem) {
+                    self.list.unlink_node(node);
+                    self.list.forget_node(node);
+                    let boxed_node = Box::from_raw(node.as_ptr());
+                    return Some(boxed_node.elem);
+                }
+            }
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+// ranges
+// split_range cuts a span out of the middle and hands it back as its own list. we walk to the
+// start from the closest end, then from the start to the end of the span, and relink the two
+// edges. drain is split_range plus an iterator over the cut out list, which means the list we
+// drained from is already whole again before the first element is even yielded. dropping the
+// Drain early drops the rest of the span and forgetting it just leaks the span, either way the
+// original list can't be left half unlinked
+pub struct Drain<'a, T> {
+    list: LinkedList<T>,
+    _boo: PhantomData<&'a mut LinkedList<T>>,
+}
+
+// turns any RangeBounds into a plain start..end and checks it against len
+fn resolve_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s
+            .checked_add(1)
+            .expect("attempted to index from after usize::MAX"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e
+            .checked_add(1)
+            .expect("attempted to index up to and including usize::MAX"),
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start (is {start}) should be <= range end (is {end})"
+    );
+    assert!(
+        end <= len,
+        "range end (is {end}) should be <= len (is {len})"
+    );
+    (start, end)
+}
+
+impl<T> LinkedList<T> {
+    pub fn split_range<R: RangeBounds<usize>>(&mut self, range: R) -> LinkedList<T> {
+        let (start, end) = resolve_range(range, self.len);
+        if start == end {
+            return LinkedList::new();
+        }
+
+        let first = self.walk_to(None, start);
+        let last = self.walk_to(Some((first, start)), end - 1);
+        // same as the cursor splits, only the nodes we take out lose their handles
+        self.forget_span(Some(first), end - start);
+        unsafe {
+            let prev = (*first.as_ptr()).prev.take();
+            let next = (*last.as_ptr()).next.take();
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.front = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.back = prev,
+            }
+        }
+        self.len -= end - start;
+
+        LinkedList {
+            front: Some(first),
+            back: Some(last),
+            len: end - start,
+            _boo: PhantomData,
+            handles: None,
+        }
+    }
+
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        Drain {
+            list: self.split_range(range),
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// runs of equal things
+// dedup walks the list once remembering the last node it kept. same_bucket gets the current
+// element first and the kept one second (like Vec::dedup_by) and says yes if the current one
+// should go. the predicate only ever looks, all the unlinking happens after it returns
+impl<T> LinkedList<T> {
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let Some(mut kept) = self.front else {
+            return;
+        };
+        unsafe {
+            let mut cur = (*kept.as_ptr()).next;
+            while let Some(node) = cur {
+                cur = (*node.as_ptr()).next;
+                if same_bucket(&mut (*node.as_ptr()).elem, &mut (*kept.as_ptr()).elem) {
+                    self.unlink_node(node);
+                    self.forget_node(node);
+                    drop(Box::from_raw(node.as_ptr()));
+                } else {
+                    kept = node;
+                }
+            }
+        }
+    }
+
+    // cuts the list into runs where pred holds for every neighbouring pair, each run is made of
+    // the original nodes. runs are cut off our front one at a time so if pred panics the runs
+    // found so far and the rest of the list are all valid lists and get dropped normally
+    pub fn chunk_by<F>(mut self, mut pred: F) -> LinkedList<LinkedList<T>>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut chunks = LinkedList::new();
+        unsafe {
+            while let Some(first) = self.front {
+                // find the end of this run
+                let mut last = first;
+                let mut len = 1;
+                while let Some(next) = (*last.as_ptr()).next {
+                    if !pred(&(*last.as_ptr()).elem, &(*next.as_ptr()).elem) {
+                        break;
+                    }
+                    last = next;
+                    len += 1;
+                }
+
+                let next = (*last.as_ptr()).next.take();
+                match next {
+                    Some(next) => (*next.as_ptr()).prev = None,
+                    None => self.back = None,
+                }
+                self.front = next;
+                self.len -= len;
+
+                chunks.push_back(LinkedList {
+                    front: Some(first),
+                    back: Some(last),
+                    len,
+                    _boo: PhantomData,
+                    handles: None,
+                });
+            }
+        }
+        chunks
+    }
+}
+
+// moving things around
+// none of these touch an element, they only rewire pointers
+impl<T> LinkedList<T> {
+    // every node swaps its prev and next, and the list swaps front and back
+    pub fn reverse(&mut self) {
+        let mut cur = self.front;
+        unsafe {
+            while let Some(node) = cur {
+                let node = &mut *node.as_ptr();
+                std::mem::swap(&mut node.prev, &mut node.next);
+                // what used to be next is now prev
+                cur = node.prev;
+            }
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    // the first k elements go to the back. the list is really a ring with one cut in it, so this
+    // is just joining back to front and cutting somewhere else
+    //
+    //   front -> A <-> B <-> C <-> D <- back     rotate_left(1)
+    //   front -> B <-> C <-> D <-> A <- back
+    pub fn rotate_left(&mut self, k: usize) {
+        let len = self.len;
+        assert!(
+            k <= len,
+            "rotation amount (is {k}) should be <= len (is {len})"
+        );
+        if k == 0 || k == len {
+            return;
+        }
+
+        let new_front = self.walk_to(None, k);
+        unsafe {
+            let new_back = (*new_front.as_ptr()).prev.take().unwrap();
+            (*new_back.as_ptr()).next = None;
+
+            let old_front = self.front.unwrap();
+            let old_back = self.back.unwrap();
+            (*old_back.as_ptr()).next = Some(old_front);
+            (*old_front.as_ptr()).prev = Some(old_back);
+
+            self.front = Some(new_front);
+            self.back = Some(new_back);
+        }
+    }
+
+    // the last k elements go to the front
+    pub fn rotate_right(&mut self, k: usize) {
+        let len = self.len;
+        assert!(
+            k <= len,
+            "rotation amount (is {k}) should be <= len (is {len})"
+        );
+        self.rotate_left(len - k);
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // the current node moves to an end and the cursor goes with it. on the ghost there is nothing
+    // to move
+    pub fn move_current_to_front(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.list.unlink_node(cur);
+                self.list.link_front(cur);
+            }
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_current_to_back(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.list.unlink_node(cur);
+                self.list.link_back(cur);
+            }
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    // pop an end of the list without walking the cursor there, like std's CursorMut. we keep our
+    // place unless we were on the node that goes: off the front we move on to the new front, off
+    // the back onto the ghost, same as remove_current would leave us
+    pub fn pop_front(&mut self) -> Option<T> {
+        let front = self.list.front?;
+        if self.cur == Some(front) {
+            self.move_next();
+        }
+        if let Some(index) = &mut self.index {
+            *index -= 1;
+        }
+        self.list.pop_front()
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let back = self.list.back?;
+        if self.cur == Some(back) {
+            self.cur = None;
+            self.index = None;
+        }
+        self.list.pop_back()
+    }
+}
+
+// handles
+// a NodeHandle remembers one node so we can get at it, remove it or insert next to it later in
+// O(1), without walking a cursor there. the catch is that the node might be gone by the time the
+// handle comes back, and reading through a pointer to a freed node is exactly the UB we can't
+// have. so a handle never gets dereferenced on its own say so:
+// - every list that hands out handles gets a unique id, and a handle only works on its own list
+// - the list keeps a table of the nodes it gave handles for, keyed by address. a handle only
+//   resolves if its address is in there, so we only ever follow pointers the table vouches for
+// - each entry gets a stamp from a counter that only goes up. if a node is freed and a new one
+//   lands on the same address it gets a new stamp and the old handle still doesn't match
+// a node is dropped from the table whenever it is freed or leaves the list, on its own or with a
+// bunch of others in a split. splits have to walk the part that leaves to find its handles (see
+// forget_span), which they only do if the list has handed out any. nodes that stay, or that move
+// around inside the list (sort, reverse, rotate, move_current_to_front...) keep their handles
+static NEXT_LIST_ID: AtomicU64 = AtomicU64::new(1);
+// shared by all lists, so the halves of split_at_current can't hand out the same stamp twice
+static NEXT_STAMP: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    list: u64,
+    node: usize,
+    stamp: u64,
+}
+
+struct HandleTable<T> {
+    id: u64,
+    // address -> (the node, its stamp)
+    live: HashMap<usize, (NonNull<Node<T>>, u64)>,
+}
+
+impl<T> LinkedList<T> {
+    // a node that already has a handle keeps its stamp, so handing out a second handle to it
+    // gives back an equal handle
+    fn register(&mut self, node: NonNull<Node<T>>) -> NodeHandle {
+        let table = self.handles.get_or_insert_with(|| {
+            Box::new(HandleTable {
+                id: NEXT_LIST_ID.fetch_add(1, AtomicOrdering::Relaxed),
+                live: HashMap::new(),
+            })
+        });
+        let addr = node.as_ptr().addr();
+        let stamp = match table.live.get(&addr) {
+            Some(&(_, stamp)) => stamp,
+            None => {
+                let stamp = NEXT_STAMP.fetch_add(1, AtomicOrdering::Relaxed);
+                table.live.insert(addr, (node, stamp));
+                stamp
+            }
+        };
+        NodeHandle {
+            list: table.id,
+            node: addr,
+            stamp,
+        }
+    }
+
+    // the node is leaving (or being freed), any handle to it is stale from now on
+    fn forget_node(&mut self, node: NonNull<Node<T>>) {
+        if let Some(table) = &mut self.handles {
+            table.live.remove(&node.as_ptr().addr());
+        }
+    }
+
+    fn forget_all_nodes(&mut self) {
+        if let Some(table) = &mut self.handles {
+            table.live.clear();
+        }
+    }
+
+    // count nodes starting at first are about to leave the list. the table doesn't know where
+    // its nodes are, so we walk the span, but only if there are any handles to lose
+    fn forget_span(&mut self, first: Link<T>, count: usize) {
+        let Some(table) = &mut self.handles else {
+            return;
+        };
+        if table.live.is_empty() {
+            return;
+        }
+        if count == self.len {
+            table.live.clear();
+            return;
+        }
+        let mut cur = first;
+        for _ in 0..count {
+            let node = cur.unwrap();
+            table.live.remove(&node.as_ptr().addr());
+            cur = unsafe { (*node.as_ptr()).next };
+        }
+    }
+
+    // every node leaves in a new list, we keep our (now empty) table so handles stay tied to us
+    fn take_nodes(&mut self) -> LinkedList<T> {
+        self.forget_all_nodes();
+        LinkedList {
+            front: self.front.take(),
+            back: self.back.take(),
+            len: std::mem::take(&mut self.len),
+            _boo: PhantomData,
+            handles: None,
+        }
+    }
+
+    // the only way from a NodeHandle to a pointer
+    fn resolve(&self, handle: NodeHandle) -> Link<T> {
+        let table = self.handles.as_ref()?;
+        if table.id != handle.list {
+            return None;
+        }
+        match table.live.get(&handle.node) {
+            Some(&(node, stamp)) if stamp == handle.stamp => Some(node),
+            _ => None,
+        }
+    }
+
+    // where a node sits, found by walking both ways at once until one of them hits an end, so it
+    // costs O(min(i, n - i)) rather than O(n)
+    fn index_of(&self, node: NonNull<Node<T>>) -> usize {
+        let (mut towards_front, mut towards_back) = (node, node);
+        let mut steps = 0;
+        unsafe {
+            loop {
+                match (*towards_front.as_ptr()).prev {
+                    Some(prev) => towards_front = prev,
+                    None => return steps,
+                }
+                match (*towards_back.as_ptr()).next {
+                    Some(next) => towards_back = next,
+                    None => return self.len - 1 - steps,
+                }
+                steps += 1;
+            }
+        }
+    }
+
+    pub fn push_front_handle(&mut self, elem: T) -> NodeHandle {
+        self.push_front(elem);
+        self.register(self.front.unwrap())
+    }
+
+    pub fn push_back_handle(&mut self, elem: T) -> NodeHandle {
+        self.push_back(elem);
+        self.register(self.back.unwrap())
+    }
+
+    pub fn contains_handle(&self, handle: NodeHandle) -> bool {
+        self.resolve(handle).is_some()
+    }
+
+    pub fn get_by_handle(&self, handle: NodeHandle) -> Option<&T> {
+        unsafe { self.resolve(handle).map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn get_mut_by_handle(&mut self, handle: NodeHandle) -> Option<&mut T> {
+        unsafe { self.resolve(handle).map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    // None if the handle is stale or from some other list
+    pub fn remove_by_handle(&mut self, handle: NodeHandle) -> Option<T> {
+        let node = self.resolve(handle)?;
+        unsafe {
+            self.unlink_node(node);
+            self.forget_node(node);
+            let boxed_node = Box::from_raw(node.as_ptr());
+            Some(boxed_node.elem)
+        }
+    }
+
+    // if the handle is no good we give the element back
+    pub fn insert_after_handle(&mut self, handle: NodeHandle, elem: T) -> Result<NodeHandle, T> {
+        let Some(at) = self.resolve(handle) else {
+            return Err(elem);
+        };
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: Some(at),
+                next: (*at.as_ptr()).next,
+                elem,
+            })));
+            match (*at.as_ptr()).next {
+                Some(next) => (*next.as_ptr()).prev = Some(new),
+                None => self.back = Some(new),
+            }
+            (*at.as_ptr()).next = Some(new);
+            self.len += 1;
+            Ok(self.register(new))
+        }
+    }
+
+    pub fn insert_before_handle(&mut self, handle: NodeHandle, elem: T) -> Result<NodeHandle, T> {
+        let Some(at) = self.resolve(handle) else {
+            return Err(elem);
+        };
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: (*at.as_ptr()).prev,
+                next: Some(at),
+                elem,
+            })));
+            match (*at.as_ptr()).prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(new),
+                None => self.front = Some(new),
+            }
+            (*at.as_ptr()).prev = Some(new);
+            self.len += 1;
+            Ok(self.register(new))
+        }
+    }
+
+    // a cursor sitting on the handle's node. the cursor needs to know its index so this walks,
+    // see index_of
+    pub fn cursor_from(&self, handle: NodeHandle) -> Option<Cursor<'_, T>> {
+        let node = self.resolve(handle)?;
+        Some(Cursor {
+            cur: Some(node),
+            index: Some(self.index_of(node)),
+            list: self,
+        })
+    }
+
+    pub fn cursor_from_mut(&mut self, handle: NodeHandle) -> Option<CursorMut<'_, T>> {
+        let node = self.resolve(handle)?;
+        Some(CursorMut {
+            cur: Some(node),
+            index: Some(self.index_of(node)),
+            list: self,
+        })
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // a handle to whatever we are on, None on the ghost
+    pub fn current_handle(&mut self) -> Option<NodeHandle> {
+        self.cur.map(|node| self.list.register(node))
+    }
+
+    // insert_before and insert_after, plus a handle to the new node so we can find it again
+    // later without walking
+    pub fn insert_before_handle(&mut self, elem: T) -> NodeHandle {
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        let node = input.front.unwrap();
+        self.splice_before(input);
+        self.list.register(node)
+    }
+
+    pub fn insert_after_handle(&mut self, elem: T) -> NodeHandle {
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        let node = input.front.unwrap();
+        self.splice_after(input);
+        self.list.register(node)
+    }
+}
+
+// two cursors at once
+// a CursorMut borrows the whole list, so two of them over one list can't both exist. what we can
+// do is really cut the list in two at the cursor, give each half its own CursorMut, and glue the
+// halves back together afterwards. the halves need to live somewhere while the cursors are out,
+// so this is scoped like std::thread::scope: both cursors are handed to a closure, and when it
+// returns (or panics) SplitGuard's Drop joins the halves again
+struct SplitGuard<'c, 'a, T> {
+    cursor: &'c mut CursorMut<'a, T>,
+    before: LinkedList<T>,
+    after: LinkedList<T>,
+}
+
+impl<'c, 'a, T> Drop for SplitGuard<'c, 'a, T> {
+    fn drop(&mut self) {
+        let boundary = self.before.len;
+        let start = self.after.front;
+        // both halves kept their share of the handle table up to date, every node in either one
+        // is ours again so the shares just go back together
+        let mut table = self.before.handles.take();
+        match (&mut table, self.after.handles.take()) {
+            (Some(table), Some(after)) => table.live.extend(after.live),
+            (None, after) => table = after,
+            _ => {}
+        }
+        // the cursor's list was emptied when we split, nothing is lost by overwriting it
+        *self.cursor.list = std::mem::take(&mut self.before);
+        self.cursor.list.append(&mut self.after);
+        self.cursor.list.handles = table;
+        // we can't trust the old current node to still exist, so the cursor goes to the start of
+        // what was the second half (the ghost if that half ended up empty)
+        self.cursor.cur = start;
+        self.cursor.index = start.map(|_| boundary);
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // the first cursor gets everything before the current node and starts on its back, the second
+    // gets the current node and everything after and starts on its front. both start right next
+    // to the split. from the ghost the whole list goes to the first one and the second is empty
+    //
+    // each cursor only sees its own half and can do anything a CursorMut can to it. indices are
+    // relative to the half. afterwards we are on the front of the (edited) second half with the
+    // index recomputed
+    //
+    // no node leaves the list for good, so handles keep working, and handles made inside f work
+    // on the whole list afterwards. for that each half gets the handle table entries of its own
+    // nodes, under the list's id. sorting the entries out walks the shorter half, but only if
+    // there are any handles
+    pub fn split_at_current<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(CursorMut<'_, T>, CursorMut<'_, T>) -> R,
+    {
+        // without its table the list has no handles to forget while we cut it
+        let table = self.list.handles.take();
+        let mut before = self.split_before();
+        let mut after = std::mem::take(self.list);
+        let id = table.as_ref().map_or_else(
+            || NEXT_LIST_ID.fetch_add(1, AtomicOrdering::Relaxed),
+            |t| t.id,
+        );
+        let mut before_live = table.map(|t| t.live).unwrap_or_default();
+        let mut after_live = HashMap::new();
+        if !before_live.is_empty() {
+            let shorter = if after.len < before.len {
+                &after
+            } else {
+                &before
+            };
+            let mut addrs = std::collections::HashSet::new();
+            let mut cur = shorter.front;
+            while let Some(node) = cur {
+                addrs.insert(node.as_ptr().addr());
+                cur = unsafe { (*node.as_ptr()).next };
+            }
+            let (in_shorter, rest) = before_live
+                .into_iter()
+                .partition(|(addr, _)| addrs.contains(addr));
+            (before_live, after_live) = if after.len < before.len {
+                (rest, in_shorter)
+            } else {
+                (in_shorter, rest)
+            };
+        }
+        before.handles = Some(Box::new(HandleTable {
+            id,
+            live: before_live,
+        }));
+        after.handles = Some(Box::new(HandleTable {
+            id,
+            live: after_live,
+        }));
+        let mut guard = SplitGuard {
+            cursor: self,
+            before,
+            after,
+        };
+        f(
+            guard.before.cursor_back_mut(),
+            guard.after.cursor_front_mut(),
+        )
+        // guard dropped here, halves rejoined
+    }
+}
+
+// merging
+// merge_by is one merge step of the sort: both lists are already sorted, we walk them both and
+// relink the nodes into one chain. MergeGuard takes care of the prev pointers at the end and of
+// a panicking comparator, so it is O(n + m) and allocates nothing
+impl<T> LinkedList<T> {
+    pub fn merge(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, |a, b| a.cmp(b));
+    }
+
+    // both lists should be sorted by compare, other is left empty. it's stable, on a tie our
+    // element goes first
+    pub fn merge_by<F>(&mut self, other: &mut Self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if other.is_empty() {
+            return;
+        }
+        // either way other's nodes become ours and its handles go stale, our table stays ours
+        if self.is_empty() {
+            self.append(&mut other.take_nodes());
+            return;
+        }
+
+        // other's nodes are ours from now on, even if compare panics
+        other.forget_all_nodes();
+        let theirs = other.front.take();
+        other.back = None;
+        let ours_len = self.len;
+        self.len += std::mem::take(&mut other.len);
+
+        let ours = self.front.take();
+        self.back = None;
+        let mut guard = MergeGuard {
+            list: self,
+            out_head: None,
+            out_tail: None,
+            p: ours,
+            psize: ours_len,
+            q: theirs,
+        };
+        unsafe {
+            while let (Some(p), Some(q)) = (guard.p.filter(|_| guard.psize > 0), guard.q) {
+                let e = if compare(&(*p.as_ptr()).elem, &(*q.as_ptr()).elem) != Ordering::Greater {
+                    guard.p = (*p.as_ptr()).next;
+                    guard.psize -= 1;
+                    p
+                } else {
+                    guard.q = (*q.as_ptr()).next;
+                    q
+                };
+                guard.push_out(e);
+            }
+        }
+        // guard dropped here, whatever is left of either list goes on the end
+    }
+
+    // k way merge. lists are merged pairwise in rounds like the passes of the sort, so every
+    // element takes part in O(log k) merges. ties go to the list that came first
+    pub fn merge_all<I>(lists: I) -> Self
+    where
+        I: IntoIterator<Item = LinkedList<T>>,
+        T: Ord,
+    {
+        Self::merge_all_by(lists, |a, b| a.cmp(b))
+    }
+
+    pub fn merge_all_by<I, F>(lists: I, mut compare: F) -> Self
+    where
+        I: IntoIterator<Item = LinkedList<T>>,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut lists: Vec<_> = lists.into_iter().collect();
+        while lists.len() > 1 {
+            let mut round = Vec::with_capacity(lists.len().div_ceil(2));
+            let mut pairs = lists.into_iter();
+            while let Some(mut first) = pairs.next() {
+                if let Some(mut second) = pairs.next() {
+                    first.merge_by(&mut second, &mut compare);
+                }
+                round.push(first);
+            }
+            lists = round;
+        }
+        lists.pop().unwrap_or_default()
+    }
+}
+
+// lazily merges any number of sorted Iters without touching the lists. every step looks at the
+// head of each iterator so it's O(k) per element, which is fine for a handful of shards
+pub struct MergeIter<'a, T, F = fn(&T, &T) -> Ordering> {
+    heads: Vec<(&'a T, Iter<'a, T>)>,
+    compare: F,
+}
+
+impl<'a, T: Ord> MergeIter<'a, T> {
+    pub fn new<I>(iters: I) -> Self
+    where
+        I: IntoIterator<Item = Iter<'a, T>>,
+    {
+        Self::new_by(iters, T::cmp)
+    }
+}
+
+impl<'a, T, F> MergeIter<'a, T, F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    pub fn new_by<I>(iters: I, compare: F) -> Self
+    where
+        I: IntoIterator<Item = Iter<'a, T>>,
+    {
+        let heads = iters
+            .into_iter()
+            .filter_map(|mut iter| iter.next().map(|head| (head, iter)))
+            .collect();
+        MergeIter { heads, compare }
+    }
+}
+
+impl<'a, T, F> Iterator for MergeIter<'a, T, F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // strictly smaller wins so ties go to the earlier iterator
+        let mut min = 0;
+        for i in 1..self.heads.len() {
+            if (self.compare)(self.heads[i].0, self.heads[min].0) == Ordering::Less {
+                min = i;
+            }
+        }
+        let (head, iter) = self.heads.get_mut(min)?;
+        let result = *head;
+        match iter.next() {
+            Some(next) => *head = next,
+            // keep the order of the rest for the tie breaking
+            None => {
+                self.heads.remove(min);
+            }
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.heads.iter().map(|(_, iter)| iter.len() + 1).sum();
+        (len, Some(len))
+    }
+}
+
+// set algebra
+// for lists that are sorted (by Ord) and used as small ordered sets. all four operations are the
+// same walk down both lists, comparing the fronts like a merge, they only differ in what they
+// keep: things only in self, things in both, things only in other. duplicates are matched up one
+// to one, so with no duplicates this is exactly what BTreeSet does
+//
+// the into_* versions eat both lists and reuse their nodes, the ones that don't make it are freed.
+// once one list runs out the rest of the other is kept or dropped in one go. compare can't break
+// anything if it panics because all three lists are whole between comparisons
+impl<T: Ord> LinkedList<T> {
+    fn into_set_op(
+        mut self,
+        mut other: Self,
+        keep_ours: bool,
+        keep_both: bool,
+        keep_theirs: bool,
+    ) -> Self {
+        let mut out = LinkedList::new();
+        unsafe {
+            while let (Some(a), Some(b)) = (self.front, other.front) {
+                match (*a.as_ptr()).elem.cmp(&(*b.as_ptr()).elem) {
+                    Ordering::Less => {
+                        self.unlink_node(a);
+                        self.forget_node(a);
+                        if keep_ours {
+                            out.link_back(a);
+                        } else {
+                            drop(Box::from_raw(a.as_ptr()));
+                        }
+                    }
+                    Ordering::Greater => {
+                        other.unlink_node(b);
+                        other.forget_node(b);
+                        if keep_theirs {
+                            out.link_back(b);
+                        } else {
+                            drop(Box::from_raw(b.as_ptr()));
+                        }
+                    }
+                    Ordering::Equal => {
+                        self.unlink_node(a);
+                        self.forget_node(a);
+                        other.unlink_node(b);
+                        other.forget_node(b);
+                        drop(Box::from_raw(b.as_ptr()));
+                        if keep_both {
+                            out.link_back(a);
+                        } else {
+                            drop(Box::from_raw(a.as_ptr()));
+                        }
+                    }
+                }
+            }
+        }
+        if keep_ours {
+            out.append(&mut self);
+        }
+        if keep_theirs {
+            out.append(&mut other);
+        }
+        out
+    }
+
+    pub fn into_union(self, other: Self) -> Self {
+        self.into_set_op(other, true, true, true)
+    }
+
+    pub fn into_intersection(self, other: Self) -> Self {
+        self.into_set_op(other, false, true, false)
+    }
+
+    pub fn into_difference(self, other: Self) -> Self {
+        self.into_set_op(other, true, false, false)
+    }
+
+    pub fn into_symmetric_difference(self, other: Self) -> Self {
+        self.into_set_op(other, true, false, true)
+    }
+
+    // the borrowing versions are lazy iterators over references, like BTreeSet's
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T> {
+        Union(SetMerge::new(self, other))
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T> {
+        Intersection(SetMerge::new(self, other))
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T> {
+        Difference(SetMerge::new(self, other))
+    }
+
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T> {
+        SymmetricDifference(SetMerge::new(self, other))
+    }
+}
+
+// the shared walk for the borrowing versions, holding the next element of each side
+struct SetMerge<'a, T> {
+    ours: Iter<'a, T>,
+    theirs: Iter<'a, T>,
+    our_head: Option<&'a T>,
+    their_head: Option<&'a T>,
+}
+
+impl<'a, T: Ord> SetMerge<'a, T> {
+    fn new(ours: &'a LinkedList<T>, theirs: &'a LinkedList<T>) -> Self {
+        let mut ours = ours.iter();
+        let mut theirs = theirs.iter();
+        SetMerge {
+            our_head: ours.next(),
+            their_head: theirs.next(),
+            ours,
+            theirs,
+        }
+    }
+
+    fn next(&mut self, keep_ours: bool, keep_both: bool, keep_theirs: bool) -> Option<&'a T> {
+        loop {
+            match (self.our_head, self.their_head) {
+                (None, None) => return None,
+                (Some(a), None) => {
+                    self.our_head = self.ours.next();
+                    return if keep_ours { Some(a) } else { None };
+                }
+                (None, Some(b)) => {
+                    self.their_head = self.theirs.next();
+                    return if keep_theirs { Some(b) } else { None };
+                }
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    Ordering::Less => {
+                        self.our_head = self.ours.next();
+                        if keep_ours {
+                            return Some(a);
+                        }
+                    }
+                    Ordering::Greater => {
+                        self.their_head = self.theirs.next();
+                        if keep_theirs {
+                            return Some(b);
+                        }
+                    }
+                    Ordering::Equal => {
+                        self.our_head = self.ours.next();
+                        self.their_head = self.theirs.next();
+                        if keep_both {
+                            return Some(a);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+pub struct Union<'a, T>(SetMerge<'a, T>);
+pub struct Intersection<'a, T>(SetMerge<'a, T>);
+pub struct Difference<'a, T>(SetMerge<'a, T>);
+pub struct SymmetricDifference<'a, T>(SetMerge<'a, T>);
+
+impl<'a, T: Ord> Iterator for Union<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next(true, true, true)
+    }
+}
+
+impl<'a, T: Ord> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next(false, true, false)
+    }
+}
+
+impl<'a, T: Ord> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next(true, false, false)
+    }
+}
+
+impl<'a, T: Ord> Iterator for SymmetricDifference<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next(true, false, true)
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+// a Cursor is basically a &LinkedList<T>, and &U is Send only when U is Sync
+unsafe impl<'a, T: Sync> Send for Cursor<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Cursor<'a, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<LinkedList<i32>>();
+    is_sync::<LinkedList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    is_send::<Cursor<i32>>();
+    is_sync::<Cursor<i32>>();
+
+    fn linked_list_covariant<'a, T>(x: LinkedList<&'static T>) -> LinkedList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+    fn into_iter_covariant<'a, T>(x: IntoIter<&'static T>) -> IntoIter<&'a T> {
+        x
+    }
+    fn cursor_covariant<'i, 'a, T>(x: Cursor<'i, &'static T>) -> Cursor<'i, &'a T> {
+        x
+    }
+}
+
+/// ```compile-fail
+/// use twomanylinkedlist::sixth::IterMut;
+///
+/// fn iter_mut_covariant<'i, 'a, T>(x: IterMut<'i, &'static T>) -> IterMut<'i, &'a T> { x }
+/// ```
+fn iter_mut_invariant() {}
+
+#[cfg(test)]
+mod test {
+    use super::{Iter, LinkedList, MergeIter};
+
+    fn generate_test() -> LinkedList<i32> {
+        list_from(&[0, 1, 2, 3, 4, 5, 6])
     }
 
     fn list_from<T: Clone>(v: &[T]) -> LinkedList<T> {
@@ -721,387 +2468,1662 @@ mod test {
     }
 
     #[test]
-    fn test_basic_front() {
-        let mut list = LinkedList::new();
-
-        // Try to break an empty list
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+    fn test_basic_front() {
+        let mut list = LinkedList::new();
+
+        // Try to break an empty list
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Try to break a one item list
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Mess around
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        list.push_front(20);
+        assert_eq!(list.len(), 2);
+        list.push_front(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq!(list.len(), 2);
+        list.push_front(40);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(40));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = LinkedList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        assert_eq!(m.pop_front(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_front(), Some(2));
+        assert_eq!(m.pop_front(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_front(), None);
+        m.push_back(1);
+        m.push_back(3);
+        m.push_back(5);
+        m.push_back(7);
+        assert_eq!(m.pop_front(), Some(1));
+
+        let mut n = LinkedList::new();
+        n.push_front(2);
+        n.push_front(3);
+        {
+            assert_eq!(n.front().unwrap(), &3);
+            let x = n.front_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_front(), Some(0));
+        assert_eq!(n.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn test_iterator() {
+        let m = generate_test();
+        for (i, elt) in m.iter().enumerate() {
+            assert_eq!(i as i32, *elt);
+        }
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_iterator_double_end() {
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.next().unwrap(), &6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(it.next_back().unwrap(), &4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next_back().unwrap(), &5);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_rev_iter() {
+        let m = generate_test();
+        for (i, elt) in m.iter().rev().enumerate() {
+            assert_eq!(6 - i as i32, *elt);
+        }
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().rev().next(), None);
+        n.push_front(4);
+        let mut it = n.iter().rev();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_mut_iter() {
+        let mut m = generate_test();
+        let mut len = m.len();
+        for (i, elt) in m.iter_mut().enumerate() {
+            assert_eq!(i as i32, *elt);
+            len -= 1;
+        }
+        assert_eq!(len, 0);
+        let mut n = LinkedList::new();
+        assert!(n.iter_mut().next().is_none());
+        n.push_front(4);
+        n.push_back(5);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert!(it.next().is_some());
+        assert!(it.next().is_some());
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_iterator_mut_double_end() {
+        let mut n = LinkedList::new();
+        assert!(n.iter_mut().next_back().is_none());
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(*it.next().unwrap(), 6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(*it.next_back().unwrap(), 4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(*it.next_back().unwrap(), 5);
+        assert!(it.next_back().is_none());
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut n: LinkedList<u8> = list_from(&[]);
+        let mut m = list_from(&[]);
+        assert!(n == m);
+        n.push_front(1);
+        assert!(n != m);
+        m.push_back(1);
+        assert!(n == m);
+
+        let n = list_from(&[2, 3, 4]);
+        let m = list_from(&[1, 2, 3]);
+        assert!(n != m);
+    }
+
+    #[test]
+    fn test_ord() {
+        let n = list_from(&[]);
+        let m = list_from(&[1, 2, 3]);
+        assert!(n < m);
+        assert!(m > n);
+        assert!(n <= n);
+        assert!(n >= n);
+    }
+
+    #[test]
+    fn test_ord_nan() {
+        let nan = 0.0f64 / 0.0;
+        let n = list_from(&[nan]);
+        let m = list_from(&[nan]);
+        assert!(!(n < m));
+        assert!(!(n > m));
+        assert!(!(n <= m));
+        assert!(!(n >= m));
+
+        let n = list_from(&[nan]);
+        let one = list_from(&[1.0f64]);
+        assert!(!(n < one));
+        assert!(!(n > one));
+        assert!(!(n <= one));
+        assert!(!(n >= one));
+
+        let u = list_from(&[1.0f64, 2.0, nan]);
+        let v = list_from(&[1.0f64, 2.0, 3.0]);
+        assert!(!(u < v));
+        assert!(!(u > v));
+        assert!(!(u <= v));
+        assert!(!(u >= v));
+
+        let s = list_from(&[1.0f64, 2.0, 4.0, 2.0]);
+        let t = list_from(&[1.0f64, 2.0, 3.0, 2.0]);
+        assert!(!(s < t));
+        assert!(s > one);
+        assert!(!(s <= one));
+        assert!(s >= one);
+    }
+
+    #[test]
+    fn test_debug() {
+        let list: LinkedList<i32> = (0..10).collect();
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
+
+        let list: LinkedList<&str> = vec!["just", "one", "test", "more"]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
+    }
+
+    #[test]
+    fn test_hashmap() {
+        // Check that HashMap works with this as a key
+
+        let list1: LinkedList<i32> = (0..10).collect();
+        let list2: LinkedList<i32> = (1..11).collect();
+        let mut map = std::collections::HashMap::new();
+
+        assert_eq!(map.insert(list1.clone(), "list1"), None);
+        assert_eq!(map.insert(list2.clone(), "list2"), None);
+
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.get(&list1), Some(&"list1"));
+        assert_eq!(map.get(&list2), Some(&"list2"));
+
+        assert_eq!(map.remove(&list1), Some("list1"));
+        assert_eq!(map.remove(&list2), Some("list2"));
+
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_move_peek() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), Some(&mut 3));
+        assert_eq!(cursor.peek_prev(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 6));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&mut 5));
+        assert_eq!(cursor.index(), Some(5));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.peek_next(), Some(&mut 6));
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
+        assert_eq!(cursor.index(), Some(4));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        // check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[7, 1, 8, 2, 3, 4, 5, 6]
+        );
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
+        );
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(7));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
+
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 8, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        let mut p: LinkedList<u32> = LinkedList::new();
+        p.extend([100, 101, 102, 103]);
+        let mut q: LinkedList<u32> = LinkedList::new();
+        q.extend([200, 201, 202, 203]);
+        cursor.splice_after(p);
+        cursor.splice_before(q);
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[200, 201, 202, 203, 1, 100, 101, 102, 103, 8, 2, 3, 4, 5, 6]
+        );
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        let tmp = cursor.split_before();
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
+        m = tmp;
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        let tmp = cursor.split_after();
+        assert_eq!(
+            tmp.into_iter().collect::<Vec<_>>(),
+            &[102, 103, 8, 2, 3, 4, 5, 6]
+        );
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[200, 201, 202, 203, 1, 100, 101]
+        );
+    }
+
+    #[test]
+    fn test_cursor_mut_insert_remove_index() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        let mut cursor = m.cursor_mut();
+        // on the ghost of an empty list
+        assert_eq!(cursor.remove_current(), None);
+        assert!(cursor.remove_current_as_list().is_none());
+        cursor.insert_after(2);
+        cursor.insert_before(3);
+        cursor.insert_after(1);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        // on 2
+        cursor.insert_before(10);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.insert_after(20);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), Some(&mut 20));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 10, 2, 20, 3]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        cursor.move_prev();
+        // on 20
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(removed.len(), 1);
+        check_links(&removed);
+        assert_eq!(removed.into_iter().collect::<Vec<_>>(), &[20]);
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 10, 2]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.remove_current(), None);
+        assert!(m.is_empty());
+        check_links(&m);
+    }
+
+    #[test]
+    fn test_cursor_move_peek_immutable() {
+        let m: LinkedList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.front(), Some(&1));
+        assert_eq!(cursor.back(), Some(&6));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_next(), Some(&2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&5));
+        assert_eq!(cursor.index(), Some(4));
+
+        // two cursors over the same list at once, and references outlive the cursor
+        let mut other = cursor.clone();
+        other.move_next();
+        let five = {
+            let cursor = cursor;
+            cursor.current().unwrap()
+        };
+        assert_eq!(other.current(), Some(&6));
+        assert_eq!(five, &5);
+
+        let mut m = m;
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        {
+            let view = cursor.as_cursor();
+            assert_eq!(view.current(), Some(&2));
+            assert_eq!(view.index(), Some(1));
+        }
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.as_cursor().current(), Some(&3));
+
+        let empty: LinkedList<u32> = LinkedList::new();
+        let mut cursor = empty.cursor();
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.front(), None);
+    }
+
+    #[test]
+    fn test_cursor_threads() {
+        let m: LinkedList<u32> = (0..10).collect();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut cursor = m.cursor();
+                    let mut sum = 0;
+                    cursor.move_next();
+                    while let Some(x) = cursor.current() {
+                        sum += x;
+                        cursor.move_next();
+                    }
+                    assert_eq!(sum, 45);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_cursor_positioned() {
+        let mut m: LinkedList<u32> = list_from(&[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(m.cursor_front().current(), Some(&0));
+        assert_eq!(m.cursor_front().index(), Some(0));
+        assert_eq!(m.cursor_back().current(), Some(&6));
+        assert_eq!(m.cursor_back().index(), Some(6));
+        assert_eq!(m.cursor_at(3).current(), Some(&3));
+        assert_eq!(m.cursor_at(7).current(), None);
+        assert_eq!(m.cursor_at(7).index(), None);
+
+        assert_eq!(m.cursor_front_mut().current(), Some(&mut 0));
+        assert_eq!(m.cursor_back_mut().current(), Some(&mut 6));
+        let mut cursor = m.cursor_at_mut(5);
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(cursor.remove_current(), Some(5));
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(cursor.current(), Some(&mut 6));
+        check_links(&m);
+
+        let mut empty: LinkedList<u32> = LinkedList::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+        assert_eq!(empty.cursor_at(0).index(), None);
+        assert_eq!(empty.cursor_front_mut().current(), None);
+    }
+
+    #[test]
+    fn test_cursor_seek() {
+        // every start to every target, from the ghost and from real elements
+        let mut m: LinkedList<u32> = (0..9).collect();
+        for from in 0..=m.len() {
+            for to in 0..=m.len() {
+                let mut cursor = m.cursor_at(from);
+                cursor.seek(to);
+                if to == 9 {
+                    assert_eq!(cursor.index(), None);
+                    assert_eq!(cursor.current(), None);
+                } else {
+                    assert_eq!(cursor.index(), Some(to));
+                    assert_eq!(cursor.current(), Some(&(to as u32)));
+                }
+
+                let mut cursor = m.cursor_at_mut(from);
+                cursor.seek(to);
+                assert_eq!(cursor.current().copied(), cursor.index().map(|i| i as u32));
+            }
+        }
+
+        // seeking keeps working after edits moved things around
+        let mut cursor = m.cursor_at_mut(4);
+        cursor.insert_before(100);
+        assert_eq!(cursor.index(), Some(5));
+        cursor.seek(4);
+        assert_eq!(cursor.current(), Some(&mut 100));
+        cursor.seek(9);
+        assert_eq!(cursor.current(), Some(&mut 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "cursor index (is 4) should be <= len (is 3)")]
+    fn test_cursor_seek_out_of_bounds() {
+        let m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        m.cursor_at(4);
+    }
+
+    #[test]
+    fn test_split_at_edges() {
+        // splitting off nothing at either end must hand back a truly empty list
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        let mut cursor = m.cursor_front_mut();
+        let mut before = cursor.split_before();
+        assert!(before.is_empty());
+        assert_eq!(before.front(), None);
+        before.push_back(0);
+        check_links(&before);
+        let mut cursor = m.cursor_back_mut();
+        let mut after = cursor.split_after();
+        assert!(after.is_empty());
+        assert_eq!(after.back(), None);
+        after.push_front(4);
+        check_links(&after);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_append_prepend() {
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        let mut n: LinkedList<u32> = list_from(&[4, 5]);
+        m.append(&mut n);
+        assert!(n.is_empty());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+        let mut n: LinkedList<u32> = list_from(&[0]);
+        m.prepend(&mut n);
+        assert!(n.is_empty());
+        m.append(&mut n);
+        n.prepend(&mut m);
+        assert!(m.is_empty());
+        check_links(&n);
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_split_off() {
+        for at in 0..=5 {
+            let mut m: LinkedList<u32> = (0..5).collect();
+            let tail = m.split_off(at);
+            check_links(&m);
+            check_links(&tail);
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                (0..at as u32).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                tail.into_iter().collect::<Vec<_>>(),
+                (at as u32..5).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_ops() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.insert(0, 2);
+        m.insert(0, 0);
+        m.insert(1, 1);
+        m.insert(3, 4);
+        m.insert(3, 3);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+
+        assert_eq!(m.get(0), Some(&0));
+        assert_eq!(m.get(4), Some(&4));
+        assert_eq!(m.get(5), None);
+        *m.get_mut(3).unwrap() = 30;
+        assert!(m.contains(&30));
+        assert!(!m.contains(&3));
+        assert_eq!(m.get_mut(9), None);
+
+        m.swap(0, 4);
+        m.swap(1, 1);
+        m.swap(3, 2);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 1, 30, 2, 0]);
+
+        assert_eq!(m.remove(4), 0);
+        assert_eq!(m.remove(0), 4);
+        assert_eq!(m.remove(1), 30);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 4) should be <= len (is 3)")]
+    fn test_insert_out_of_bounds() {
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        m.insert(4, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 3) should be < len (is 3)")]
+    fn test_remove_out_of_bounds() {
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        m.remove(3);
+    }
 
-        // Try to break a one item list
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+    #[test]
+    #[should_panic(expected = "split index (is 1) should be <= len (is 0)")]
+    fn test_split_off_out_of_bounds() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.split_off(1);
+    }
 
-        // Mess around
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        list.push_front(20);
-        assert_eq!(list.len(), 2);
-        list.push_front(30);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front(), Some(30));
-        assert_eq!(list.len(), 2);
-        list.push_front(40);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front(), Some(40));
-        assert_eq!(list.len(), 2);
-        assert_eq!(list.pop_front(), Some(20));
-        assert_eq!(list.len(), 1);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+    #[test]
+    #[should_panic(expected = "swap index (is 7) should be < len (is 3)")]
+    fn test_swap_out_of_bounds() {
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        m.swap(0, 7);
     }
 
     #[test]
-    fn test_basic() {
-        let mut m = LinkedList::new();
-        assert_eq!(m.pop_front(), None);
-        assert_eq!(m.pop_back(), None);
-        assert_eq!(m.pop_front(), None);
-        m.push_front(1);
-        assert_eq!(m.pop_front(), Some(1));
-        m.push_back(2);
-        m.push_back(3);
-        assert_eq!(m.len(), 2);
-        assert_eq!(m.pop_front(), Some(2));
-        assert_eq!(m.pop_front(), Some(3));
-        assert_eq!(m.len(), 0);
-        assert_eq!(m.pop_front(), None);
+    fn test_sort() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.sort();
+        check_links(&m);
+        assert!(m.is_sorted());
+
         m.push_back(1);
-        m.push_back(3);
-        m.push_back(5);
-        m.push_back(7);
-        assert_eq!(m.pop_front(), Some(1));
+        m.sort();
+        check_links(&m);
 
-        let mut n = LinkedList::new();
-        n.push_front(2);
-        n.push_front(3);
-        {
-            assert_eq!(n.front().unwrap(), &3);
-            let x = n.front_mut().unwrap();
-            assert_eq!(*x, 3);
-            *x = 0;
+        // every length up to a few runs past a power of two, in a scrambled order
+        for len in 0..40 {
+            let mut m: LinkedList<i32> = (0..len).map(|x| (x * 17 + 5) % 23).collect();
+            let mut v: Vec<_> = m.iter().cloned().collect();
+            m.sort();
+            v.sort();
+            check_links(&m);
+            assert!(m.is_sorted());
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+        }
+
+        let mut m: LinkedList<i32> = list_from(&[5, 4, 3, 2, 1]);
+        m.sort_by(|a, b| b.cmp(a));
+        assert!(!m.is_sorted());
+        assert!(m.is_sorted_by(|a, b| a >= b));
+        m.sort_by_key(|x| *x);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        // sort by the first field only, the second records the original order
+        let pairs = [
+            (3, 0),
+            (1, 1),
+            (3, 2),
+            (2, 3),
+            (1, 4),
+            (3, 5),
+            (2, 6),
+            (1, 7),
+        ];
+        let mut v = pairs.to_vec();
+        v.sort_by_key(|p| p.0);
+
+        let mut m: LinkedList<(i32, i32)> = list_from(&pairs);
+        m.sort_by_key(|p| p.0);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+
+        let mut m: LinkedList<(i32, i32)> = list_from(&pairs);
+        m.sort_by_cached_key(|p| p.0.to_string());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    fn test_sort_by_cached_key_calls() {
+        let mut m: LinkedList<i32> = list_from(&[3, 1, 2, 5, 4]);
+        let mut calls = 0;
+        m.sort_by_cached_key(|x| {
+            calls += 1;
+            -x
+        });
+        assert_eq!(calls, 5);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_sort_panic_safe() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        // panic on every possible comparison in turn, the list must stay whole every time
+        for panic_at in 0..30 {
+            let mut m: LinkedList<i32> = (0..13).map(|x| (x * 7) % 13).collect();
+            let mut count = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                m.sort_by(|a, b| {
+                    count += 1;
+                    if count > panic_at {
+                        panic!("comparator gave up");
+                    }
+                    a.cmp(b)
+                })
+            }));
+            check_links(&m);
+            let mut v: Vec<_> = m.iter().cloned().collect();
+            v.sort();
+            assert_eq!(v, (0..13).collect::<Vec<_>>());
+            if result.is_ok() {
+                assert!(m.is_sorted());
+            }
         }
+
+        let mut m: LinkedList<i32> = list_from(&[3, 1, 2]);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.sort_by_cached_key(|x| {
+                if *x == 2 {
+                    panic!("no key for you");
+                }
+                *x
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[3, 1, 2]);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        m.retain(|x| x % 3 != 0);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 4, 5, 7, 8]);
+
+        m.retain_mut(|x| {
+            *x *= 10;
+            *x > 20
+        });
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[40, 50, 70, 80]);
+
+        m.retain(|_| false);
+        check_links(&m);
+        assert!(m.is_empty());
+        m.retain(|_| false);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let evens: Vec<_> = m.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(evens, &[0, 2, 4, 6, 8]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3, 5, 7, 9]);
+
+        // lazy, stopping early leaves the rest alone
         {
-            assert_eq!(n.back().unwrap(), &2);
-            let y = n.back_mut().unwrap();
-            assert_eq!(*y, 2);
-            *y = 1;
+            let mut iter = m.extract_if(|x| *x > 2);
+            assert_eq!(iter.size_hint(), (0, Some(5)));
+            assert_eq!(iter.next(), Some(3));
+            assert_eq!(iter.size_hint(), (0, Some(3)));
         }
-        assert_eq!(n.pop_front(), Some(0));
-        assert_eq!(n.pop_front(), Some(1));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 5, 7, 9]);
+
+        // can edit the ones that stay
+        let taken: Vec<_> = m
+            .extract_if(|x| {
+                *x += 1;
+                *x == 10
+            })
+            .collect();
+        assert_eq!(taken, &[10]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 6, 8]);
     }
 
     #[test]
-    fn test_iterator() {
-        let m = generate_test();
-        for (i, elt) in m.iter().enumerate() {
-            assert_eq!(i as i32, *elt);
+    fn test_retain_panic_safe() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.retain(|x| {
+                if *x == 6 {
+                    panic!("predicate gave up");
+                }
+                x % 2 == 1
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.len(), 7);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 3, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn test_split_range() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let mid = m.split_range(3..7);
+        check_links(&m);
+        check_links(&mid);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 7, 8, 9]);
+        assert_eq!(mid.iter().cloned().collect::<Vec<_>>(), &[3, 4, 5, 6]);
+
+        let front = m.split_range(..=1);
+        check_links(&m);
+        assert_eq!(front.iter().cloned().collect::<Vec<_>>(), &[0, 1]);
+        let back = m.split_range(2..);
+        check_links(&m);
+        assert_eq!(back.iter().cloned().collect::<Vec<_>>(), &[8, 9]);
+        let none = m.split_range(1..1);
+        assert!(none.is_empty());
+        let all = m.split_range(..);
+        check_links(&m);
+        assert!(m.is_empty());
+        assert_eq!(all.iter().cloned().collect::<Vec<_>>(), &[2, 7]);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let mut drain = m.drain(2..8);
+        assert_eq!(drain.len(), 6);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(7));
+        assert_eq!(drain.collect::<Vec<_>>(), &[3, 4, 5, 6]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 8, 9]);
+
+        // dropped partway
+        {
+            let mut drain = m.drain(1..=2);
+            assert_eq!(drain.next(), Some(1));
         }
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().next(), None);
-        n.push_front(4);
-        let mut it = n.iter();
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next().unwrap(), &4);
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert_eq!(it.next(), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 9]);
+
+        let mut m: LinkedList<i32> = (0..3).collect();
+        assert_eq!(m.drain(..).rev().collect::<Vec<_>>(), &[2, 1, 0]);
+        assert!(m.is_empty());
+        assert_eq!(m.drain(..).next(), None);
     }
 
+    // forgotten partway, the span leaks on purpose, which miri rightly complains about
     #[test]
-    fn test_iterator_double_end() {
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().next(), None);
-        n.push_front(4);
-        n.push_front(5);
-        n.push_front(6);
-        let mut it = n.iter();
-        assert_eq!(it.size_hint(), (3, Some(3)));
-        assert_eq!(it.next().unwrap(), &6);
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert_eq!(it.next_back().unwrap(), &4);
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next_back().unwrap(), &5);
-        assert_eq!(it.next_back(), None);
-        assert_eq!(it.next(), None);
+    #[cfg_attr(miri, ignore)]
+    fn test_drain_forget() {
+        let mut m: LinkedList<String> = (0..5).map(|x| x.to_string()).collect();
+        let mut drain = m.drain(1..4);
+        assert_eq!(drain.next().as_deref(), Some("1"));
+        std::mem::forget(drain);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &["0", "4"]);
     }
 
     #[test]
-    fn test_rev_iter() {
-        let m = generate_test();
-        for (i, elt) in m.iter().rev().enumerate() {
-            assert_eq!(6 - i as i32, *elt);
+    #[should_panic(expected = "range end (is 5) should be <= len (is 3)")]
+    fn test_drain_out_of_bounds() {
+        let mut m: LinkedList<i32> = (0..3).collect();
+        m.drain(1..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start (is 2) should be <= range end (is 1)")]
+    fn test_split_range_backwards() {
+        let mut m: LinkedList<i32> = (0..3).collect();
+        #[allow(clippy::reversed_empty_ranges)]
+        m.split_range(2..1);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut m: LinkedList<i32> = list_from(&[1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        m.dedup();
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 1, 4]);
+
+        let mut m: LinkedList<i32> = list_from(&[10, 11, 20, 21, 22, 30, 12]);
+        m.dedup_by_key(|x| *x / 10);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[10, 20, 30, 12]);
+
+        // same_bucket gets (current, kept) and can fold the dropped one into the kept one
+        let mut m: LinkedList<i32> = list_from(&[1, 2, 3, 10, 11, 30]);
+        m.dedup_by(|cur, kept| {
+            if *cur / 10 == *kept / 10 {
+                *kept += *cur;
+                true
+            } else {
+                false
+            }
+        });
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[6, 21, 30]);
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.dedup();
+        assert!(m.is_empty());
+        m.push_back(7);
+        m.push_back(7);
+        m.dedup();
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[7]);
+    }
+
+    #[test]
+    fn test_chunk_by() {
+        let m: LinkedList<i32> = list_from(&[1, 1, 2, 3, 3, 3, 1]);
+        let chunks = m.chunk_by(|a, b| a == b);
+        assert_eq!(chunks.len(), 4);
+        for chunk in &chunks {
+            check_links(chunk);
         }
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().rev().next(), None);
-        n.push_front(4);
-        let mut it = n.iter().rev();
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next().unwrap(), &4);
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert_eq!(it.next(), None);
+        let chunks: Vec<Vec<i32>> = chunks
+            .into_iter()
+            .map(|c| c.into_iter().collect())
+            .collect();
+        assert_eq!(chunks, vec![vec![1, 1], vec![2], vec![3, 3, 3], vec![1]]);
+
+        // ascending runs
+        let m: LinkedList<i32> = list_from(&[1, 2, 3, 2, 5, 0]);
+        let chunks: Vec<Vec<i32>> = m
+            .chunk_by(|a, b| a < b)
+            .into_iter()
+            .map(|c| c.into_iter().collect())
+            .collect();
+        assert_eq!(chunks, vec![vec![1, 2, 3], vec![2, 5], vec![0]]);
+
+        let m: LinkedList<i32> = LinkedList::new();
+        assert!(m.chunk_by(|a, b| a == b).is_empty());
     }
 
     #[test]
-    fn test_mut_iter() {
-        let mut m = generate_test();
-        let mut len = m.len();
-        for (i, elt) in m.iter_mut().enumerate() {
-            assert_eq!(i as i32, *elt);
-            len -= 1;
+    fn test_reverse() {
+        let mut m: LinkedList<i32> = (0..5).collect();
+        m.reverse();
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 3, 2, 1, 0]);
+        m.push_front(5);
+        m.push_back(-1);
+        check_links(&m);
+        m.reverse();
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[-1, 0, 1, 2, 3, 4, 5]
+        );
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.reverse();
+        assert!(m.is_empty());
+        m.push_back(1);
+        m.reverse();
+        check_links(&m);
+        assert_eq!(m.front(), Some(&1));
+    }
+
+    #[test]
+    fn test_rotate() {
+        for k in 0..=5 {
+            let mut m: LinkedList<i32> = (0..5).collect();
+            let mut v: Vec<_> = (0..5).collect();
+            m.rotate_left(k);
+            v.rotate_left(k);
+            check_links(&m);
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+            m.rotate_right(k);
+            v.rotate_right(k);
+            check_links(&m);
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
         }
-        assert_eq!(len, 0);
-        let mut n = LinkedList::new();
-        assert!(n.iter_mut().next().is_none());
-        n.push_front(4);
-        n.push_back(5);
-        let mut it = n.iter_mut();
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert!(it.next().is_some());
-        assert!(it.next().is_some());
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert!(it.next().is_none());
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.rotate_left(0);
+        m.rotate_right(0);
+        assert!(m.is_empty());
     }
 
     #[test]
-    fn test_iterator_mut_double_end() {
-        let mut n = LinkedList::new();
-        assert!(n.iter_mut().next_back().is_none());
-        n.push_front(4);
-        n.push_front(5);
-        n.push_front(6);
-        let mut it = n.iter_mut();
-        assert_eq!(it.size_hint(), (3, Some(3)));
-        assert_eq!(*it.next().unwrap(), 6);
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert_eq!(*it.next_back().unwrap(), 4);
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(*it.next_back().unwrap(), 5);
-        assert!(it.next_back().is_none());
-        assert!(it.next().is_none());
+    #[should_panic(expected = "rotation amount (is 4) should be <= len (is 3)")]
+    fn test_rotate_out_of_bounds() {
+        let mut m: LinkedList<i32> = (0..3).collect();
+        m.rotate_right(4);
     }
 
     #[test]
-    fn test_eq() {
-        let mut n: LinkedList<u8> = list_from(&[]);
-        let mut m = list_from(&[]);
-        assert!(n == m);
-        n.push_front(1);
-        assert!(n != m);
-        m.push_back(1);
-        assert!(n == m);
+    fn test_cursor_move_current() {
+        let mut m: LinkedList<i32> = (0..5).collect();
+        let mut cursor = m.cursor_at_mut(2);
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+        cursor.seek(4);
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), Some(4));
+        cursor.seek(1);
+        cursor.move_current_to_back();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.current(), Some(&mut 0));
+        assert_eq!(cursor.peek_next(), None);
+        cursor.move_next();
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 1, 3, 4, 0]);
 
-        let n = list_from(&[2, 3, 4]);
-        let m = list_from(&[1, 2, 3]);
-        assert!(n != m);
+        let mut m: LinkedList<i32> = list_from(&[1]);
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_current_to_back();
+        cursor.move_current_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&m);
     }
 
     #[test]
-    fn test_ord() {
-        let n = list_from(&[]);
-        let m = list_from(&[1, 2, 3]);
-        assert!(n < m);
-        assert!(m > n);
-        assert!(n <= n);
-        assert!(n >= n);
+    fn test_cursor_pop_ends() {
+        let mut m: LinkedList<i32> = (0..5).collect();
+        let mut cursor = m.cursor_at_mut(2);
+        assert_eq!(cursor.pop_front(), Some(0));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.pop_back(), Some(4));
+        assert_eq!(cursor.index(), Some(1));
+        cursor.seek(0);
+        assert_eq!(cursor.pop_front(), Some(1));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.seek(1);
+        assert_eq!(cursor.pop_back(), Some(3));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.pop_front(), Some(2));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.pop_front(), None);
+        assert_eq!(cursor.pop_back(), None);
+        check_links(&m);
+        assert!(m.is_empty());
+
+        let mut m: LinkedList<i32> = list_from(&[1]);
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.pop_front(), Some(1));
+        assert_eq!(cursor.index(), None);
+        check_links(&m);
     }
 
     #[test]
-    fn test_ord_nan() {
-        let nan = 0.0f64 / 0.0;
-        let n = list_from(&[nan]);
-        let m = list_from(&[nan]);
-        assert!(!(n < m));
-        assert!(!(n > m));
-        assert!(!(n <= m));
-        assert!(!(n >= m));
-
-        let n = list_from(&[nan]);
-        let one = list_from(&[1.0f64]);
-        assert!(!(n < one));
-        assert!(!(n > one));
-        assert!(!(n <= one));
-        assert!(!(n >= one));
-
-        let u = list_from(&[1.0f64, 2.0, nan]);
-        let v = list_from(&[1.0f64, 2.0, 3.0]);
-        assert!(!(u < v));
-        assert!(!(u > v));
-        assert!(!(u <= v));
-        assert!(!(u >= v));
+    fn test_handles() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let two = m.push_back_handle(2);
+        let one = m.push_front_handle(1);
+        let four = m.push_back_handle(4);
+        assert_eq!(m.get_by_handle(one), Some(&1));
+        assert_eq!(m.get_by_handle(four), Some(&4));
+
+        let three = m.insert_after_handle(two, 3).unwrap();
+        let zero = m.insert_before_handle(one, 0).unwrap();
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
 
-        let s = list_from(&[1.0f64, 2.0, 4.0, 2.0]);
-        let t = list_from(&[1.0f64, 2.0, 3.0, 2.0]);
-        assert!(!(s < t));
-        assert!(s > one);
-        assert!(!(s <= one));
-        assert!(s >= one);
+        *m.get_mut_by_handle(three).unwrap() = 30;
+        assert_eq!(m.remove_by_handle(two), Some(2));
+        assert_eq!(m.remove_by_handle(four), Some(4));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 30]);
+
+        // removed nodes have stale handles
+        assert!(!m.contains_handle(two));
+        assert_eq!(m.get_by_handle(two), None);
+        assert_eq!(m.remove_by_handle(two), None);
+        assert_eq!(m.insert_after_handle(four, 5), Err(5));
+        assert!(m.cursor_from(two).is_none());
+
+        // popping makes them stale too
+        assert_eq!(m.pop_front(), Some(0));
+        assert!(!m.contains_handle(zero));
+        assert!(m.contains_handle(one));
+
+        // the cursor inserts give handles back as well, if we ask
+        let mut cursor = m.cursor_front_mut();
+        let before = cursor.insert_before_handle(-1);
+        let after = cursor.insert_after_handle(2);
+        assert_eq!(cursor.current_handle(), Some(one));
+        assert_eq!(m.get_by_handle(before), Some(&-1));
+        assert_eq!(m.get_by_handle(after), Some(&2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[-1, 1, 2, 30]);
     }
 
     #[test]
-    fn test_debug() {
-        let list: LinkedList<i32> = (0..10).collect();
-        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
+    fn test_handles_other_list_and_reuse() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut n: LinkedList<i32> = LinkedList::new();
+        let a = m.push_back_handle(1);
+        let b = n.push_back_handle(1);
+        assert_ne!(a, b);
+        assert_eq!(n.get_by_handle(a), None);
+        assert_eq!(m.remove_by_handle(b), None);
+        assert_eq!(n.len(), 1);
+
+        // free a node and make new ones until one lands on the old address, the old handle must
+        // still be stale
+        assert_eq!(m.remove_by_handle(a), Some(1));
+        let mut fresh = Vec::new();
+        for i in 0..8 {
+            fresh.push(m.push_back_handle(i));
+        }
+        assert!(!m.contains_handle(a));
+        for (i, h) in fresh.iter().enumerate() {
+            assert_eq!(m.get_by_handle(*h), Some(&(i as i32)));
+        }
+    }
 
-        let list: LinkedList<&str> = vec!["just", "one", "test", "more"]
+    #[test]
+    fn test_handles_survive_relinking() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let handles: Vec<_> = [5, 3, 1, 4, 2]
             .iter()
-            .copied()
+            .map(|x| m.push_back_handle(*x))
             .collect();
-        assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
+        m.sort();
+        m.reverse();
+        m.rotate_left(2);
+        for (h, x) in handles.iter().zip([5, 3, 1, 4, 2]) {
+            assert_eq!(m.get_by_handle(*h), Some(&x));
+        }
+
+        // move to front through a handle, the lru way
+        m.cursor_from_mut(handles[2])
+            .unwrap()
+            .move_current_to_front();
+        assert_eq!(m.front(), Some(&1));
+        assert_eq!(m.get_by_handle(handles[2]), Some(&1));
+        check_links(&m);
+
+        // splitting only drops the handles of the nodes that leave
+        let kept: Vec<_> = m.iter().take(2).cloned().collect();
+        let tail = m.split_off(2);
+        for (h, x) in handles.iter().zip([5, 3, 1, 4, 2]) {
+            assert_eq!(m.contains_handle(*h), kept.contains(&x));
+            assert!(!tail.contains_handle(*h));
+        }
     }
 
     #[test]
-    fn test_hashmap() {
-        // Check that HashMap works with this as a key
-
-        let list1: LinkedList<i32> = (0..10).collect();
-        let list2: LinkedList<i32> = (1..11).collect();
-        let mut map = std::collections::HashMap::new();
+    fn test_handles_append_prepend() {
+        // the other list's nodes leave it, so its handles go stale, whether we were empty or not
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut n = LinkedList::new();
+        let two = n.push_back_handle(2);
+        m.append(&mut n);
+        assert_eq!(m.get_by_handle(two), None);
+        assert_eq!(n.get_by_handle(two), None);
+        let one = n.push_back_handle(1);
+        m.prepend(&mut n);
+        assert_eq!(m.get_by_handle(one), None);
+
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut n = LinkedList::new();
+        let one = n.push_back_handle(1);
+        m.prepend(&mut n);
+        assert_eq!(m.get_by_handle(one), None);
+        assert_eq!(n.get_by_handle(one), None);
+
+        // our own handles stay
+        let three = m.push_back_handle(3);
+        n.push_back(4);
+        m.append(&mut n);
+        n.push_back(0);
+        m.prepend(&mut n);
+        assert_eq!(m.get_by_handle(three), Some(&3));
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 3, 4]);
+        check_links(&m);
+    }
 
-        assert_eq!(map.insert(list1.clone(), "list1"), None);
-        assert_eq!(map.insert(list2.clone(), "list2"), None);
+    #[test]
+    fn test_handles_survive_splits() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let handles: Vec<_> = (0..10).map(|x| m.push_back_handle(x)).collect();
+        let alive = |m: &LinkedList<i32>| {
+            handles
+                .iter()
+                .filter(|h| m.contains_handle(**h))
+                .map(|h| *m.get_by_handle(*h).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let mut cursor = m.cursor_at_mut(2);
+        let front = cursor.split_before();
+        let mut cursor = m.cursor_at_mut(5);
+        let back = cursor.split_after();
+        assert_eq!(alive(&m), &[2, 3, 4, 5, 6, 7]);
+        assert_eq!(alive(&front), &[] as &[i32]);
+        assert_eq!(alive(&back), &[] as &[i32]);
+
+        let middle = m.split_range(1..3);
+        assert_eq!(middle.iter().cloned().collect::<Vec<_>>(), &[3, 4]);
+        assert_eq!(alive(&m), &[2, 5, 6, 7]);
+        let tail = m.split_off(4);
+        assert!(tail.is_empty());
+        assert_eq!(alive(&m), &[2, 5, 6, 7]);
+
+        // from the ghost everything leaves, and no handle follows it
+        let all = m.cursor_mut().split_after();
+        assert_eq!(alive(&m), &[] as &[i32]);
+        assert_eq!(alive(&all), &[] as &[i32]);
+        // the list still hands out handles that work
+        let h = m.push_back_handle(42);
+        assert_eq!(m.get_by_handle(h), Some(&42));
+    }
 
-        assert_eq!(map.len(), 2);
+    #[test]
+    fn test_cursor_from() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let handles: Vec<_> = (0..7).map(|x| m.push_back_handle(x)).collect();
+        for (i, h) in handles.iter().enumerate() {
+            let cursor = m.cursor_from(*h).unwrap();
+            assert_eq!(cursor.index(), Some(i));
+            assert_eq!(cursor.current(), Some(&(i as i32)));
+        }
+        let mut cursor = m.cursor_from_mut(handles[5]).unwrap();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 6));
+        assert_eq!(cursor.index(), Some(6));
+        assert_eq!(cursor.remove_current(), Some(6));
+        assert!(!m.contains_handle(handles[6]));
+    }
 
-        assert_eq!(map.get(&list1), Some(&"list1"));
-        assert_eq!(map.get(&list2), Some(&"list2"));
+    #[test]
+    fn test_split_at_current() {
+        let mut m: LinkedList<i32> = (0..6).collect();
+        let mut cursor = m.cursor_at_mut(3);
+        let sum = cursor.split_at_current(|mut before, mut after| {
+            assert_eq!(before.current(), Some(&mut 2));
+            assert_eq!(before.index(), Some(2));
+            assert_eq!(after.current(), Some(&mut 3));
+            assert_eq!(after.index(), Some(0));
+            assert_eq!(before.peek_next(), None);
+            assert_eq!(after.peek_prev(), None);
+
+            // edit both halves at the same time
+            before.insert_after(20);
+            before.move_prev();
+            assert_eq!(before.remove_current(), Some(1));
+            after.remove_current();
+            after.insert_before(30);
+            after.move_next();
+            *after.current().unwrap() *= 100;
+            7
+        });
+        assert_eq!(sum, 7);
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 30));
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 2, 20, 30, 4, 500]
+        );
+    }
 
-        assert_eq!(map.remove(&list1), Some("list1"));
-        assert_eq!(map.remove(&list2), Some("list2"));
+    #[test]
+    fn test_split_at_current_handles() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let handles: Vec<_> = (0..6).map(|x| m.push_back_handle(x)).collect();
+        let mut cursor = m.cursor_at_mut(2);
+        let (made_before, made_after) = cursor.split_at_current(|mut before, mut after| {
+            // removing a node in either half still makes its handle stale
+            before.move_prev();
+            assert_eq!(before.remove_current(), Some(0));
+            after.move_next();
+            assert_eq!(after.remove_current(), Some(3));
+            (
+                before.insert_after_handle(10),
+                after.current_handle().unwrap(),
+            )
+        });
+        // the untouched nodes and the ones made inside all resolve on the whole list again
+        let alive: Vec<_> = handles
+            .iter()
+            .map(|h| m.get_by_handle(*h).copied())
+            .collect();
+        assert_eq!(alive, &[None, Some(1), Some(2), None, Some(4), Some(5)]);
+        assert_eq!(m.get_by_handle(made_before), Some(&10));
+        assert_eq!(m.get_by_handle(made_after), Some(&4));
+        assert_eq!(m.remove_by_handle(handles[5]), Some(5));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 10, 2, 4]);
 
-        assert!(map.is_empty());
+        // the ghost path and a list without handles end up the same
+        let mut cursor = m.cursor_mut();
+        cursor.split_at_current(|_, _| {});
+        assert_eq!(m.get_by_handle(handles[1]), Some(&1));
+        let mut n: LinkedList<i32> = (0..3).collect();
+        let mut cursor = n.cursor_at_mut(1);
+        let h = cursor.split_at_current(|_, mut after| after.current_handle().unwrap());
+        assert_eq!(n.get_by_handle(h), Some(&1));
     }
 
     #[test]
-    fn test_cursor_move_peek() {
-        let mut m: LinkedList<u32> = LinkedList::new();
-        m.extend([1, 2, 3, 4, 5, 6]);
+    fn test_split_at_current_edges() {
+        // from the ghost everything is in the first half
+        let mut m: LinkedList<i32> = (0..3).collect();
         let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        assert_eq!(cursor.current(), Some(&mut 1));
-        assert_eq!(cursor.peek_next(), Some(&mut 2));
-        assert_eq!(cursor.peek_prev(), None);
-        assert_eq!(cursor.index(), Some(0));
-        cursor.move_prev();
-        assert_eq!(cursor.current(), None);
-        assert_eq!(cursor.peek_next(), Some(&mut 1));
-        assert_eq!(cursor.peek_prev(), Some(&mut 6));
-        assert_eq!(cursor.index(), None);
-        cursor.move_next();
-        cursor.move_next();
-        assert_eq!(cursor.current(), Some(&mut 2));
-        assert_eq!(cursor.peek_next(), Some(&mut 3));
-        assert_eq!(cursor.peek_prev(), Some(&mut 1));
-        assert_eq!(cursor.index(), Some(1));
+        cursor.split_at_current(|mut before, mut after| {
+            assert_eq!(before.current(), Some(&mut 2));
+            assert_eq!(after.current(), None);
+            after.insert_after(3);
+        });
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(3));
+        check_links(&m);
 
-        let mut cursor = m.cursor_mut();
-        cursor.move_prev();
-        assert_eq!(cursor.current(), Some(&mut 6));
-        assert_eq!(cursor.peek_next(), None);
-        assert_eq!(cursor.peek_prev(), Some(&mut 5));
-        assert_eq!(cursor.index(), Some(5));
-        cursor.move_next();
+        // emptying the second half leaves us on the ghost
+        let mut cursor = m.cursor_front_mut();
+        cursor.split_at_current(|before, mut after| {
+            assert_eq!(before.index(), None);
+            after.split_after();
+            after.remove_current();
+        });
         assert_eq!(cursor.current(), None);
-        assert_eq!(cursor.peek_next(), Some(&mut 1));
-        assert_eq!(cursor.peek_prev(), Some(&mut 6));
         assert_eq!(cursor.index(), None);
-        cursor.move_prev();
-        cursor.move_prev();
-        assert_eq!(cursor.current(), Some(&mut 5));
-        assert_eq!(cursor.peek_next(), Some(&mut 6));
-        assert_eq!(cursor.peek_prev(), Some(&mut 4));
-        assert_eq!(cursor.index(), Some(4));
+        assert!(m.is_empty());
+        check_links(&m);
     }
 
     #[test]
-    fn test_cursor_mut_insert() {
-        let mut m: LinkedList<u32> = LinkedList::new();
-        m.extend([1, 2, 3, 4, 5, 6]);
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        cursor.splice_before(Some(7).into_iter().collect());
-        cursor.splice_after(Some(8).into_iter().collect());
-        // check_links(&m);
-        assert_eq!(
-            m.iter().cloned().collect::<Vec<_>>(),
-            &[7, 1, 8, 2, 3, 4, 5, 6]
-        );
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        cursor.move_prev();
-        cursor.splice_before(Some(9).into_iter().collect());
-        cursor.splice_after(Some(10).into_iter().collect());
+    fn test_split_at_current_palindrome() {
+        fn is_palindrome(list: &mut LinkedList<char>) -> bool {
+            let odd = list.len() % 2 == 1;
+            let mut cursor = list.cursor_at_mut(list.len() / 2);
+            cursor.split_at_current(|mut before, mut after| {
+                if odd {
+                    // the middle one has nothing to match against
+                    after.move_next();
+                }
+                // walk outwards from the middle
+                loop {
+                    match (before.current(), after.current()) {
+                        (Some(a), Some(b)) if a == b => {
+                            before.move_prev();
+                            after.move_next();
+                        }
+                        (None, None) => return true,
+                        _ => return false,
+                    }
+                }
+            })
+        }
+        for (word, expected) in [
+            ("abba", true),
+            ("abcba", true),
+            ("a", true),
+            ("", true),
+            ("abca", false),
+            ("abcda", false),
+        ] {
+            let mut m: LinkedList<char> = word.chars().collect();
+            assert_eq!(is_palindrome(&mut m), expected, "{word}");
+            check_links(&m);
+            assert_eq!(m.iter().collect::<String>(), word);
+        }
+    }
+
+    #[test]
+    fn test_split_at_current_panic() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut m: LinkedList<i32> = (0..6).collect();
+        let mut cursor = m.cursor_at_mut(2);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            cursor.split_at_current(|mut before, _after| {
+                before.remove_current();
+                panic!("oops");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut m: LinkedList<i32> = list_from(&[1, 3, 5, 7]);
+        let mut n: LinkedList<i32> = list_from(&[0, 2, 3, 8, 9]);
+        m.merge(&mut n);
+        assert!(n.is_empty());
         check_links(&m);
+        check_links(&n);
         assert_eq!(
             m.iter().cloned().collect::<Vec<_>>(),
-            &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
+            &[0, 1, 2, 3, 3, 5, 7, 8, 9]
         );
 
-        /* remove_current not impl'd
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        cursor.move_prev();
-        assert_eq!(cursor.remove_current(), None);
-        cursor.move_next();
-        cursor.move_next();
-        assert_eq!(cursor.remove_current(), Some(7));
-        cursor.move_prev();
-        cursor.move_prev();
-        cursor.move_prev();
-        assert_eq!(cursor.remove_current(), Some(9));
-        cursor.move_next();
-        assert_eq!(cursor.remove_current(), Some(10));
+        let mut empty = LinkedList::new();
+        m.merge(&mut empty);
+        assert_eq!(m.len(), 9);
+        empty.merge(&mut m);
+        assert!(m.is_empty());
+        check_links(&empty);
+        assert_eq!(empty.len(), 9);
+
+        // stable, ties keep ours first
+        let mut m: LinkedList<(i32, char)> = list_from(&[(1, 'a'), (2, 'a'), (2, 'b')]);
+        let mut n: LinkedList<(i32, char)> = list_from(&[(1, 'x'), (2, 'x'), (3, 'x')]);
+        m.merge_by(&mut n, |a, b| a.0.cmp(&b.0));
         check_links(&m);
-        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-        */
+        assert_eq!(m.iter().map(|p| p.1).collect::<String>(), "axabxx");
+
+        // other's handles go stale whether or not we were empty, ours keep working
+        for ours_empty in [false, true] {
+            let mut m: LinkedList<i32> = LinkedList::new();
+            let ours = (!ours_empty).then(|| m.push_back_handle(1));
+            let mut n: LinkedList<i32> = LinkedList::new();
+            let theirs = n.push_back_handle(2);
+            m.merge(&mut n);
+            assert_eq!(m.get_by_handle(theirs), None);
+            assert_eq!(n.get_by_handle(theirs), None);
+            if let Some(ours) = ours {
+                assert_eq!(m.get_by_handle(ours), Some(&1));
+            }
+            let again = m.push_back_handle(3);
+            assert_eq!(m.get_by_handle(again), Some(&3));
+        }
+    }
 
-        let mut m: LinkedList<u32> = LinkedList::new();
-        m.extend([1, 8, 2, 3, 4, 5, 6]);
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        let mut p: LinkedList<u32> = LinkedList::new();
-        p.extend([100, 101, 102, 103]);
-        let mut q: LinkedList<u32> = LinkedList::new();
-        q.extend([200, 201, 202, 203]);
-        cursor.splice_after(p);
-        cursor.splice_before(q);
+    #[test]
+    fn test_merge_panic_safe() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        for panic_at in 0..8 {
+            let mut m: LinkedList<i32> = list_from(&[1, 3, 5, 7]);
+            let mut n: LinkedList<i32> = list_from(&[2, 4, 6]);
+            let mut count = 0;
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                m.merge_by(&mut n, |a, b| {
+                    count += 1;
+                    if count > panic_at {
+                        panic!("comparator gave up");
+                    }
+                    a.cmp(b)
+                })
+            }));
+            check_links(&m);
+            check_links(&n);
+            assert!(n.is_empty());
+            let mut v: Vec<_> = m.iter().cloned().collect();
+            v.sort();
+            assert_eq!(v, &[1, 2, 3, 4, 5, 6, 7]);
+            if result.is_ok() {
+                assert!(m.is_sorted());
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_all() {
+        for k in 0..7 {
+            let lists: Vec<LinkedList<i32>> = (0..k)
+                .map(|i| (0..5).map(|x| x * k + i).collect())
+                .collect();
+            let m = LinkedList::merge_all(lists);
+            check_links(&m);
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                (0..5 * k).collect::<Vec<_>>()
+            );
+        }
+
+        // ties go to the earlier list, with an odd number of lists too
+        let lists = (0..5).map(|i| list_from(&[(0, i), (1, i)]));
+        let m = LinkedList::merge_all_by(lists, |a, b| a.0.cmp(&b.0));
         check_links(&m);
         assert_eq!(
-            m.iter().cloned().collect::<Vec<_>>(),
-            &[200, 201, 202, 203, 1, 100, 101, 102, 103, 8, 2, 3, 4, 5, 6]
+            m.iter().map(|p| p.1).collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 4, 0, 1, 2, 3, 4]
         );
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        cursor.move_prev();
-        let tmp = cursor.split_before();
-        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[]);
-        m = tmp;
-        let mut cursor = m.cursor_mut();
-        cursor.move_next();
-        cursor.move_next();
-        cursor.move_next();
-        cursor.move_next();
-        cursor.move_next();
-        cursor.move_next();
-        cursor.move_next();
-        let tmp = cursor.split_after();
+    }
+
+    #[test]
+    fn test_merge_iter() {
+        let a: LinkedList<i32> = list_from(&[1, 4, 7]);
+        let b: LinkedList<i32> = list_from(&[2, 5]);
+        let c: LinkedList<i32> = LinkedList::new();
+        let d: LinkedList<i32> = list_from(&[0, 3, 6, 9]);
+        let iter = MergeIter::new([a.iter(), b.iter(), c.iter(), d.iter()]);
+        assert_eq!(iter.size_hint(), (9, Some(9)));
         assert_eq!(
-            tmp.into_iter().collect::<Vec<_>>(),
-            &[102, 103, 8, 2, 3, 4, 5, 6]
+            iter.cloned().collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 4, 5, 6, 7, 9]
         );
-        check_links(&m);
+        // the lists are untouched
+        assert_eq!(a.len(), 3);
+
+        let a: LinkedList<(i32, char)> = list_from(&[(1, 'a'), (3, 'a')]);
+        let b: LinkedList<(i32, char)> = list_from(&[(1, 'b'), (2, 'b')]);
+        let merged: String = MergeIter::new_by([a.iter(), b.iter()], |x, y| x.0.cmp(&y.0))
+            .map(|p| p.1)
+            .collect();
+        assert_eq!(merged, "abba");
+
+        let none: Vec<Iter<'_, i32>> = Vec::new();
+        assert_eq!(MergeIter::new(none).next(), None);
+    }
+
+    #[test]
+    fn test_set_ops_borrowed() {
+        let a: LinkedList<i32> = list_from(&[1, 2, 4, 6, 8]);
+        let b: LinkedList<i32> = list_from(&[2, 3, 4, 9]);
+        let e: LinkedList<i32> = LinkedList::new();
+
         assert_eq!(
-            m.iter().cloned().collect::<Vec<_>>(),
-            &[200, 201, 202, 203, 1, 100, 101]
+            a.union(&b).cloned().collect::<Vec<_>>(),
+            &[1, 2, 3, 4, 6, 8, 9]
+        );
+        assert_eq!(a.intersection(&b).cloned().collect::<Vec<_>>(), &[2, 4]);
+        assert_eq!(a.difference(&b).cloned().collect::<Vec<_>>(), &[1, 6, 8]);
+        assert_eq!(b.difference(&a).cloned().collect::<Vec<_>>(), &[3, 9]);
+        assert_eq!(
+            a.symmetric_difference(&b).cloned().collect::<Vec<_>>(),
+            &[1, 3, 6, 8, 9]
+        );
+
+        assert_eq!(a.union(&e).count(), 5);
+        assert_eq!(e.union(&a).count(), 5);
+        assert_eq!(a.intersection(&e).count(), 0);
+        assert_eq!(a.difference(&e).count(), 5);
+        assert_eq!(e.difference(&a).count(), 0);
+        assert_eq!(e.symmetric_difference(&a).count(), 5);
+
+        // same answers as BTreeSet
+        use std::collections::BTreeSet;
+        let x: BTreeSet<i32> = a.iter().cloned().collect();
+        let y: BTreeSet<i32> = b.iter().cloned().collect();
+        assert!(a.union(&b).eq(x.union(&y)));
+        assert!(a.intersection(&b).eq(x.intersection(&y)));
+        assert!(a.difference(&b).eq(x.difference(&y)));
+        assert!(a.symmetric_difference(&b).eq(x.symmetric_difference(&y)));
+    }
+
+    #[test]
+    fn test_set_ops_owned() {
+        let a: LinkedList<i32> = list_from(&[1, 2, 4, 6, 8]);
+        let b: LinkedList<i32> = list_from(&[2, 3, 4, 9]);
+
+        let u = a.clone().into_union(b.clone());
+        check_links(&u);
+        assert_eq!(
+            u.iter().cloned().collect::<Vec<_>>(),
+            &[1, 2, 3, 4, 6, 8, 9]
         );
+        let i = a.clone().into_intersection(b.clone());
+        check_links(&i);
+        assert_eq!(i.iter().cloned().collect::<Vec<_>>(), &[2, 4]);
+        let d = a.clone().into_difference(b.clone());
+        check_links(&d);
+        assert_eq!(d.iter().cloned().collect::<Vec<_>>(), &[1, 6, 8]);
+        let s = a.clone().into_symmetric_difference(b.clone());
+        check_links(&s);
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(), &[1, 3, 6, 8, 9]);
+
+        let e = LinkedList::new();
+        let u = e.into_union(a.clone());
+        check_links(&u);
+        assert_eq!(u, a);
+        let i = a.clone().into_intersection(LinkedList::new());
+        assert!(i.is_empty());
+        check_links(&i);
+
+        // strings so a leak or double free would show up under miri
+        let a: LinkedList<String> = ["a", "c", "e"].iter().map(|s| s.to_string()).collect();
+        let b: LinkedList<String> = ["b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        let s = a.into_symmetric_difference(b);
+        check_links(&s);
+        assert_eq!(s.iter().cloned().collect::<String>(), "abde");
     }
 
     fn check_links<T: Eq + std::fmt::Debug>(list: &LinkedList<T>) {
@@ -1110,5 +4132,21 @@ mod test {
         let re_reved: Vec<_> = from_back.into_iter().rev().collect();
 
         assert_eq!(from_front, re_reved);
+        assert_eq!(from_front.len(), list.len());
+
+        // equal elements could hide a bad prev pointer, so walk the actual nodes too
+        unsafe {
+            let mut prev = None;
+            let mut cur = list.front;
+            let mut count = 0;
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                prev = Some(node);
+                cur = (*node.as_ptr()).next;
+                count += 1;
+            }
+            assert_eq!(list.back, prev);
+            assert_eq!(count, list.len());
+        }
     }
 }
@@ -0,0 +1,1347 @@
+// a sorted map out of linked lists
+// a skip list is a stack of sorted linked lists. the bottom one (level 0) has every entry, and
+// every list above it has roughly half the entries of the one below. every node gets a random
+// height when it's inserted, and is in the lists of every level below that. to find a key we start
+// in the top list, which is short, walk along until the next node would be too far, drop a level,
+// and keep going. every level only takes a couple of steps, so finding is O(log n) expected, with
+// no rebalancing ever.
+//
+// a node's links are its tower: next[level] is the next node at that level. the map itself has
+// the tower for the start of every list (head). level 0 also has prev links, so we can walk
+// backwards, which gets us DoubleEndedIterator, cursors that go both ways, and last() for free.
+use crate::rng::{self, new_seed};
+use std::{
+    borrow::Borrow,
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
+
+// with p = 1/2 this is plenty for anything that fits in memory
+const MAX_HEIGHT: usize = 32;
+
+pub struct SkipMap<K, V> {
+    // head[level] is the first node of the list at that level, head.len() is the tallest tower
+    head: Vec<Link<K, V>>,
+    back: Link<K, V>,
+    len: usize,
+    rng: u64,
+    _boo: PhantomData<(K, V)>,
+}
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: Link<K, V>,
+    next: Box<[Link<K, V>]>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    pub fn new() -> Self {
+        SkipMap {
+            head: Vec::new(),
+            back: None,
+            len: 0,
+            rng: new_seed(),
+            _boo: PhantomData,
+        }
+    }
+
+    // every extra level is a coin flip, which is what the trailing ones of a random number are
+    fn random_height(&mut self) -> usize {
+        let random = rng::next(&mut self.rng);
+        (random.trailing_ones() as usize + 1).min(MAX_HEIGHT)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // the node after pred at this level, where a pred of None means the head (which is empty
+    // for an empty map)
+    unsafe fn next_at(&self, pred: Link<K, V>, level: usize) -> Link<K, V> {
+        unsafe {
+            match pred {
+                Some(pred) => (*pred.as_ptr()).next[level],
+                None => self.head.get(level).copied().flatten(),
+            }
+        }
+    }
+
+    unsafe fn set_next_at(&mut self, pred: Link<K, V>, level: usize, link: Link<K, V>) {
+        unsafe {
+            match pred {
+                Some(pred) => (*pred.as_ptr()).next[level] = link,
+                None => self.head[level] = link,
+            }
+        }
+    }
+
+    // takes node out of every level it's in. preds[level] has to be the node right before it at
+    // every one of those levels
+    unsafe fn unlink(&mut self, node: NonNull<Node<K, V>>, preds: &[Link<K, V>]) -> (K, V) {
+        unsafe {
+            let boxed_node = Box::from_raw(node.as_ptr());
+            for (level, &next) in boxed_node.next.iter().enumerate() {
+                self.set_next_at(preds[level], level, next);
+            }
+            match boxed_node.next[0] {
+                Some(next) => (*next.as_ptr()).prev = boxed_node.prev,
+                None => self.back = boxed_node.prev,
+            }
+            // levels nobody reaches any more
+            while self.head.last() == Some(&None) {
+                self.head.pop();
+            }
+            self.len -= 1;
+            (boxed_node.key, boxed_node.value)
+        }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        unsafe {
+            self.head
+                .first()
+                .copied()
+                .flatten()
+                .map(|node| (&(*node.as_ptr()).key, &(*node.as_ptr()).value))
+        }
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        unsafe {
+            self.back
+                .map(|node| (&(*node.as_ptr()).key, &(*node.as_ptr()).value))
+        }
+    }
+
+    // the first node is first in every list it's in, so the head is its pred everywhere
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let first = self.head.first().copied().flatten()?;
+        unsafe { Some(self.unlink(first, &[None; MAX_HEIGHT])) }
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    // for every level, the last node whose key is still before what we're looking for, None
+    // meaning the head
+    fn search(&self, before: impl Fn(&K) -> bool) -> [Link<K, V>; MAX_HEIGHT] {
+        let mut preds = [None; MAX_HEIGHT];
+        let mut pred = None;
+        unsafe {
+            for level in (0..self.head.len()).rev() {
+                while let Some(next) = self.next_at(pred, level)
+                    && before(&(*next.as_ptr()).key)
+                {
+                    pred = Some(next);
+                }
+                preds[level] = pred;
+            }
+        }
+        preds
+    }
+
+    // the first node with a key >= key
+    fn lower_bound<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let preds = self.search(|k| k.borrow() < key);
+        unsafe { self.next_at(preds[0], 0) }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.lower_bound(key)
+            .filter(|node| unsafe { (*node.as_ptr()).key.borrow() == key })
+    }
+
+    // like a map, inserting a key that's already there swaps the value and keeps the old key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.search(|k| *k < key);
+        unsafe {
+            if let Some(next) = self.next_at(preds[0], 0)
+                && (*next.as_ptr()).key == key
+            {
+                return Some(std::mem::replace(&mut (*next.as_ptr()).value, value));
+            }
+
+            let height = self.random_height();
+            while self.head.len() < height {
+                self.head.push(None);
+            }
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                key,
+                value,
+                prev: preds[0],
+                next: vec![None; height].into_boxed_slice(),
+            })));
+            for (level, &pred) in preds.iter().enumerate().take(height) {
+                (*new.as_ptr()).next[level] = self.next_at(pred, level);
+                self.set_next_at(pred, level, Some(new));
+            }
+            match (*new.as_ptr()).next[0] {
+                Some(next) => (*next.as_ptr()).prev = Some(new),
+                None => self.back = Some(new),
+            }
+            self.len += 1;
+        }
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe { self.find(key).map(|node| &(*node.as_ptr()).value) }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe { self.find(key).map(|node| &mut (*node.as_ptr()).value) }
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        unsafe {
+            self.find(key)
+                .map(|node| (&(*node.as_ptr()).key, &(*node.as_ptr()).value))
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let preds = self.search(|k| k.borrow() < key);
+        unsafe {
+            let node = self
+                .next_at(preds[0], 0)
+                .filter(|node| (*node.as_ptr()).key.borrow() == key)?;
+            Some(self.unlink(node, &preds))
+        }
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let back = self.back?;
+        unsafe {
+            let preds = self.search(|k| k < &(*back.as_ptr()).key);
+            Some(self.unlink(back, &preds))
+        }
+    }
+
+    // the entries with keys in range, found with two searches: one for the first node in the
+    // range and one for the last
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        unsafe {
+            let front = match range.start_bound() {
+                Bound::Included(start) => self.lower_bound(start),
+                Bound::Excluded(start) => {
+                    let preds = self.search(|k| k.borrow() <= start);
+                    self.next_at(preds[0], 0)
+                }
+                Bound::Unbounded => self.head.first().copied().flatten(),
+            };
+            let back = match range.end_bound() {
+                Bound::Included(end) => self.search(|k| k.borrow() <= end)[0],
+                Bound::Excluded(end) => self.search(|k| k.borrow() < end)[0],
+                Bound::Unbounded => self.back,
+            };
+            // the range can be empty (or backwards), then back comes before front
+            match (front, back) {
+                (Some(f), Some(b)) if (*f.as_ptr()).key <= (*b.as_ptr()).key => Range {
+                    front,
+                    back,
+                    _boo: PhantomData,
+                },
+                _ => Range {
+                    front: None,
+                    back: None,
+                    _boo: PhantomData,
+                },
+            }
+        }
+    }
+}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipMap<K, V> {
+    fn clone(&self) -> Self {
+        self.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for SkipMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for SkipMap<K, V> {}
+
+// level 0 has everything, so walking it frees every node exactly once
+impl<K, V> Drop for SkipMap<K, V> {
+    fn drop(&mut self) {
+        let mut cur = self.head.first().copied().flatten();
+        while let Some(node) = cur {
+            unsafe {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                cur = boxed_node.next[0];
+            }
+        }
+    }
+}
+
+// iterators only ever walk level 0
+pub struct Iter<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    len: usize,
+    _boo: PhantomData<&'a (K, V)>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            front: self.head.first().copied().flatten(),
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipMap<K, V> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).next[0];
+                (&(*node.as_ptr()).key, &(*node.as_ptr()).value)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).prev;
+                (&(*node.as_ptr()).key, &(*node.as_ptr()).value)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// keys stay shared, changing them could break the order
+pub struct IterMut<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    len: usize,
+    _boo: PhantomData<(&'a K, &'a mut V)>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            front: self.head.first().copied().flatten(),
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipMap<K, V> {
+    type IntoIter = IterMut<'a, K, V>;
+    type Item = (&'a K, &'a mut V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).next[0];
+                (&(*node.as_ptr()).key, &mut (*node.as_ptr()).value)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).prev;
+                (&(*node.as_ptr()).key, &mut (*node.as_ptr()).value)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// takes the nodes away from the map up front. from then on only level 0 matters, so taking a
+// node off either end is just unhooking it from its neighbour
+pub struct IntoIter<K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    len: usize,
+    _boo: PhantomData<(K, V)>,
+}
+
+impl<K, V> IntoIterator for SkipMap<K, V> {
+    type IntoIter = IntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let iter = IntoIter {
+            front: self.head.first().copied().flatten(),
+            back: self.back,
+            len: self.len,
+            _boo: PhantomData,
+        };
+        self.head.clear();
+        self.back = None;
+        self.len = 0;
+        iter
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                let boxed_node = Box::from_raw(node.as_ptr());
+                self.front = boxed_node.next[0];
+                (boxed_node.key, boxed_node.value)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                let boxed_node = Box::from_raw(node.as_ptr());
+                self.back = boxed_node.prev;
+                (boxed_node.key, boxed_node.value)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
+
+// front and back are both in the range (or both None), and we stop once they meet
+pub struct Range<'a, K, V> {
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _boo: PhantomData<&'a (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.map(|node| unsafe {
+            if self.front == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.front = (*node.as_ptr()).next[0];
+            }
+            (&(*node.as_ptr()).key, &(*node.as_ptr()).value)
+        })
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.map(|node| unsafe {
+            if self.front == self.back {
+                self.front = None;
+                self.back = None;
+            } else {
+                self.back = (*node.as_ptr()).prev;
+            }
+            (&(*node.as_ptr()).key, &(*node.as_ptr()).value)
+        })
+    }
+}
+
+// cursors walk level 0, with the ghost between the back and the front like sixth
+pub struct Cursor<'a, K, V> {
+    cur: Link<K, V>,
+    map: &'a SkipMap<K, V>,
+}
+
+impl<'a, K, V> Clone for Cursor<'a, K, V> {
+    fn clone(&self) -> Self {
+        Cursor {
+            cur: self.cur,
+            map: self.map,
+        }
+    }
+}
+
+pub struct CursorMut<'a, K, V> {
+    cur: Link<K, V>,
+    map: &'a mut SkipMap<K, V>,
+}
+
+impl<K, V> SkipMap<K, V> {
+    pub fn cursor_front(&self) -> Cursor<'_, K, V> {
+        Cursor {
+            cur: self.head.first().copied().flatten(),
+            map: self,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, K, V> {
+        Cursor {
+            cur: self.back,
+            map: self,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, K, V> {
+        CursorMut {
+            cur: self.head.first().copied().flatten(),
+            map: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, K, V> {
+        CursorMut {
+            cur: self.back,
+            map: self,
+        }
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    // on the first entry with a key >= key, or the ghost if there isn't one
+    pub fn cursor_at<Q>(&self, key: &Q) -> Cursor<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        Cursor {
+            cur: self.lower_bound(key),
+            map: self,
+        }
+    }
+
+    pub fn cursor_at_mut<Q>(&mut self, key: &Q) -> CursorMut<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        CursorMut {
+            cur: self.lower_bound(key),
+            map: self,
+        }
+    }
+}
+
+impl<'a, K, V> Cursor<'a, K, V> {
+    pub fn move_next(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).next[0] },
+            None => self.map.head.first().copied().flatten(),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).prev },
+            None => self.map.back,
+        };
+    }
+
+    pub fn key(&self) -> Option<&'a K> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).key) }
+    }
+
+    pub fn value(&self) -> Option<&'a V> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).value) }
+    }
+
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let mut next = self.clone();
+        next.move_next();
+        next.key().zip(next.value())
+    }
+
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let mut prev = self.clone();
+        prev.move_prev();
+        prev.key().zip(prev.value())
+    }
+}
+
+impl<'a, K, V> CursorMut<'a, K, V> {
+    pub fn as_cursor(&self) -> Cursor<'_, K, V> {
+        Cursor {
+            cur: self.cur,
+            map: self.map,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).next[0] },
+            None => self.map.head.first().copied().flatten(),
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        self.cur = match self.cur {
+            Some(cur) => unsafe { (*cur.as_ptr()).prev },
+            None => self.map.back,
+        };
+    }
+
+    pub fn key(&self) -> Option<&K> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).key) }
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).value) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => (*cur.as_ptr()).next[0],
+                None => self.map.head.first().copied().flatten(),
+            };
+            next.map(|node| (&(*node.as_ptr()).key, &mut (*node.as_ptr()).value))
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => (*cur.as_ptr()).prev,
+                None => self.map.back,
+            };
+            prev.map(|node| (&(*node.as_ptr()).key, &mut (*node.as_ptr()).value))
+        }
+    }
+}
+
+impl<'a, K: Ord, V> CursorMut<'a, K, V> {
+    // takes the current entry out and moves on to the next one. we only have prev links on
+    // level 0, so finding the preds on the other levels is a search, O(log n)
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        let cur = self.cur?;
+        unsafe {
+            let preds = self.map.search(|k| k < &(*cur.as_ptr()).key);
+            self.cur = (*cur.as_ptr()).next[0];
+            Some(self.map.unlink(cur, &preds))
+        }
+    }
+}
+
+// a set is a map without values
+pub struct SkipSet<K> {
+    map: SkipMap<K, ()>,
+}
+
+impl<K> SkipSet<K> {
+    pub fn new() -> Self {
+        SkipSet {
+            map: SkipMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn first(&self) -> Option<&K> {
+        self.map.first_key_value().map(|(k, _)| k)
+    }
+
+    pub fn last(&self) -> Option<&K> {
+        self.map.last_key_value().map(|(k, _)| k)
+    }
+
+    pub fn pop_first(&mut self) -> Option<K> {
+        self.map.pop_first().map(|(k, _)| k)
+    }
+
+    pub fn iter(&self) -> SetIter<'_, K> {
+        SetIter {
+            iter: self.map.iter(),
+        }
+    }
+}
+
+impl<K: Ord> SkipSet<K> {
+    // true if it wasn't there yet. like std's sets, an equal key that's already there stays
+    pub fn insert(&mut self, key: K) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.get_key_value(key).map(|(k, _)| k)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn take<Q>(&mut self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.remove_entry(key).map(|(k, _)| k)
+    }
+
+    pub fn pop_last(&mut self) -> Option<K> {
+        self.map.pop_last().map(|(k, _)| k)
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> SetRange<'_, K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        SetRange {
+            range: self.map.range(range),
+        }
+    }
+}
+
+impl<K> Default for SkipSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone> Clone for SkipSet<K> {
+    fn clone(&self) -> Self {
+        SkipSet {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K: Ord> Extend<K> for SkipSet<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl<K: Ord> FromIterator<K> for SkipSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K: Debug> Debug for SkipSet<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self).finish()
+    }
+}
+
+impl<K: PartialEq> PartialEq for SkipSet<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K: Eq> Eq for SkipSet<K> {}
+
+pub struct SetIter<'a, K> {
+    iter: Iter<'a, K, ()>,
+}
+
+impl<'a, K> IntoIterator for &'a SkipSet<K> {
+    type IntoIter = SetIter<'a, K>;
+    type Item = &'a K;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K> Iterator for SetIter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for SetIter<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K> ExactSizeIterator for SetIter<'a, K> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+pub struct SetIntoIter<K> {
+    iter: IntoIter<K, ()>,
+}
+
+impl<K> IntoIterator for SkipSet<K> {
+    type IntoIter = SetIntoIter<K>;
+    type Item = K;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SetIntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+impl<K> Iterator for SetIntoIter<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for SetIntoIter<K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K> ExactSizeIterator for SetIntoIter<K> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+pub struct SetRange<'a, K> {
+    range: Range<'a, K, ()>,
+}
+
+impl<'a, K> Iterator for SetRange<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for SetRange<'a, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|(k, _)| k)
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<K: Send, V: Send> Send for SkipMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipMap<K, V> {}
+
+unsafe impl<K: Send, V: Send> Send for IntoIter<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for IntoIter<K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Iter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Send> Send for IterMut<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for IterMut<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Range<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Range<'a, K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Cursor<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Cursor<'a, K, V> {}
+
+unsafe impl<'a, K: Send, V: Send> Send for CursorMut<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for CursorMut<'a, K, V> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<SkipMap<i32, i32>>();
+    is_sync::<SkipMap<i32, i32>>();
+
+    is_send::<SkipSet<i32>>();
+    is_sync::<SkipSet<i32>>();
+
+    is_send::<IntoIter<i32, i32>>();
+    is_sync::<IntoIter<i32, i32>>();
+
+    is_send::<Iter<i32, i32>>();
+    is_sync::<Iter<i32, i32>>();
+
+    is_send::<IterMut<i32, i32>>();
+    is_sync::<IterMut<i32, i32>>();
+
+    is_send::<Range<i32, i32>>();
+    is_sync::<Range<i32, i32>>();
+
+    is_send::<Cursor<i32, i32>>();
+    is_sync::<Cursor<i32, i32>>();
+
+    fn skip_map_covariant<'a, T>(x: SkipMap<&'static T, &'static T>) -> SkipMap<&'a T, &'a T> {
+        x
+    }
+    fn skip_set_covariant<'a, T>(x: SkipSet<&'static T>) -> SkipSet<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T, &'static T>) -> Iter<'i, &'a T, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SkipMap, SkipSet};
+    use crate::rng::TestRng;
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_basic() {
+        let mut m = SkipMap::new();
+        assert_eq!(m.get(&1), None);
+        assert_eq!(m.first_key_value(), None);
+        assert_eq!(m.pop_first(), None);
+        assert_eq!(m.pop_last(), None);
+
+        assert_eq!(m.insert(3, "c"), None);
+        assert_eq!(m.insert(1, "a"), None);
+        assert_eq!(m.insert(2, "b"), None);
+        assert_eq!(m.insert(2, "B"), Some("b"));
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.get(&2), Some(&"B"));
+        assert!(m.contains_key(&3));
+        assert!(!m.contains_key(&4));
+        *m.get_mut(&3).unwrap() = "C";
+        assert_eq!(m.first_key_value(), Some((&1, &"a")));
+        assert_eq!(m.last_key_value(), Some((&3, &"C")));
+        check_towers(&m);
+
+        assert_eq!(m.remove(&2), Some("B"));
+        assert_eq!(m.remove(&2), None);
+        assert_eq!(m.pop_last(), Some((3, "C")));
+        assert_eq!(m.pop_first(), Some((1, "a")));
+        assert!(m.is_empty());
+        check_towers(&m);
+        assert!(m.head.is_empty());
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut m: SkipMap<String, usize> = SkipMap::new();
+        for word in ["pear", "apple", "fig"] {
+            m.insert(word.to_string(), word.len());
+        }
+        assert_eq!(m.get("fig"), Some(&3));
+        assert_eq!(m.get_key_value("apple"), Some((&"apple".to_string(), &5)));
+        assert_eq!(
+            m.range::<str, _>((Bound::Included("b"), Bound::Excluded("p")))
+                .map(|(k, _)| k.as_str())
+                .collect::<Vec<_>>(),
+            &["fig"]
+        );
+        assert_eq!(m.remove_entry("pear"), Some(("pear".to_string(), 4)));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m: SkipMap<u32, u32> = (0..10).rev().map(|i| (i, i * 10)).collect();
+        assert_eq!(
+            m.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            m.iter().rev().map(|(_, v)| *v).collect::<Vec<_>>(),
+            (0..10).rev().map(|i| i * 10).collect::<Vec<_>>()
+        );
+        for (k, v) in m.iter_mut() {
+            *v += k;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.len(), 10);
+        assert_eq!(it.next_back(), Some((&9, &mut 99)));
+        assert_eq!(it.next(), Some((&0, &mut 0)));
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next(), Some((0, 0)));
+        assert_eq!(it.next_back(), Some((9, 99)));
+        assert_eq!(it.len(), 8);
+        // dropping the rest frees them
+        drop(it);
+
+        assert_eq!(
+            format!("{:?}", m.range(..2).collect::<Vec<_>>()),
+            "[(0, 0), (1, 11)]"
+        );
+        assert_eq!(format!("{:?}", SkipMap::from_iter([(1, 2)])), "{1: 2}");
+        assert_eq!(m, m.clone());
+    }
+
+    #[test]
+    fn test_range() {
+        let m: SkipMap<i32, ()> = (0..20).map(|i| (i * 2, ())).collect();
+        let b: BTreeMap<i32, ()> = (0..20).map(|i| (i * 2, ())).collect();
+        let bounds = |i: i32| [Bound::Included(i), Bound::Excluded(i), Bound::Unbounded];
+        // every pair of bounds is a lot of ranges for miri
+        let step = if cfg!(miri) { 5 } else { 1 };
+        for lo in (-2..42).step_by(step) {
+            for hi in (-2..42).step_by(step) {
+                for start in bounds(lo) {
+                    for end in bounds(hi) {
+                        // btree panics on backwards ranges, we are just empty
+                        let backwards = match (start, end) {
+                            (Bound::Included(s), Bound::Included(e)) => s > e,
+                            (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+                            (Bound::Included(s) | Bound::Excluded(s), Bound::Excluded(e))
+                            | (Bound::Excluded(s), Bound::Included(e)) => s >= e,
+                        };
+                        let ours = m.range((start, end)).map(|(k, _)| *k);
+                        if backwards {
+                            assert_eq!(ours.count(), 0);
+                            continue;
+                        }
+                        let theirs = b.range((start, end)).map(|(k, _)| *k);
+                        assert!(ours.eq(theirs), "{start:?} {end:?}");
+                        let ours = m.range((start, end)).rev().map(|(k, _)| *k);
+                        let theirs = b.range((start, end)).rev().map(|(k, _)| *k);
+                        assert!(ours.eq(theirs), "{start:?} {end:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_against_btree() {
+        let mut rng = TestRng(0x2545F4914F6CDD1D);
+        let mut m = SkipMap::new();
+        let mut b = BTreeMap::new();
+        for step in 0..5000 {
+            let key = rng.below(500);
+            match rng.below(6) {
+                0..=2 => assert_eq!(m.insert(key, step), b.insert(key, step)),
+                3 => assert_eq!(m.remove(&key), b.remove(&key)),
+                4 => assert_eq!(m.get(&key), b.get(&key)),
+                _ => {
+                    if rng.below(2) == 0 {
+                        assert_eq!(m.pop_first(), b.pop_first());
+                    } else {
+                        assert_eq!(m.pop_last(), b.pop_last());
+                    }
+                }
+            }
+            assert_eq!(m.len(), b.len());
+        }
+        check_towers(&m);
+        assert!(m.iter().eq(b.iter()));
+    }
+
+    #[test]
+    fn test_cursors() {
+        let mut m: SkipMap<u32, u32> = (1..=6).map(|i| (i * 10, i)).collect();
+        let mut cursor = m.cursor_front();
+        assert_eq!(cursor.key(), Some(&10));
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.peek_next(), Some((&10, &1)));
+        assert_eq!(cursor.peek_prev(), Some((&60, &6)));
+        cursor.move_prev();
+        assert_eq!(cursor.value(), Some(&6));
+        assert_eq!(m.cursor_back().key(), Some(&60));
+
+        // on or after
+        assert_eq!(m.cursor_at(&30).key(), Some(&30));
+        assert_eq!(m.cursor_at(&31).key(), Some(&40));
+        assert_eq!(m.cursor_at(&61).key(), None);
+
+        let mut cursor = m.cursor_at_mut(&25);
+        assert_eq!(cursor.key(), Some(&30));
+        *cursor.value_mut().unwrap() = 300;
+        assert_eq!(cursor.peek_prev(), Some((&20, &mut 2)));
+        assert_eq!(cursor.remove_current(), Some((30, 300)));
+        assert_eq!(cursor.key(), Some(&40));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some((&20, &2)));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some((60, 6)));
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some((10, 1)));
+        check_towers(&m);
+        assert_eq!(m.iter().map(|(k, _)| *k).collect::<Vec<_>>(), &[20, 40, 50]);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut s: SkipSet<u32> = [5, 1, 3].into_iter().collect();
+        assert!(s.insert(4));
+        assert!(!s.insert(4));
+        assert!(s.contains(&3));
+        assert_eq!(s.get(&5), Some(&5));
+        assert_eq!(s.first(), Some(&1));
+        assert_eq!(s.last(), Some(&5));
+        assert_eq!(s.range(2..5).copied().collect::<Vec<_>>(), &[3, 4]);
+        assert_eq!(s.range(2..5).rev().copied().collect::<Vec<_>>(), &[4, 3]);
+        assert!(s.remove(&3));
+        assert!(!s.remove(&3));
+        assert_eq!(s.take(&4), Some(4));
+        assert_eq!(format!("{s:?}"), "{1, 5}");
+        assert_eq!(s, s.clone());
+        assert_eq!(s.pop_last(), Some(5));
+        assert_eq!(s.pop_first(), Some(1));
+        assert!(s.is_empty());
+
+        let s: SkipSet<u32> = (0..5).collect();
+        assert_eq!(
+            s.iter().rev().copied().collect::<Vec<_>>(),
+            &[4, 3, 2, 1, 0]
+        );
+        assert_eq!(s.into_iter().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_drops() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+        let mut m: SkipMap<u32, Rc<()>> = (0..50).map(|i| (i, rc.clone())).collect();
+        m.remove(&10);
+        m.insert(20, rc.clone());
+        assert_eq!(Rc::strong_count(&rc), 50);
+        let mut it = m.into_iter();
+        it.next();
+        it.next_back();
+        assert_eq!(Rc::strong_count(&rc), 48);
+        drop(it);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    // every level is sorted and only has nodes from the level below, level 0's prev links agree
+    // with its next links, and back is the last node
+    fn check_towers<K: Ord, V>(m: &SkipMap<K, V>) {
+        unsafe {
+            let mut count = 0;
+            let mut prev = None;
+            let mut cur = m.head.first().copied().flatten();
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                if let Some(prev) = prev {
+                    assert!((*prev.as_ptr()).key < (*node.as_ptr()).key);
+                }
+                count += 1;
+                prev = cur;
+                cur = (*node.as_ptr()).next[0];
+            }
+            assert_eq!(m.back, prev);
+            assert_eq!(count, m.len);
+
+            for level in 1..m.head.len() {
+                assert!(m.head[level].is_some());
+                let mut below = m.head[level - 1];
+                let mut cur = m.head[level];
+                while let Some(node) = cur {
+                    let tower = &(*node.as_ptr()).next;
+                    assert!(tower.len() > level);
+                    // it has to be further along the level below
+                    while below != Some(node) {
+                        below = (*below.unwrap().as_ptr()).next[level - 1];
+                    }
+                    cur = (*node.as_ptr()).next[level];
+                }
+            }
+        }
+    }
+}
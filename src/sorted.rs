@@ -0,0 +1,383 @@
+// a sixth::LinkedList that keeps itself sorted
+// the smallest element is always the front and the biggest the back, so min, max, pop_min and
+// pop_max are all O(1), which makes this a fine little priority queue when there aren't many
+// elements. inserting has to walk to the right spot, but it walks from the back, so elements that
+// come in in order (the usual case for timers and such) go straight on the end.
+//
+// the order comes from a Comparator. by default that's the element's own Ord (NaturalOrder), but
+// any Fn(&T, &T) -> Ordering works too, for ordering by a field or backwards
+use crate::sixth::{self, LinkedList};
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    iter::{Skip, Take},
+    ops::{Bound, RangeBounds},
+};
+
+pub trait Comparator<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+// T's own Ord
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NaturalOrder;
+
+impl<T: Ord + ?Sized> Comparator<T> for NaturalOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Comparator<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+pub struct SortedList<T, C = NaturalOrder> {
+    list: LinkedList<T>,
+    cmp: C,
+}
+
+impl<T: Ord> SortedList<T> {
+    pub fn new() -> Self {
+        Self::with_comparator(NaturalOrder)
+    }
+}
+
+impl<T, C: Comparator<T>> SortedList<T, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        SortedList {
+            list: LinkedList::new(),
+            cmp,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+
+    // goes after everything it's equal to, so equal elements come out in the order they went in.
+    // we walk from the back to the last element that's <= elem and go right after it (or walk off
+    // the front and go on the front)
+    pub fn insert(&mut self, elem: T) {
+        let mut cursor = self.list.cursor_back_mut();
+        while let Some(cur) = cursor.current() {
+            if self.cmp.compare(cur, &elem) != Ordering::Greater {
+                break;
+            }
+            cursor.move_prev();
+        }
+        // spliced like LinkedList::insert does, so we don't pay for a NodeHandle nobody asked for
+        let mut input = LinkedList::new();
+        input.push_back(elem);
+        cursor.splice_after(input);
+    }
+
+    // the first element that compares equal to value
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let mut cursor = self.list.cursor_front_mut();
+        while let Some(cur) = cursor.current() {
+            match self.cmp.compare(cur, value) {
+                Ordering::Less => cursor.move_next(),
+                Ordering::Equal => return cursor.remove_current(),
+                Ordering::Greater => break,
+            }
+        }
+        None
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.list
+            .iter()
+            .map(|x| self.cmp.compare(x, value))
+            .find(|&ord| ord != Ordering::Less)
+            == Some(Ordering::Equal)
+    }
+
+    // counts what's below the range from the front and what's above it from the back, the range
+    // is whatever is left in the middle. a backwards range is just empty
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        let below = match range.start_bound() {
+            Bound::Included(start) => self.count_front(|x| self.cmp.compare(x, start).is_lt()),
+            Bound::Excluded(start) => self.count_front(|x| self.cmp.compare(x, start).is_le()),
+            Bound::Unbounded => 0,
+        };
+        let above = match range.end_bound() {
+            Bound::Included(end) => self.count_back(|x| self.cmp.compare(x, end).is_gt()),
+            Bound::Excluded(end) => self.count_back(|x| self.cmp.compare(x, end).is_ge()),
+            Bound::Unbounded => 0,
+        };
+        let len = self.len().saturating_sub(below + above);
+        Range {
+            iter: self.list.iter().skip(below).take(len),
+        }
+    }
+
+    fn count_front(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.list.iter().take_while(|x| pred(x)).count()
+    }
+
+    fn count_back(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.list.iter().rev().take_while(|x| pred(x)).count()
+    }
+
+    // everything in other comes over, in one pass over both lists. on ties ours come first, and
+    // it's our comparator that decides
+    pub fn merge(&mut self, other: &mut Self) {
+        let cmp = &self.cmp;
+        self.list
+            .merge_by(&mut other.list, |a, b| cmp.compare(a, b));
+    }
+
+    pub fn iter(&self) -> sixth::Iter<'_, T> {
+        self.list.iter()
+    }
+
+    // already sorted, so this is free
+    pub fn into_list(self) -> LinkedList<T> {
+        self.list
+    }
+}
+
+impl<T, C: Comparator<T>> SortedList<T, C> {
+    // sorts the list (stably) and takes it over
+    pub fn from_list(mut list: LinkedList<T>, cmp: C) -> Self {
+        list.sort_by(|a, b| cmp.compare(a, b));
+        SortedList { list, cmp }
+    }
+}
+
+impl<T: Ord> Default for SortedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, C: Clone> Clone for SortedList<T, C> {
+    fn clone(&self) -> Self {
+        SortedList {
+            list: self.list.clone(),
+            cmp: self.cmp.clone(),
+        }
+    }
+}
+
+impl<T, C: Comparator<T>> Extend<T> for SortedList<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+// collecting goes through a plain list and one sort, which beats inserting one at a time
+impl<T: Ord> FromIterator<T> for SortedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_list(iter.into_iter().collect(), NaturalOrder)
+    }
+}
+
+impl<T: Debug, C> Debug for SortedList<T, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.list.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, C> PartialEq for SortedList<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+impl<T: Eq, C> Eq for SortedList<T, C> {}
+
+impl<'a, T, C: Comparator<T>> IntoIterator for &'a SortedList<T, C> {
+    type IntoIter = sixth::Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, C> IntoIterator for SortedList<T, C> {
+    type IntoIter = sixth::IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+pub struct Range<'a, T> {
+    iter: Take<Skip<sixth::Iter<'a, T>>>,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Range<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SortedList;
+    use crate::sixth::LinkedList;
+    use std::cmp::Ordering;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    fn contents<T: Clone, C: super::Comparator<T>>(list: &SortedList<T, C>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = SortedList::new();
+        assert_eq!(m.pop_min(), None);
+        assert_eq!(m.pop_max(), None);
+        for x in [5, 1, 4, 1, 3, 9, 2, 6] {
+            m.insert(x);
+        }
+        assert_eq!(contents(&m), &[1, 1, 2, 3, 4, 5, 6, 9]);
+        assert_eq!(m.min(), Some(&1));
+        assert_eq!(m.max(), Some(&9));
+        assert!(m.contains(&4));
+        assert!(!m.contains(&7));
+        assert!(!m.contains(&10));
+        assert_eq!(m.remove(&1), Some(1));
+        assert_eq!(m.remove(&7), None);
+        assert_eq!(m.remove(&9), Some(9));
+        assert_eq!(m.pop_min(), Some(1));
+        assert_eq!(m.pop_max(), Some(6));
+        assert_eq!(m.len(), 4);
+        assert_eq!(format!("{m:?}"), "[2, 3, 4, 5]");
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_stable_duplicates() {
+        // sorted by the number only, the letter says which one went in first
+        let mut m = SortedList::with_comparator(|a: &(u32, char), b: &(u32, char)| a.0.cmp(&b.0));
+        for x in [(2, 'a'), (1, 'a'), (2, 'b'), (3, 'a'), (1, 'b'), (2, 'c')] {
+            m.insert(x);
+        }
+        assert_eq!(
+            contents(&m),
+            &[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b'), (2, 'c'), (3, 'a')]
+        );
+        // removing takes the oldest of the equals
+        assert_eq!(m.remove(&(2, 'z')), Some((2, 'a')));
+        assert_eq!(m.pop_min(), Some((1, 'a')));
+        assert_eq!(m.pop_max(), Some((3, 'a')));
+
+        let mut other = SortedList::with_comparator(m.cmp);
+        other.extend([(1, 'x'), (2, 'x'), (4, 'x')]);
+        m.merge(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(
+            contents(&m),
+            &[(1, 'b'), (1, 'x'), (2, 'b'), (2, 'c'), (2, 'x'), (4, 'x')]
+        );
+    }
+
+    #[test]
+    fn test_custom_order() {
+        let mut m = SortedList::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        m.extend([3, 1, 2]);
+        assert_eq!(contents(&m), &[3, 2, 1]);
+        assert_eq!(m.pop_min(), Some(3));
+        // ranges follow the comparator's order too
+        assert_eq!(
+            m.range((Included(2), Included(1)))
+                .copied()
+                .collect::<Vec<_>>(),
+            &[2, 1]
+        );
+
+        let list: LinkedList<i32> = [4, 8, 6].into_iter().collect();
+        let by_desc = |a: &i32, b: &i32| -> Ordering { b.cmp(a) };
+        let m = SortedList::from_list(list, by_desc);
+        assert_eq!(contents(&m), &[8, 6, 4]);
+        assert_eq!(m.into_list().into_iter().collect::<Vec<_>>(), &[8, 6, 4]);
+    }
+
+    #[test]
+    fn test_range() {
+        let m: SortedList<i32> = [5, 3, 8, 1, 3, 9, 5].into_iter().collect();
+        assert_eq!(contents(&m), &[1, 3, 3, 5, 5, 8, 9]);
+        let r = |it: super::Range<'_, i32>| it.copied().collect::<Vec<_>>();
+        assert_eq!(r(m.range(3..8)), &[3, 3, 5, 5]);
+        assert_eq!(r(m.range(3..=8)), &[3, 3, 5, 5, 8]);
+        assert_eq!(r(m.range(4..)), &[5, 5, 8, 9]);
+        assert_eq!(r(m.range(..=3)), &[1, 3, 3]);
+        assert_eq!(r(m.range(..)), &[1, 3, 3, 5, 5, 8, 9]);
+        assert_eq!(r(m.range(6..7)), &[] as &[i32]);
+        assert_eq!(r(m.range((Included(8), Excluded(3)))), &[] as &[i32]);
+        assert_eq!(r(m.range(10..)), &[] as &[i32]);
+        assert_eq!(
+            m.range((Excluded(3), Unbounded))
+                .rev()
+                .copied()
+                .collect::<Vec<_>>(),
+            &[9, 8, 5, 5]
+        );
+        assert_eq!(m.range(3..8).len(), 4);
+    }
+
+    #[test]
+    fn test_priority_queue() {
+        let mut queue = SortedList::new();
+        let mut out = Vec::new();
+        for round in 0..4 {
+            queue.extend([round * 3 + 2, round, 10 - round]);
+            out.push(queue.pop_min().unwrap());
+        }
+        out.extend(std::iter::from_fn(|| queue.pop_max()));
+        assert_eq!(out, &[0, 1, 2, 2, 11, 10, 9, 8, 8, 7, 5, 3]);
+        assert_eq!(queue, SortedList::new());
+    }
+}
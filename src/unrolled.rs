@@ -0,0 +1,1280 @@
+// an unrolled linked list
+// sixth::LinkedList allocates one node per element, so walking a list of u8s means chasing a
+// pointer for every single byte. here every node holds up to N elements in an inline array, so we
+// chase one pointer per N elements and the elements of a node sit next to each other in memory.
+//
+// the price is keeping the nodes full enough, otherwise we are back to one element per node.
+// the rule is: every node holds at least N / 2 elements, unless it's the only node in the list.
+// - inserting into a full node splits it in two halves first
+// - after removing, or after any split or splice, the two nodes on either side of every place
+//   where we cut or glued get looked at together (fix_junction): if they fit in one node they
+//   become one node, if one of them is too small it borrows from the other
+//
+// elements of a node always live at elems[0..len], so inserting or removing in a node shifts the
+// elements after it. that's O(N), and N is small.
+//
+// cursors point at (node, offset in node). anything that moves elements between nodes gets told
+// where the cursor is (track) and fixes it up, so cursors survive splits and merges
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::{self, NonNull},
+};
+
+pub struct UnrolledList<T, const N: usize = 16> {
+    front: Link<T, N>,
+    back: Link<T, N>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+type Link<T, const N: usize> = Option<NonNull<Node<T, N>>>;
+
+// a spot in the list: the node and where in the node
+type Pos<T, const N: usize> = (NonNull<Node<T, N>>, usize);
+
+struct Node<T, const N: usize> {
+    prev: Link<T, N>,
+    next: Link<T, N>,
+    len: usize,
+    elems: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> Drop for Node<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.elems.as_mut_ptr().cast::<T>(),
+                self.len,
+            ));
+        }
+    }
+}
+
+// node level helpers, these only look at the nodes they are given
+//
+// SAFETY (for every function down to the impl): every node passed in is alive and nobody holds a
+// reference into its elements
+unsafe fn len<T, const N: usize>(node: NonNull<Node<T, N>>) -> usize {
+    unsafe { (*node.as_ptr()).len }
+}
+
+unsafe fn elems<T, const N: usize>(node: NonNull<Node<T, N>>) -> *mut T {
+    unsafe { (&raw mut (*node.as_ptr()).elems).cast::<T>() }
+}
+
+unsafe fn next_pos<T, const N: usize>((node, off): Pos<T, N>) -> Option<Pos<T, N>> {
+    unsafe {
+        if off + 1 < len(node) {
+            Some((node, off + 1))
+        } else {
+            (*node.as_ptr()).next.map(|next| (next, 0))
+        }
+    }
+}
+
+unsafe fn prev_pos<T, const N: usize>((node, off): Pos<T, N>) -> Option<Pos<T, N>> {
+    unsafe {
+        if off > 0 {
+            Some((node, off - 1))
+        } else {
+            (*node.as_ptr()).prev.map(|prev| (prev, len(prev) - 1))
+        }
+    }
+}
+
+// the node needs room
+unsafe fn insert_in<T, const N: usize>(
+    node: NonNull<Node<T, N>>,
+    off: usize,
+    elem: T,
+    track: &mut Option<Pos<T, N>>,
+) {
+    unsafe {
+        let base = elems(node);
+        let node_len = len(node);
+        ptr::copy(base.add(off), base.add(off + 1), node_len - off);
+        base.add(off).write(elem);
+        (*node.as_ptr()).len += 1;
+        if let Some((t, o)) = track
+            && *t == node
+            && *o >= off
+        {
+            *o += 1;
+        }
+    }
+}
+
+// nothing is tracked here, removing is always followed by working out where the cursor goes
+unsafe fn remove_in<T, const N: usize>(node: NonNull<Node<T, N>>, off: usize) -> T {
+    unsafe {
+        let base = elems(node);
+        let node_len = len(node);
+        let elem = base.add(off).read();
+        ptr::copy(base.add(off + 1), base.add(off), node_len - off - 1);
+        (*node.as_ptr()).len -= 1;
+        elem
+    }
+}
+
+// the first count elements of from go on the end of to, to needs the room
+unsafe fn move_to_back<T, const N: usize>(
+    from: NonNull<Node<T, N>>,
+    count: usize,
+    to: NonNull<Node<T, N>>,
+    track: &mut Option<Pos<T, N>>,
+) {
+    unsafe {
+        let (from_len, to_len) = (len(from), len(to));
+        ptr::copy_nonoverlapping(elems(from), elems(to).add(to_len), count);
+        ptr::copy(elems(from).add(count), elems(from), from_len - count);
+        (*from.as_ptr()).len -= count;
+        (*to.as_ptr()).len += count;
+        if let Some((t, o)) = track
+            && *t == from
+        {
+            if *o < count {
+                *t = to;
+                *o += to_len;
+            } else {
+                *o -= count;
+            }
+        }
+    }
+}
+
+// the last count elements of from go on the front of to, to needs the room
+unsafe fn move_to_front<T, const N: usize>(
+    from: NonNull<Node<T, N>>,
+    count: usize,
+    to: NonNull<Node<T, N>>,
+    track: &mut Option<Pos<T, N>>,
+) {
+    unsafe {
+        let (from_len, to_len) = (len(from), len(to));
+        ptr::copy(elems(to), elems(to).add(count), to_len);
+        ptr::copy_nonoverlapping(elems(from).add(from_len - count), elems(to), count);
+        (*from.as_ptr()).len -= count;
+        (*to.as_ptr()).len += count;
+        match track {
+            Some((t, o)) if *t == to => *o += count,
+            Some((t, o)) if *t == from && *o >= from_len - count => {
+                *t = to;
+                *o -= from_len - count;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<T, const N: usize> UnrolledList<T, N> {
+    // nodes are at least half full
+    const MIN: usize = N / 2;
+
+    pub fn new() -> Self {
+        const {
+            assert!(
+                N >= 2,
+                "unrolled list nodes need room for at least 2 elements"
+            )
+        };
+        Self {
+            front: None,
+            back: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    fn new_node() -> NonNull<Node<T, N>> {
+        unsafe {
+            NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                prev: None,
+                next: None,
+                len: 0,
+                elems: [const { MaybeUninit::uninit() }; N],
+            })))
+        }
+    }
+
+    // puts new in the list right after node, or at the front when node is None
+    unsafe fn link_after(&mut self, node: Link<T, N>, new: NonNull<Node<T, N>>) {
+        unsafe {
+            let next = match node {
+                Some(node) => (*node.as_ptr()).next.replace(new),
+                None => self.front.replace(new),
+            };
+            (*new.as_ptr()).prev = node;
+            (*new.as_ptr()).next = next;
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(new),
+                None => self.back = Some(new),
+            }
+        }
+    }
+
+    // takes an empty node out of the list and frees it
+    unsafe fn free_node(&mut self, node: NonNull<Node<T, N>>) {
+        unsafe {
+            debug_assert_eq!(len(node), 0);
+            let boxed_node = Box::from_raw(node.as_ptr());
+            match boxed_node.prev {
+                Some(prev) => (*prev.as_ptr()).next = boxed_node.next,
+                None => self.front = boxed_node.next,
+            }
+            match boxed_node.next {
+                Some(next) => (*next.as_ptr()).prev = boxed_node.prev,
+                None => self.back = boxed_node.prev,
+            }
+        }
+    }
+
+    // moves node[at..] into a new node right after it, and returns the new node
+    unsafe fn split_node(
+        &mut self,
+        node: NonNull<Node<T, N>>,
+        at: usize,
+        track: &mut Option<Pos<T, N>>,
+    ) -> NonNull<Node<T, N>> {
+        unsafe {
+            let new = Self::new_node();
+            self.link_after(Some(node), new);
+            move_to_front(node, len(node) - at, new, track);
+            new
+        }
+    }
+
+    // puts elem at node[off], splitting the node first if it is full. returns where elem ended up
+    unsafe fn insert_at(
+        &mut self,
+        node: NonNull<Node<T, N>>,
+        off: usize,
+        elem: T,
+        track: &mut Option<Pos<T, N>>,
+    ) -> Pos<T, N> {
+        unsafe {
+            let (mut node, mut off) = (node, off);
+            if len(node) == N {
+                let half = N.div_ceil(2);
+                let new = self.split_node(node, half, track);
+                if off > half {
+                    node = new;
+                    off -= half;
+                }
+            }
+            insert_in(node, off, elem, track);
+            self.len += 1;
+            (node, off)
+        }
+    }
+
+    // left and the node after it, if there is one, get looked at together. if they fit in one
+    // node they become one node, otherwise whichever one is too small borrows from the other.
+    // returns the node that now holds the end of what was the right node
+    unsafe fn fix_junction(
+        &mut self,
+        left: NonNull<Node<T, N>>,
+        track: &mut Option<Pos<T, N>>,
+    ) -> NonNull<Node<T, N>> {
+        unsafe {
+            let Some(right) = (*left.as_ptr()).next else {
+                return left;
+            };
+            let (left_len, right_len) = (len(left), len(right));
+            if left_len + right_len <= N {
+                move_to_back(right, right_len, left, track);
+                self.free_node(right);
+                left
+            } else {
+                // together they are more than N, so the other one can spare enough
+                if left_len < Self::MIN {
+                    move_to_back(right, Self::MIN - left_len, left, track);
+                } else if right_len < Self::MIN {
+                    move_to_front(left, Self::MIN - right_len, right, track);
+                }
+                right
+            }
+        }
+    }
+
+    // node just lost elements, get it back in shape
+    unsafe fn fix_node(&mut self, node: NonNull<Node<T, N>>, track: &mut Option<Pos<T, N>>) {
+        unsafe {
+            if let Some(prev) = (*node.as_ptr()).prev {
+                self.fix_junction(prev, track);
+            } else if (*node.as_ptr()).next.is_some() {
+                self.fix_junction(node, track);
+            } else if len(node) == 0 {
+                self.free_node(node);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let front = match self.front {
+                Some(front) => front,
+                None => {
+                    let new = Self::new_node();
+                    self.link_after(None, new);
+                    new
+                }
+            };
+            self.insert_at(front, 0, elem, &mut None);
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let back = match self.back {
+                Some(back) => back,
+                None => {
+                    let new = Self::new_node();
+                    self.link_after(None, new);
+                    new
+                }
+            };
+            self.insert_at(back, len(back), elem, &mut None);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe {
+            let front = self.front?;
+            let elem = remove_in(front, 0);
+            self.len -= 1;
+            self.fix_node(front, &mut None);
+            Some(elem)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        unsafe {
+            let back = self.back?;
+            let elem = remove_in(back, len(back) - 1);
+            self.len -= 1;
+            self.fix_node(back, &mut None);
+            Some(elem)
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.front.map(|node| &*elems(node)) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.front.map(|node| &mut *elems(node)) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.back.map(|node| &*elems(node).add(len(node) - 1)) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.back.map(|node| &mut *elems(node).add(len(node) - 1)) }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // moves all of other's nodes onto our back and fixes up where they meet
+    pub fn append(&mut self, other: &mut Self) {
+        let other = std::mem::take(other);
+        let Some(other_front) = other.front else {
+            return;
+        };
+        unsafe {
+            match self.back {
+                Some(back) => {
+                    (*back.as_ptr()).next = Some(other_front);
+                    (*other_front.as_ptr()).prev = Some(back);
+                    self.back = other.back;
+                    self.len += other.len;
+                    self.fix_junction(back, &mut None);
+                }
+                None => {
+                    self.front = other.front;
+                    self.back = other.back;
+                    self.len = other.len;
+                }
+            }
+        }
+        std::mem::forget(other);
+    }
+}
+
+impl<T, const N: usize> Default for UnrolledList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for UnrolledList<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for UnrolledList<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for UnrolledList<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for UnrolledList<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for UnrolledList<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for UnrolledList<T, N> {}
+
+impl<T: PartialOrd, const N: usize> PartialOrd for UnrolledList<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, const N: usize> Ord for UnrolledList<T, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash, const N: usize> Hash for UnrolledList<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+// whole nodes at a time, Node's Drop takes care of the elements
+impl<T, const N: usize> Drop for UnrolledList<T, N> {
+    fn drop(&mut self) {
+        while let Some(node) = self.front {
+            unsafe {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                self.front = boxed_node.next;
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T, const N: usize = 16> {
+    front: Option<Pos<T, N>>,
+    back: Option<Pos<T, N>>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<T, const N: usize> UnrolledList<T, N> {
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        unsafe {
+            Iter {
+                front: self.front.map(|node| (node, 0)),
+                back: self.back.map(|node| (node, len(node) - 1)),
+                len: self.len,
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a UnrolledList<T, N> {
+    type IntoIter = Iter<'a, T, N>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|(node, off)| unsafe {
+                self.len -= 1;
+                self.front = next_pos((node, off));
+                &*elems(node).add(off)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for Iter<'a, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|(node, off)| unsafe {
+                self.len -= 1;
+                self.back = prev_pos((node, off));
+                &*elems(node).add(off)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for Iter<'a, T, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T, const N: usize = 16> {
+    front: Option<Pos<T, N>>,
+    back: Option<Pos<T, N>>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<T, const N: usize> UnrolledList<T, N> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        unsafe {
+            IterMut {
+                front: self.front.map(|node| (node, 0)),
+                back: self.back.map(|node| (node, len(node) - 1)),
+                len: self.len,
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut UnrolledList<T, N> {
+    type IntoIter = IterMut<'a, T, N>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|(node, off)| unsafe {
+                self.len -= 1;
+                self.front = next_pos((node, off));
+                &mut *elems(node).add(off)
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for IterMut<'a, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|(node, off)| unsafe {
+                self.len -= 1;
+                self.back = prev_pos((node, off));
+                &mut *elems(node).add(off)
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const N: usize> ExactSizeIterator for IterMut<'a, T, N> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T, const N: usize = 16> {
+    list: UnrolledList<T, N>,
+}
+
+impl<T, const N: usize> IntoIterator for UnrolledList<T, N> {
+    type IntoIter = IntoIter<T, N>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// cursors, with the same ghost between back and front as sixth
+pub struct CursorMut<'a, T, const N: usize = 16> {
+    cur: Option<Pos<T, N>>,
+    list: &'a mut UnrolledList<T, N>,
+    index: Option<usize>,
+}
+
+impl<T, const N: usize> UnrolledList<T, N> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, N> {
+        CursorMut {
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, N> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, N> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+}
+
+impl<'a, T, const N: usize> CursorMut<'a, T, N> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { next_pos(cur) };
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() += 1;
+            } else {
+                self.index = None;
+            }
+        } else if let Some(front) = self.list.front {
+            self.cur = Some((front, 0));
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            self.cur = unsafe { prev_pos(cur) };
+            if self.cur.is_some() {
+                *self.index.as_mut().unwrap() -= 1;
+            } else {
+                self.index = None;
+            }
+        } else if let Some(back) = self.list.back {
+            self.cur = Some((back, unsafe { len(back) } - 1));
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|(node, off)| &mut *elems(node).add(off)) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            let next = match self.cur {
+                Some(cur) => next_pos(cur),
+                None => self.list.front.map(|front| (front, 0)),
+            };
+            next.map(|(node, off)| &mut *elems(node).add(off))
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(cur) => prev_pos(cur),
+                None => self.list.back.map(|back| (back, len(back) - 1)),
+            };
+            prev.map(|(node, off)| &mut *elems(node).add(off))
+        }
+    }
+
+    // on the ghost these are push_back and push_front, like sixth
+    pub fn insert_before(&mut self, elem: T) {
+        match self.cur {
+            Some((node, off)) => unsafe {
+                self.list.insert_at(node, off, elem, &mut self.cur);
+                *self.index.as_mut().unwrap() += 1;
+            },
+            None => self.list.push_back(elem),
+        }
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        match self.cur {
+            Some((node, off)) => unsafe {
+                self.list.insert_at(node, off + 1, elem, &mut self.cur);
+            },
+            None => self.list.push_front(elem),
+        }
+    }
+
+    // takes the current element out and moves on to the next one, which takes over our index
+    pub fn remove_current(&mut self) -> Option<T> {
+        let (node, off) = self.cur?;
+        unsafe {
+            let elem = remove_in(node, off);
+            self.list.len -= 1;
+            self.cur = if off < len(node) {
+                Some((node, off))
+            } else {
+                (*node.as_ptr()).next.map(|next| (next, 0))
+            };
+            self.list.fix_node(node, &mut self.cur);
+            if self.cur.is_none() {
+                self.index = None;
+            }
+            Some(elem)
+        }
+    }
+
+    // split_before and split_after first split our node so the cut falls between two nodes,
+    // then fix up the node on each side of the cut, each in its own list now
+    pub fn split_before(&mut self) -> UnrolledList<T, N> {
+        let Some((node, off)) = self.cur else {
+            return std::mem::take(self.list);
+        };
+        let list = &mut *self.list;
+        unsafe {
+            let node = if off > 0 {
+                list.split_node(node, off, &mut self.cur)
+            } else {
+                node
+            };
+            let mut before = UnrolledList::new();
+            if let Some(before_back) = (*node.as_ptr()).prev.take() {
+                (*before_back.as_ptr()).next = None;
+                before.front = list.front;
+                before.back = Some(before_back);
+                before.len = self.index.unwrap();
+                list.front = Some(node);
+                list.len -= before.len;
+                before.fix_node(before_back, &mut None);
+                list.fix_node(node, &mut self.cur);
+            }
+            self.index = Some(0);
+            before
+        }
+    }
+
+    pub fn split_after(&mut self) -> UnrolledList<T, N> {
+        let Some((node, off)) = self.cur else {
+            return std::mem::take(self.list);
+        };
+        let list = &mut *self.list;
+        unsafe {
+            if off + 1 < len(node) {
+                list.split_node(node, off + 1, &mut self.cur);
+            }
+            let mut after = UnrolledList::new();
+            if let Some(after_front) = (*node.as_ptr()).next.take() {
+                (*after_front.as_ptr()).prev = None;
+                after.front = Some(after_front);
+                after.back = list.back;
+                after.len = list.len - self.index.unwrap() - 1;
+                list.back = Some(node);
+                list.len -= after.len;
+                after.fix_node(after_front, &mut None);
+                list.fix_node(node, &mut self.cur);
+            }
+            after
+        }
+    }
+
+    // glues input's nodes in at a node boundary (splitting our node to make one if needed),
+    // then fixes up both places where the lists meet. on the ghost splice_before goes on the back
+    // and splice_after on the front, like sixth
+    pub fn splice_before(&mut self, input: UnrolledList<T, N>) {
+        // nothing to glue in, so don't go splitting our node
+        if input.is_empty() {
+            return;
+        }
+        let input_len = input.len;
+        match self.cur {
+            Some((node, off)) => unsafe {
+                let node = if off > 0 {
+                    self.list.split_node(node, off, &mut self.cur)
+                } else {
+                    node
+                };
+                self.splice_between((*node.as_ptr()).prev, input);
+                *self.index.as_mut().unwrap() += input_len;
+            },
+            None => self.list.append(&mut { input }),
+        }
+    }
+
+    pub fn splice_after(&mut self, input: UnrolledList<T, N>) {
+        if input.is_empty() {
+            return;
+        }
+        match self.cur {
+            Some((node, off)) => unsafe {
+                if off + 1 < len(node) {
+                    self.list.split_node(node, off + 1, &mut self.cur);
+                }
+                self.splice_between(Some(node), input);
+            },
+            None => unsafe { self.splice_between(None, input) },
+        }
+    }
+
+    // input's nodes go right after node, or at the very front for None
+    unsafe fn splice_between(&mut self, node: Link<T, N>, mut input: UnrolledList<T, N>) {
+        let (Some(input_front), Some(input_back)) = (input.front.take(), input.back.take()) else {
+            return;
+        };
+        let list = &mut *self.list;
+        unsafe {
+            let next = match node {
+                Some(node) => (*node.as_ptr()).next.replace(input_front),
+                None => list.front.replace(input_front),
+            };
+            (*input_front.as_ptr()).prev = node;
+            (*input_back.as_ptr()).next = next;
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(input_back),
+                None => list.back = Some(input_back),
+            }
+            list.len += std::mem::take(&mut input.len);
+
+            // if input was a single node it might have been merged away in the first fix
+            let mut left = input_back;
+            if let Some(node) = node {
+                let fixed = list.fix_junction(node, &mut self.cur);
+                if input_front == input_back {
+                    left = fixed;
+                }
+            }
+            list.fix_junction(left, &mut self.cur);
+        }
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send, const N: usize> Send for UnrolledList<T, N> {}
+unsafe impl<T: Sync, const N: usize> Sync for UnrolledList<T, N> {}
+
+unsafe impl<'a, T: Sync, const N: usize> Send for Iter<'a, T, N> {}
+unsafe impl<'a, T: Sync, const N: usize> Sync for Iter<'a, T, N> {}
+
+unsafe impl<'a, T: Send, const N: usize> Send for IterMut<'a, T, N> {}
+unsafe impl<'a, T: Sync, const N: usize> Sync for IterMut<'a, T, N> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<UnrolledList<i32>>();
+    is_sync::<UnrolledList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    fn unrolled_list_covariant<'a, T>(x: UnrolledList<&'static T>) -> UnrolledList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{UnrolledList, len};
+    use crate::rng::TestRng;
+
+    // small nodes so a handful of elements already needs several of them
+    type List = UnrolledList<u32, 4>;
+
+    fn list_from(v: &[u32]) -> List {
+        v.iter().cloned().collect()
+    }
+
+    fn contents<const N: usize>(list: &UnrolledList<u32, N>) -> Vec<u32> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = List::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.front(), Some(&2));
+        assert_eq!(m.back(), Some(&3));
+        *m.front_mut().unwrap() = 20;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(20));
+        assert!(m.is_empty());
+        check_nodes(&m);
+
+        for i in 0..20 {
+            m.push_back(i);
+            m.push_front(100 + i);
+            check_nodes(&m);
+        }
+        assert_eq!(m.len(), 40);
+        for i in (0..20).rev() {
+            assert_eq!(m.pop_back(), Some(i));
+            assert_eq!(m.pop_front(), Some(100 + i));
+            check_nodes(&m);
+        }
+        assert!(m.is_empty());
+        assert_eq!(m.front, None);
+        assert_eq!(m.back, None);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m: List = (0..10).collect();
+        assert_eq!(contents(&m), (0..10).collect::<Vec<_>>());
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            (0..10).rev().collect::<Vec<_>>()
+        );
+        let mut it = m.iter();
+        assert_eq!(it.size_hint(), (10, Some(10)));
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&9));
+        assert_eq!(it.len(), 8);
+
+        for x in m.iter_mut() {
+            *x *= 2;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.next_back(), Some(&mut 18));
+        assert_eq!(it.next(), Some(&mut 0));
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(18));
+        assert_eq!(it.len(), 8);
+        assert_eq!(format!("{:?}", list_from(&[1, 2, 3])), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_traits() {
+        let a = list_from(&[1, 2, 3]);
+        let b = list_from(&[1, 2, 4]);
+        assert!(a != b);
+        assert!(a < b);
+        assert_eq!(a, a.clone());
+        let mut map = std::collections::HashMap::new();
+        map.insert(a.clone(), "a");
+        assert_eq!(map.get(&a), Some(&"a"));
+
+        let mut c = list_from(&[1, 2, 3, 4, 5]);
+        c.append(&mut list_from(&[6, 7]));
+        check_nodes(&c);
+        assert_eq!(contents(&c), &[1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_drops_elements() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+        let mut m: UnrolledList<Rc<()>, 4> = (0..10).map(|_| rc.clone()).collect();
+        m.pop_front();
+        {
+            let mut cursor = m.cursor_front_mut();
+            cursor.move_next();
+            cursor.remove_current();
+        }
+        assert_eq!(Rc::strong_count(&rc), 9);
+        drop(m);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_cursor_move_peek() {
+        let mut m = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.index(), Some(4));
+        for i in (0..5).rev() {
+            assert_eq!(
+                cursor.peek_prev(),
+                Some(&mut (i as u32)).filter(|&&mut i| i > 0)
+            );
+            assert_eq!(cursor.current(), Some(&mut (i as u32 + 1)));
+            cursor.move_prev();
+        }
+        assert_eq!(cursor.index(), None);
+        assert_eq!(m.cursor_back_mut().current(), Some(&mut 6));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert() {
+        // same story as sixth's test
+        let mut m = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        check_nodes(&m);
+        assert_eq!(contents(&m), &[7, 1, 8, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        check_nodes(&m);
+        assert_eq!(contents(&m), &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]);
+
+        let mut cursor = m.cursor_mut();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(7));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        check_nodes(&m);
+        assert_eq!(contents(&m), &[1, 8, 2, 3, 4, 5, 6]);
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(100);
+        cursor.insert_after(200);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        let after = cursor.split_after();
+        assert_eq!(cursor.current(), Some(&mut 8));
+        check_nodes(&m);
+        check_nodes(&before);
+        check_nodes(&after);
+        assert_eq!(contents(&before), &[1, 100]);
+        assert_eq!(contents(&m), &[8]);
+        assert_eq!(contents(&after), &[200, 2, 3, 4, 5, 6]);
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        check_nodes(&m);
+        assert_eq!(contents(&m), &[1, 100, 8, 200, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_cursor_random_against_vec() {
+        let mut rng = TestRng(0x9E3779B97F4A7C15);
+        let mut m = List::new();
+        let mut v: Vec<u32> = Vec::new();
+        // the cursor's spot in v, v.len() is the ghost
+        let mut at = 0;
+        for step in 0..3000u32 {
+            {
+                let mut cursor = m.cursor_mut();
+                if at < v.len() {
+                    for _ in 0..=at {
+                        cursor.move_next();
+                    }
+                }
+                match rng.below(9) {
+                    0 => {
+                        cursor.move_next();
+                        at = if at == v.len() { 0 } else { at + 1 };
+                        if v.is_empty() {
+                            at = 0;
+                        }
+                    }
+                    1 => {
+                        cursor.move_prev();
+                        at = if at == 0 { v.len() } else { at - 1 };
+                    }
+                    2 => {
+                        cursor.insert_before(step);
+                        if at == v.len() {
+                            v.push(step);
+                            at += 1;
+                        } else {
+                            v.insert(at, step);
+                            at += 1;
+                        }
+                    }
+                    3 => {
+                        cursor.insert_after(step);
+                        if at == v.len() {
+                            v.insert(0, step);
+                            at += 1;
+                        } else {
+                            v.insert(at + 1, step);
+                        }
+                    }
+                    4 => {
+                        let removed = cursor.remove_current();
+                        if at < v.len() {
+                            assert_eq!(removed, Some(v.remove(at)));
+                        } else {
+                            assert_eq!(removed, None);
+                        }
+                    }
+                    5 | 6 => {
+                        let n = rng.below(12);
+                        let input: List = (0..n as u32).map(|i| 10_000 * step + i).collect();
+                        let items: Vec<u32> = input.iter().cloned().collect();
+                        if rng.below(2) == 0 {
+                            cursor.splice_before(input);
+                            let spot = at;
+                            v.splice(spot..spot, items);
+                            at += n;
+                        } else {
+                            cursor.splice_after(input);
+                            let spot = if at == v.len() { 0 } else { at + 1 };
+                            v.splice(spot..spot, items);
+                            if at == v.len() - n {
+                                at = v.len();
+                            }
+                        }
+                    }
+                    _ => {
+                        // split and put it back together
+                        if rng.below(2) == 0 {
+                            let before = cursor.split_before();
+                            check_nodes(&before);
+                            assert_eq!(cursor.index(), (at < v.len()).then_some(0));
+                            cursor.splice_before(before);
+                        } else {
+                            let after = cursor.split_after();
+                            check_nodes(&after);
+                            cursor.splice_after(after);
+                        }
+                    }
+                }
+                assert_eq!(cursor.index(), (at < v.len()).then_some(at));
+                assert_eq!(cursor.current().copied(), v.get(at).copied());
+            }
+            check_nodes(&m);
+            assert_eq!(m.len(), v.len());
+        }
+        assert_eq!(contents(&m), v);
+    }
+
+    // links agree both ways, node lens add up, and every node is between half full and full
+    // unless it is the only one
+    fn check_nodes<T, const N: usize>(list: &UnrolledList<T, N>) {
+        let mut total = 0;
+        let mut prev = None;
+        let mut cur = list.front;
+        let mut nodes = 0;
+        unsafe {
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).prev, prev);
+                let node_len = len(node);
+                assert!(node_len <= N);
+                assert!(node_len > 0);
+                total += node_len;
+                nodes += 1;
+                prev = Some(node);
+                cur = (*node.as_ptr()).next;
+            }
+            assert_eq!(list.back, prev);
+            assert_eq!(total, list.len);
+            if nodes > 1 {
+                let mut cur = list.front;
+                while let Some(node) = cur {
+                    assert!(len(node) >= N / 2, "node with {} of {N}", len(node));
+                    cur = (*node.as_ptr()).next;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,975 @@
+// an xor linked list
+// a doubly linked list node has two pointers, prev and next. the old trick is to store only
+// prev ^ next in one word: if you know where you came from you can get where you're going,
+// because (prev ^ next) ^ prev == next. so walking works in both directions as long as we always
+// carry two nodes around, the one we are on and the one next to it. the ends of the list are
+// next to nothing (address 0), so from the front or back we can always get going.
+//
+// the node is one word smaller than sixth's, which for a list of u32s is 16 bytes instead of 24.
+//
+// rust (and miri) care a lot about where a pointer came from, and an address we made up out of
+// two xor'd ones didn't come from anywhere. so every pointer that goes into a link gets exposed
+// (expose_provenance) and every pointer we get back out of a link is made with
+// with_exposed_provenance_mut, which tells the compiler "this is one of the ones we exposed".
+//
+// a nice bonus: nothing in the nodes says which way is forwards, so reversing the list is just
+// swapping front and back
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::{self, NonNull},
+};
+
+pub struct XorList<T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _boo: PhantomData<T>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    // addr(prev) ^ addr(next)
+    link: usize,
+    elem: T,
+}
+
+fn addr<T>(link: Link<T>) -> usize {
+    link.map_or(0, |node| node.as_ptr().expose_provenance())
+}
+
+fn from_addr<T>(addr: usize) -> Link<T> {
+    NonNull::new(ptr::with_exposed_provenance_mut(addr))
+}
+
+// the neighbour of node that isn't the given one
+//
+// SAFETY: node is alive and neighbour really is next to it (or None at an end)
+unsafe fn other<T>(node: NonNull<Node<T>>, neighbour: Link<T>) -> Link<T> {
+    unsafe { from_addr((*node.as_ptr()).link ^ addr(neighbour)) }
+}
+
+// node's neighbour old becomes new, old and new can be None
+//
+// SAFETY: node is alive and old really is next to it
+unsafe fn replace_neighbour<T>(node: NonNull<Node<T>>, old: Link<T>, new: Link<T>) {
+    unsafe {
+        (*node.as_ptr()).link ^= addr(old) ^ addr(new);
+    }
+}
+
+impl<T> XorList<T> {
+    pub fn new() -> Self {
+        Self {
+            front: None,
+            back: None,
+            len: 0,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                link: addr(self.front),
+                elem,
+            })));
+            match self.front {
+                Some(old) => replace_neighbour(old, None, Some(new)),
+                None => self.back = Some(new),
+            }
+            self.front = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        unsafe {
+            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
+                link: addr(self.back),
+                elem,
+            })));
+            match self.back {
+                Some(old) => replace_neighbour(old, None, Some(new)),
+                None => self.front = Some(new),
+            }
+            self.back = Some(new);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe {
+            self.front.map(|node| {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                let next = from_addr(boxed_node.link);
+                match next {
+                    Some(next) => replace_neighbour(next, Some(node), None),
+                    None => self.back = None,
+                }
+                self.front = next;
+                self.len -= 1;
+                boxed_node.elem
+            })
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        unsafe {
+            self.back.map(|node| {
+                let boxed_node = Box::from_raw(node.as_ptr());
+                let prev = from_addr(boxed_node.link);
+                match prev {
+                    Some(prev) => replace_neighbour(prev, Some(node), None),
+                    None => self.front = None,
+                }
+                self.back = prev;
+                self.len -= 1;
+                boxed_node.elem
+            })
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        unsafe { self.front.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.front.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        unsafe { self.back.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    // O(1), see the top of the file
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        unsafe {
+            match (self.back, other.front) {
+                (Some(back), Some(other_front)) => {
+                    replace_neighbour(back, None, Some(other_front));
+                    replace_neighbour(other_front, None, Some(back));
+                    self.back = other.back.take();
+                    other.front = None;
+                    self.len += std::mem::take(&mut other.len);
+                }
+                (None, _) => std::mem::swap(self, other),
+                (_, None) => {}
+            }
+        }
+    }
+}
+
+impl<T> Default for XorList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for XorList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> Extend<T> for XorList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for XorList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for XorList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for XorList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for XorList<T> {}
+
+impl<T: PartialOrd> PartialOrd for XorList<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for XorList<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for XorList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> Drop for XorList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+// every end of the iterator carries the node it's on and the one it came from
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    front_prev: Link<T>,
+    back: Link<T>,
+    back_next: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a T>,
+}
+
+impl<T> XorList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front,
+            front_prev: None,
+            back: self.back,
+            back_next: None,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a XorList<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = other(node, self.front_prev);
+                self.front_prev = Some(node);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = other(node, self.back_next);
+                self.back_next = Some(node);
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    front_prev: Link<T>,
+    back: Link<T>,
+    back_next: Link<T>,
+    len: usize,
+    _boo: PhantomData<&'a mut T>,
+}
+
+impl<T> XorList<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.front,
+            front_prev: None,
+            back: self.back,
+            back_next: None,
+            len: self.len,
+            _boo: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut XorList<T> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = other(node, self.front_prev);
+                self.front_prev = Some(node);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = other(node, self.back_next);
+                self.back_next = Some(node);
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+pub struct IntoIter<T> {
+    list: XorList<T>,
+}
+
+impl<T> IntoIterator for XorList<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+// the cursor carries the node before cur too, which is all it needs to go either way. on the
+// ghost prev is the back, so walking off the back and then back on again is free
+pub struct CursorMut<'a, T> {
+    prev: Link<T>,
+    cur: Link<T>,
+    list: &'a mut XorList<T>,
+    index: Option<usize>,
+}
+
+impl<T> XorList<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            prev: self.back,
+            cur: None,
+            list: self,
+            index: None,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = self.cursor_mut();
+        cursor.move_prev();
+        cursor
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // the node after cur, or the front on the ghost
+    fn next(&self) -> Link<T> {
+        match self.cur {
+            Some(cur) => unsafe { other(cur, self.prev) },
+            None => self.list.front,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        let next = self.next();
+        match self.cur {
+            Some(cur) => {
+                self.prev = Some(cur);
+                self.index = next.map(|_| self.index.unwrap() + 1);
+            }
+            None => {
+                self.prev = None;
+                self.index = next.map(|_| 0);
+            }
+        }
+        self.cur = next;
+    }
+
+    pub fn move_prev(&mut self) {
+        let prev = self.prev;
+        self.prev = match prev {
+            Some(prev) => unsafe { other(prev, self.cur) },
+            // back to the ghost, which sits after the back
+            None => self.list.back,
+        };
+        self.index = match (prev, self.index) {
+            (None, _) => None,
+            (Some(_), Some(index)) => Some(index - 1),
+            (Some(_), None) => Some(self.list.len - 1),
+        };
+        self.cur = prev;
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe { self.next().map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe { self.prev.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    // puts the chain first..=last in between a and b, which are next to each other (None being
+    // past an end of the list)
+    unsafe fn link_between(
+        &mut self,
+        a: Link<T>,
+        b: Link<T>,
+        first: NonNull<Node<T>>,
+        last: NonNull<Node<T>>,
+    ) {
+        unsafe {
+            match a {
+                Some(a) => replace_neighbour(a, b, Some(first)),
+                None => self.list.front = Some(first),
+            }
+            match b {
+                Some(b) => replace_neighbour(b, a, Some(last)),
+                None => self.list.back = Some(last),
+            }
+            replace_neighbour(first, None, a);
+            replace_neighbour(last, None, b);
+        }
+    }
+
+    // everything the cursor can get to cheaply is right here, so inserting, removing, splitting
+    // and splicing are all O(1)
+    pub fn splice_before(&mut self, mut input: XorList<T>) {
+        let (Some(first), Some(last)) = (input.front.take(), input.back.take()) else {
+            return;
+        };
+        let input_len = std::mem::take(&mut input.len);
+        unsafe {
+            // on the ghost, before is after the back
+            self.link_between(self.prev, self.cur, first, last);
+        }
+        self.prev = Some(last);
+        self.list.len += input_len;
+        if let Some(index) = self.index.as_mut() {
+            *index += input_len;
+        }
+    }
+
+    pub fn splice_after(&mut self, mut input: XorList<T>) {
+        let (Some(first), Some(last)) = (input.front.take(), input.back.take()) else {
+            return;
+        };
+        let input_len = std::mem::take(&mut input.len);
+        unsafe {
+            match self.cur {
+                Some(cur) => self.link_between(Some(cur), other(cur, self.prev), first, last),
+                // on the ghost, after is before the front
+                None => {
+                    self.link_between(None, self.list.front, first, last);
+                    if self.prev.is_none() {
+                        self.prev = Some(last);
+                    }
+                }
+            }
+        }
+        self.list.len += input_len;
+    }
+
+    pub fn insert_before(&mut self, elem: T) {
+        self.splice_before(Some(elem).into_iter().collect());
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        self.splice_after(Some(elem).into_iter().collect());
+    }
+
+    // takes the current node out and moves on to the next one, which takes over our index
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        unsafe {
+            let next = other(cur, self.prev);
+            let boxed_node = Box::from_raw(cur.as_ptr());
+            match self.prev {
+                Some(prev) => replace_neighbour(prev, Some(cur), next),
+                None => self.list.front = next,
+            }
+            match next {
+                Some(next) => replace_neighbour(next, Some(cur), self.prev),
+                None => self.list.back = self.prev,
+            }
+            self.list.len -= 1;
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+            Some(boxed_node.elem)
+        }
+    }
+
+    pub fn split_before(&mut self) -> XorList<T> {
+        let Some(cur) = self.cur else {
+            // we're on the ghost of what's left, an empty list, so there's no back to point at
+            self.prev = None;
+            return std::mem::take(self.list);
+        };
+        let Some(prev) = self.prev.take() else {
+            return XorList::new();
+        };
+        unsafe {
+            replace_neighbour(prev, Some(cur), None);
+            replace_neighbour(cur, Some(prev), None);
+        }
+        let before_len = self.index.replace(0).unwrap();
+        self.list.len -= before_len;
+        XorList {
+            front: self.list.front.replace(cur),
+            back: Some(prev),
+            len: before_len,
+            _boo: PhantomData,
+        }
+    }
+
+    pub fn split_after(&mut self) -> XorList<T> {
+        let Some(cur) = self.cur else {
+            self.prev = None;
+            return std::mem::take(self.list);
+        };
+        unsafe {
+            let Some(next) = other(cur, self.prev) else {
+                return XorList::new();
+            };
+            replace_neighbour(cur, Some(next), None);
+            replace_neighbour(next, Some(cur), None);
+            let after_len = self.list.len - self.index.unwrap() - 1;
+            self.list.len -= after_len;
+            XorList {
+                front: Some(next),
+                back: self.list.back.replace(cur),
+                len: after_len,
+                _boo: PhantomData,
+            }
+        }
+    }
+}
+
+// *const T and *mut T opt out of Sync and Send so we have to opt back in
+unsafe impl<T: Send> Send for XorList<T> {}
+unsafe impl<T: Sync> Sync for XorList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+#[allow(dead_code)]
+fn assert_properties() {
+    fn is_send<T: Send>() {}
+    fn is_sync<T: Sync>() {}
+
+    is_send::<XorList<i32>>();
+    is_sync::<XorList<i32>>();
+
+    is_send::<IntoIter<i32>>();
+    is_sync::<IntoIter<i32>>();
+
+    is_send::<Iter<i32>>();
+    is_sync::<Iter<i32>>();
+
+    is_send::<IterMut<i32>>();
+    is_sync::<IterMut<i32>>();
+
+    fn xor_list_covariant<'a, T>(x: XorList<&'static T>) -> XorList<&'a T> {
+        x
+    }
+    fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Node, XorList};
+
+    fn list_from<T: Clone>(v: &[T]) -> XorList<T> {
+        v.iter().cloned().collect()
+    }
+
+    fn contents<T: Clone>(list: &XorList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_node_is_smaller() {
+        // one link instead of two
+        assert_eq!(
+            std::mem::size_of::<Node<u32>>(),
+            2 * std::mem::size_of::<usize>()
+        );
+        assert_eq!(
+            std::mem::size_of::<Node<()>>(),
+            std::mem::size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = XorList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_back(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        m.push_front(1);
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.front(), Some(&1));
+        assert_eq!(m.back(), Some(&3));
+        *m.front_mut().unwrap() = 10;
+        *m.back_mut().unwrap() = 30;
+        assert_eq!(contents(&m), &[10, 2, 30]);
+        assert_eq!(m.pop_back(), Some(30));
+        assert_eq!(m.pop_front(), Some(10));
+        assert_eq!(m.pop_front(), Some(2));
+        assert!(m.is_empty());
+        assert_eq!(m.front(), None);
+        assert_eq!(m.back(), None);
+
+        m.extend(0..5);
+        m.reverse();
+        assert_eq!(contents(&m), &[4, 3, 2, 1, 0]);
+        m.push_front(5);
+        m.push_back(-1);
+        assert_eq!(m.pop_back(), Some(-1));
+        m.reverse();
+        assert_eq!(contents(&m), &[0, 1, 2, 3, 4, 5]);
+
+        let mut other = list_from(&[6, 7]);
+        m.append(&mut other);
+        assert!(other.is_empty());
+        other.append(&mut m);
+        assert!(m.is_empty());
+        assert_eq!(contents(&other), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        other.clear();
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m: XorList<i32> = (0..10).collect();
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            (0..10).rev().collect::<Vec<_>>()
+        );
+        let mut it = m.iter();
+        assert_eq!(it.size_hint(), (10, Some(10)));
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next_back(), Some(&9));
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next_back(), Some(&8));
+        assert_eq!(it.len(), 6);
+        assert_eq!(it.cloned().collect::<Vec<_>>(), &[2, 3, 4, 5, 6, 7]);
+
+        for x in m.iter_mut() {
+            *x *= 2;
+        }
+        let mut it = m.iter_mut();
+        assert_eq!(it.next_back(), Some(&mut 18));
+        assert_eq!(it.next(), Some(&mut 0));
+
+        let mut it = m.clone().into_iter();
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(18));
+        assert_eq!(it.len(), 8);
+        assert_eq!(format!("{:?}", list_from(&[1, 2, 3])), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_traits() {
+        let a = list_from(&[1, 2, 3]);
+        let b = list_from(&[1, 2, 4]);
+        assert!(a != b);
+        assert!(a < b);
+        assert_eq!(a, a.clone());
+        let mut map = std::collections::HashMap::new();
+        map.insert(a.clone(), "a");
+        assert_eq!(map.get(&a), Some(&"a"));
+    }
+
+    #[test]
+    fn test_cursor_move_peek() {
+        let mut m: XorList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.peek_next(), Some(&mut 6));
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
+        assert_eq!(cursor.index(), Some(4));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+
+        // all the way round both ways
+        let mut cursor = m.cursor_mut();
+        let mut seen = Vec::new();
+        for _ in 0..7 {
+            cursor.move_next();
+            seen.push(cursor.current().copied());
+        }
+        for _ in 0..7 {
+            cursor.move_prev();
+            seen.push(cursor.current().copied());
+        }
+        let forwards = [1, 2, 3, 4, 5, 6].map(Some);
+        assert_eq!(&seen[..6], &forwards);
+        assert_eq!(seen[6], None);
+        assert_eq!(seen[7], Some(6));
+        assert_eq!(seen[13], None);
+        assert_eq!(m.cursor_back_mut().current(), Some(&mut 6));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert() {
+        // same story as sixth's test
+        let mut m: XorList<u32> = list_from(&[1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.splice_before(Some(7).into_iter().collect());
+        cursor.splice_after(Some(8).into_iter().collect());
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(contents(&m), &[7, 1, 8, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.move_prev();
+        cursor.splice_before(Some(9).into_iter().collect());
+        cursor.splice_after(Some(10).into_iter().collect());
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 10));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 9));
+        assert_eq!(contents(&m), &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]);
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            &[9, 6, 5, 4, 3, 2, 8, 1, 7, 10]
+        );
+
+        let mut cursor = m.cursor_mut();
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(7));
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(9));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(10));
+        assert_eq!(contents(&m), &[1, 8, 2, 3, 4, 5, 6]);
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(100);
+        cursor.insert_after(200);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 8));
+        assert_eq!(cursor.peek_next(), Some(&mut 200));
+        assert_eq!(cursor.peek_prev(), Some(&mut 100));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.peek_prev(), None);
+        let after = cursor.split_after();
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(contents(&before), &[1, 100]);
+        assert_eq!(contents(&m), &[8]);
+        assert_eq!(contents(&after), &[200, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            after.iter().rev().cloned().collect::<Vec<_>>(),
+            &[6, 5, 4, 3, 2, 200]
+        );
+        assert_eq!(before.iter().rev().cloned().collect::<Vec<_>>(), &[100, 1]);
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.splice_after(after);
+        cursor.splice_before(before);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.remove_current(), Some(8));
+        assert_eq!(cursor.current(), Some(&mut 200));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 100));
+        assert_eq!(contents(&m), &[1, 100, 200, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            m.iter().rev().cloned().collect::<Vec<_>>(),
+            &[6, 5, 4, 3, 2, 200, 100, 1]
+        );
+        assert_eq!(m.len(), 8);
+    }
+
+    #[test]
+    fn test_cursor_on_ghost_of_empty_list() {
+        let mut m = XorList::new();
+        let mut cursor = m.cursor_mut();
+        cursor.insert_after(2);
+        cursor.insert_before(3);
+        cursor.insert_after(1);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(contents(&m), &[1, 2, 3]);
+        assert_eq!(m.iter().rev().cloned().collect::<Vec<_>>(), &[3, 2, 1]);
+
+        // splitting on the ghost takes everything, and the cursor keeps working on what's left
+        let mut cursor = m.cursor_mut();
+        let all = cursor.split_after();
+        cursor.insert_before(4);
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 4));
+        cursor.move_next();
+        let again = cursor.split_before();
+        cursor.insert_after(5);
+        assert_eq!(contents(&all), &[1, 2, 3]);
+        assert_eq!(contents(&again), &[4]);
+        assert_eq!(contents(&m), &[5]);
+    }
+
+    #[test]
+    fn test_drops() {
+        use std::rc::Rc;
+
+        let rc = Rc::new(());
+        let mut m: XorList<Rc<()>> = (0..10).map(|_| rc.clone()).collect();
+        {
+            let mut cursor = m.cursor_front_mut();
+            cursor.move_next();
+            cursor.remove_current();
+            let _ = cursor.split_after();
+        }
+        assert_eq!(Rc::strong_count(&rc), 3);
+        drop(m);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}